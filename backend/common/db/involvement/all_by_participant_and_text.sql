@@ -7,7 +7,7 @@ on O.uid = S.uid
 where
   I.participant = $1
 and
-  S.fulltext_english @@ websearch_to_tsquery($4)
+  S.fulltext @@ c_search_tsquery($4, null)
 and
   case
     when $2::integer is null then I.mode >= 1
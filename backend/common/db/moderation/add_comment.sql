@@ -0,0 +1,3 @@
+insert into c_moderation_comment (opportunity_id, author_id, body, from_status, to_status)
+values ($1, (select id from c_person where uid = $2), $3, $4, $5)
+returning id;
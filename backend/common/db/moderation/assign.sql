@@ -0,0 +1,4 @@
+insert into c_moderation (opportunity_id, assignee_id)
+values ($1, (select id from c_person where uid = $2))
+on conflict (opportunity_id) do update
+set assignee_id = excluded.assignee_id;
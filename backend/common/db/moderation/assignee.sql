@@ -0,0 +1,4 @@
+select p.uid
+from c_moderation m
+join c_person p on p.id = m.assignee_id
+where m.opportunity_id = $1;
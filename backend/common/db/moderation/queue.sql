@@ -0,0 +1,11 @@
+select
+  o.uid, o.slug, o.title, o.opp_partner as partner, pa.name as "partner_name?", o.submitted_by,
+  coalesce(m.status_since, o.updated) as "pending_since!",
+  a.uid as "assignee?", a.username as "assignee_name?"
+from c_opportunity o
+left join c_partner pa on pa.uid = o.opp_partner
+left join c_moderation m on m.opportunity_id = o.id
+left join c_person a on a.id = m.assignee_id
+where o.review_status = 'pending'
+and ($1::uuid is null or pa.prime = $1 or $1 = any(pa.authorized))
+order by "pending_since!", o.id;
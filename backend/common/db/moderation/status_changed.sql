@@ -0,0 +1,4 @@
+insert into c_moderation (opportunity_id, status_since)
+values ($1, current_timestamp)
+on conflict (opportunity_id) do update
+set status_since = current_timestamp;
@@ -0,0 +1,5 @@
+select c.id, p.uid as "author?", p.username as "author_name?", c.body, c.from_status, c.to_status, c.created
+from c_moderation_comment c
+left join c_person p on p.id = c.author_id
+where c.opportunity_id = $1
+order by c.created, c.id;
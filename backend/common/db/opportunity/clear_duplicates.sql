@@ -0,0 +1,5 @@
+delete from c_opportunity_duplicate
+where
+  status = 'pending'
+  and (first_id = $1 or second_id = $1)
+  and not (first_id = any($2::integer[]) or second_id = any($2::integer[]));
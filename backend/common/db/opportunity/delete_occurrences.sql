@@ -0,0 +1 @@
+delete from c_opportunity_occurrence where opportunity_id = $1;
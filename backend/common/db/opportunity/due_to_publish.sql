@@ -0,0 +1,4 @@
+select id as "id!"
+from c_opportunity
+where review_status = 'scheduled' and publish_at <= $1
+order by publish_at;
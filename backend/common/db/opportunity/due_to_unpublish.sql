@@ -0,0 +1,4 @@
+select id as "id!"
+from c_opportunity
+where review_status in ('publish', 'not_required', 'scheduled') and unpublish_at <= $1
+order by unpublish_at;
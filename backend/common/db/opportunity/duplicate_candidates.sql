@@ -0,0 +1,25 @@
+select
+  o.id as "id!",
+  similarity(a.title, o.title) as "title_similarity!",
+  case
+    when a.is_online and o.is_online then 0.0::float8
+    else st_distance(a.location_point, o.location_point)
+  end as "distance",
+  (
+    exists (
+      select 1
+      from c_opportunity_occurrence x join c_opportunity_occurrence y
+        on x.starts < coalesce(y.ends, y.starts) + $2::interval
+        and y.starts < coalesce(x.ends, x.starts) + $2::interval
+      where x.opportunity_id = a.id and y.opportunity_id = o.id
+    )
+    or (
+      not exists (select 1 from c_opportunity_occurrence x where x.opportunity_id = a.id)
+      and not exists (select 1 from c_opportunity_occurrence y where y.opportunity_id = o.id)
+    )
+  ) as "overlapping!"
+from c_opportunity a join c_opportunity o on o.title % a.title and o.id <> a.id
+where
+  a.id = $1
+  and not exists (select 1 from c_opportunity_redirect r where r.slug = a.slug)
+  and not exists (select 1 from c_opportunity_redirect r where r.slug = o.slug);
@@ -0,0 +1,9 @@
+select
+  d.id, d.score, d.title_similarity, d.distance, d.overlapping, d.status, d.detected,
+  a.uid as first_uid, a.slug as first_slug, a.title as first_title, a.partner_name as first_partner_name,
+  b.uid as second_uid, b.slug as second_slug, b.title as second_title, b.partner_name as second_partner_name
+from c_opportunity_duplicate d
+  join c_opportunity a on d.first_id = a.id
+  join c_opportunity b on d.second_id = b.id
+where d.status = $1
+order by d.score desc, d.id;
@@ -0,0 +1,9 @@
+select
+  r.id,
+  r.created,
+  r.author_kind,
+  r.author_uid,
+  r.source,
+  r.snapshot
+from c_opportunity_revision r join c_opportunity o on r.opportunity_id = o.id
+where o.uid = $1 and r.id = $2;
@@ -0,0 +1 @@
+select exists(select 1 from c_opportunity where parent_uid = $1) as "exists";
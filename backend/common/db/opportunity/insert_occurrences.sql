@@ -0,0 +1,3 @@
+insert into c_opportunity_occurrence (opportunity_id, starts, ends)
+select $1, t.starts, t.ends
+from unnest($2::timestamptz[], $3::timestamptz[]) as t(starts, ends);
@@ -0,0 +1,10 @@
+insert into c_opportunity_revision (opportunity_id, author_kind, author_uid, source, snapshot)
+select $1, $2, $3, $4, $5
+where $5::jsonb is distinct from (
+  select snapshot
+  from c_opportunity_revision
+  where opportunity_id = $1
+  order by id desc
+  limit 1
+)
+returning id;
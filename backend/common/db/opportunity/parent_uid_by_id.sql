@@ -0,0 +1 @@
+select parent_uid from c_opportunity where id = $1 limit 1;
@@ -0,0 +1,10 @@
+with involvements as (
+  delete from c_involvement where opportunity = $2
+), likes as (
+  delete from c_opportunity_like where opportunity_id = $1
+), reviews as (
+  delete from c_opportunity_review where opportunity_id = $1
+), overlays as (
+  delete from c_opportunity_overlay where opportunity_id = $1
+)
+delete from c_opportunity where id = $1;
@@ -0,0 +1,62 @@
+with origin as (
+  select case
+    when $4::float8 is null or $5::float8 is null then null
+    else st_setsrid(st_point($4::float8, $5::float8), 4326)::geography
+  end as point
+), wanted as (
+  select
+    $1::text[] as topics,
+    $2::text[] as tags,
+    case
+      when $3::text is null then null
+      else replace(plainto_tsquery('english', $3::text)::text, '&', '|')::tsquery
+    end as text
+)
+select
+  s.uid as "uid!",
+  -- Only needed for comparing with the requested text
+  case
+    when wanted.text is null then ''
+    else concat_ws(' ', s.title, s.short_desc, s.description)
+  end as "words!",
+  s.tags as "tags!",
+  s.topics as "topics!",
+  m.distance as "distance",
+  (
+    select min(occ.starts)
+    from c_opportunity_occurrence occ
+    where occ.opportunity_id = s.opp_id and coalesce(occ.ends, occ.starts) > now()
+  ) as "next_occurrence"
+from c_opportunity_search s
+  join c_opportunity o on o.id = s.opp_id
+  cross join origin
+  cross join wanted
+  cross join lateral (
+    select
+      case
+        when s.location_polygon is not null then st_distance(s.location_polygon, origin.point, false)
+        when s.location_point is not null then st_distance(s.location_point, origin.point, false)
+        else null
+      end as distance,
+      (select count(*) from unnest(s.topics) topic where topic = any(wanted.topics)) as topics,
+      (select count(distinct lower(tag)) from unnest(s.tags) tag where lower(tag) = any(wanted.tags)) as tags,
+      coalesce(ts_rank(s.fulltext, wanted.text, 32), 0) as text
+  ) m
+where
+  c_opportunity_is_current(o)
+  and s.entity_type in ('opportunity', 'attraction')
+  and (
+    m.topics > 0
+    or m.tags > 0
+    or (wanted.text is not null and s.fulltext @@ wanted.text)
+    or (origin.point is not null and st_dwithin(coalesce(s.location_polygon, s.location_point), origin.point, $6::float8, false))
+  )
+-- A rough version of the ranking in recommend.rs, so that the best
+-- candidates are the ones kept when there are more than $7
+order by
+  $8::float8 * m.topics / greatest(cardinality(wanted.topics), 1)
+  + $9::float8 * m.tags / greatest(cardinality(wanted.tags), 1)
+  + $10::float8 * m.text
+  + $11::float8 * coalesce($12::float8 / ($12::float8 + m.distance), 0) desc,
+  s.uid
+limit $7;
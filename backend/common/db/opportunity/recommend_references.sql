@@ -0,0 +1,9 @@
+select
+  s.uid as "uid!",
+  s.slug as "slug!",
+  s.title as "title!",
+  s.image_url as "image_url!",
+  s.short_desc as "short_desc!"
+from unnest($1::uuid[]) with ordinality as page(uid, position)
+  join c_opportunity_search s on s.uid = page.uid
+order by page.position;
@@ -0,0 +1,2 @@
+select id from c_opportunity
+where (recurrence <> 'once' and recurrence_until is null) or recurrence_until > now();
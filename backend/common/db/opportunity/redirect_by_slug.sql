@@ -0,0 +1,4 @@
+select o.slug
+from c_opportunity_redirect r join c_opportunity o on r.opportunity_id = o.id
+where lower(r.slug) = lower($1::text)
+limit 1;
@@ -0,0 +1,7 @@
+select id from c_opportunity
+where parent_uid = $1
+  and accepted = true
+  and withdrawn = false
+  and trashed_at is null
+  and coalesce(review_status, 'not_required') in ('publish', 'not_required')
+order by id;
@@ -0,0 +1,16 @@
+select
+  uid,
+  ts_headline(
+    coalesce(c_language_regconfig($2), 'english'::regconfig),
+    title,
+    c_search_tsquery($1, $2),
+    'HighlightAll=true, StartSel=<mark>, StopSel=</mark>'
+  ) as "title!",
+  ts_headline(
+    coalesce(c_language_regconfig($2), 'english'::regconfig),
+    short_desc || ' ' || description,
+    c_search_tsquery($1, $2),
+    'MaxFragments=2, MinWords=8, MaxWords=24, FragmentDelimiter=" … ", StartSel=<mark>, StopSel=</mark>'
+  ) as "snippet!"
+from c_opportunity
+where uid = any($3);
@@ -0,0 +1 @@
+update c_opportunity set trashed_at = now() where id = $1 returning trashed_at;
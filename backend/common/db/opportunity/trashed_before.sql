@@ -0,0 +1 @@
+select id, uid from c_opportunity where trashed_at < $1 order by trashed_at;
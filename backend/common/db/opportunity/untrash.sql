@@ -0,0 +1 @@
+update c_opportunity set trashed_at = null where id = $1;
@@ -5,11 +5,14 @@ update c_opportunity set
   entity_type = $14, opp_venue = $15, opp_descriptor = $16, min_age = $17, max_age = $18, pes_domain = $19,
   tags = $20, opp_topics = $21, ticket_required = $22,
   title = $23, description = $24, short_desc = $25, image_url = $26, image_credit = $27,
-  start_datetimes = $28, has_end = $29, end_datetimes = $30, recurrence = $31, end_recurrence = $32, timezone = $33,
-  attraction_hours = $34, cost = $35, languages = $36, is_online = $37,
-  location_type = $38, location_name = $39, location_point_geojson = $40, location_polygon_geojson = $41,
-  address_street = $42, address_city = $43, address_state = $44, address_country = $45, address_zip = $46,
-  opp_hashtags = $47, opp_social_handles = $48, opp_partner = $49,
-  accepted = coalesce($50, c_opportunity.accepted), withdrawn = $51, submitted_by = $52, review_status = $53,
-  contact_name = $54, contact_email = $55, contact_phone = $56, extra_data = $57
+  start_datetimes = $28, has_end = $29, end_datetimes = $30, recurrence = $31, end_recurrence = $32,
+  recurrence_rdates = $33, recurrence_exdates = $34, timezone = $35, recurrence_until = $36,
+  attraction_hours = $37, cost = $38, languages = $39, is_online = $40,
+  location_type = $41, location_name = $42, location_point_geojson = $43, location_polygon_geojson = $44,
+  address_street = $45, address_city = $46, address_state = $47, address_country = $48, address_zip = $49,
+  opp_hashtags = $50, opp_social_handles = $51, opp_partner = $52,
+  accepted = coalesce($53, c_opportunity.accepted), withdrawn = $54, submitted_by = $55, review_status = $56,
+  contact_name = $57, contact_email = $58, contact_phone = $59, extra_data = $60,
+  translations = $61, accessibility = $62, capacity = $63,
+  publish_at = $64, unpublish_at = $65, parent_uid = $66
 where id = $1;
@@ -0,0 +1,5 @@
+update c_opportunity set
+  start_datetimes = $2, has_end = $3, end_datetimes = $4, recurrence = $5, end_recurrence = null,
+  recurrence_rdates = '{}', recurrence_exdates = '{}', recurrence_until = $6,
+  is_online = $7, location_type = $8, location_point_geojson = $9, location_polygon_geojson = $10
+where id = $1;
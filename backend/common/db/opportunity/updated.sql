@@ -0,0 +1 @@
+select uid, updated from c_opportunity where uid = any($1);
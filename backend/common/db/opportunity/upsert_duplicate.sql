@@ -0,0 +1,5 @@
+insert into c_opportunity_duplicate (first_id, second_id, score, title_similarity, distance, overlapping)
+values (least($1::integer, $2::integer), greatest($1::integer, $2::integer), $3, $4, $5, $6)
+on conflict (first_id, second_id) do update
+set score = $3, title_similarity = $4, distance = $5, overlapping = $6
+where c_opportunity_duplicate.status = 'pending';
@@ -0,0 +1,6 @@
+WITH RECURSIVE ancestors(id, parent_id) AS (
+  SELECT id, parent_id FROM c_region WHERE id = $1
+  UNION
+  SELECT r.id, r.parent_id FROM c_region r JOIN ancestors a ON r.id = a.parent_id
+)
+SELECT id AS "id!" FROM ancestors;
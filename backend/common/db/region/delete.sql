@@ -0,0 +1 @@
+DELETE FROM c_region WHERE id = $1;
@@ -0,0 +1,3 @@
+SELECT id, "name", parent_id, ST_AsGeoJSON("geometry")::jsonb AS "geometry!"
+FROM c_region
+WHERE id = $1;
@@ -0,0 +1,3 @@
+INSERT INTO c_region ("name", parent_id, "geometry")
+VALUES ($1, $2, ST_Multi(ST_CollectionExtract(ST_MakeValid(ST_SetSRID(ST_GeomFromGeoJSON($3::text), 4326)), 3))::geography)
+RETURNING id;
@@ -0,0 +1,3 @@
+SELECT r.id, r."name", r.parent_id, p."name" AS "parent_name?", r.updated
+FROM c_region r LEFT JOIN c_region p ON r.parent_id = p.id
+ORDER BY p."name" NULLS FIRST, r."name";
@@ -0,0 +1,7 @@
+UPDATE c_region
+SET
+  "name" = $2,
+  parent_id = $3,
+  "geometry" = ST_Multi(ST_CollectionExtract(ST_MakeValid(ST_SetSRID(ST_GeomFromGeoJSON($4::text), 4326)), 3))::geography,
+  updated = CURRENT_TIMESTAMP
+WHERE id = $1;
@@ -0,0 +1,11 @@
+select r.occurrence, r.status, r.created,
+  case when r.status = 'waitlisted' then (
+    select count(*) from c_rsvp w
+    where w.opportunity_id = r.opportunity_id and w.occurrence = r.occurrence and w.status = 'waitlisted'
+    and (w.created, w.id) <= (r.created, r.id)
+  ) end as position,
+  p.uid as person, p.username, p.first_name, p.last_name, p.email, p.phone
+from c_rsvp r
+join c_person p on p.id = r.person_id
+where r.opportunity_id = $1 and ($2::timestamptz is null or r.occurrence = $2) and r.status != 'cancelled'
+order by r.occurrence, r.status, r.created, r.id;
@@ -0,0 +1,8 @@
+select occ.starts, occ.ends,
+  count(r.id) filter (where r.status = 'confirmed') as "confirmed!",
+  count(r.id) filter (where r.status = 'waitlisted') as "waitlisted!"
+from c_opportunity_occurrence occ
+left join c_rsvp r on r.opportunity_id = occ.opportunity_id and r.occurrence = occ.starts
+where occ.opportunity_id = $1 and coalesce(occ.ends, occ.starts) > current_timestamp
+group by occ.starts, occ.ends
+order by occ.starts;
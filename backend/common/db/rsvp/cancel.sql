@@ -0,0 +1,3 @@
+update c_rsvp set status = 'cancelled', updated = current_timestamp
+where opportunity_id = $1 and occurrence = $2 and person_id = $3 and status != 'cancelled'
+returning id;
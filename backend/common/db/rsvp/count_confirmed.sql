@@ -0,0 +1,2 @@
+select count(*) as "count!" from c_rsvp
+where opportunity_id = $1 and occurrence = $2 and status = 'confirmed';
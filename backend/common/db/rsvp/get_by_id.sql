@@ -0,0 +1,10 @@
+select r.id, o.uid as opportunity, r.occurrence, p.uid as person, r.status, r.created, r.updated,
+  case when r.status = 'waitlisted' then (
+    select count(*) from c_rsvp w
+    where w.opportunity_id = r.opportunity_id and w.occurrence = r.occurrence and w.status = 'waitlisted'
+    and (w.created, w.id) <= (r.created, r.id)
+  ) end as position
+from c_rsvp r
+join c_opportunity o on o.id = r.opportunity_id
+join c_person p on p.id = r.person_id
+where r.id = $1;
@@ -0,0 +1,4 @@
+select exists (
+  select 1 from c_opportunity_occurrence
+  where opportunity_id = $1 and starts = $2 and coalesce(ends, starts) > current_timestamp
+) as "upcoming!";
@@ -0,0 +1 @@
+select capacity from c_opportunity where id = $1 for update;
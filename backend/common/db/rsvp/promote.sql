@@ -0,0 +1,10 @@
+update c_rsvp set status = 'confirmed', updated = current_timestamp
+where id in (
+  select id from c_rsvp
+  where opportunity_id = $1 and occurrence = $2 and status = 'waitlisted'
+  order by created, id
+  limit greatest($3::integer - (
+    select count(*) from c_rsvp where opportunity_id = $1 and occurrence = $2 and status = 'confirmed'
+  ), 0)
+)
+returning id;
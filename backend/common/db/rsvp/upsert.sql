@@ -0,0 +1,5 @@
+insert into c_rsvp (opportunity_id, occurrence, person_id, involvement_id, status)
+values ($1, $2, $3, (select id from c_involvement where participant = $4 and opportunity = $5), $6)
+on conflict (opportunity_id, occurrence, person_id) do update
+set status = $6, involvement_id = excluded.involvement_id, created = current_timestamp, updated = current_timestamp
+returning id;
@@ -0,0 +1,178 @@
+begin;
+
+alter table c_opportunity
+  add column recurrence_rdates timestamptz[] not null default '{}',
+  add column recurrence_exdates timestamptz[] not null default '{}',
+  add column recurrence_until timestamptz;
+
+-- recurrence_until is maintained by Opportunity::store, which expands
+-- the RRULE. Existing daily and weekly entries end when their
+-- recurrence ends.
+update c_opportunity set recurrence_until = end_recurrence
+where recurrence in ('daily', 'weekly');
+
+-- Recurrence is now an RRULE or one of the shorthand values, which
+-- doesn't fit the old enum
+alter table c_opportunity_search
+  alter column recurrence type text using recurrence::text,
+  add column recurrence_until timestamptz;
+
+drop type t_recurrence;
+
+update c_opportunity_search set
+  recurrence_until = c_opportunity.recurrence_until
+from c_opportunity
+where c_opportunity_search.opp_id = c_opportunity.id;
+
+create or replace function c_opportunity_is_current_as_of(opp c_opportunity, stamp timestamptz) returns boolean as
+$func$
+BEGIN
+ RETURN (
+   coalesce(opp.review_status, 'not_required') IN ('publish', 'not_required')
+   AND
+   opp.accepted = true
+   AND
+   opp.withdrawn = false
+   AND
+   (
+       (
+          coalesce(array_length(opp.start_datetimes, 1), 0) <= 1
+          AND
+          coalesce(array_length(opp.end_datetimes, 1), 0) = 0
+       )
+       OR
+       EXISTS (SELECT value FROM unnest(opp.start_datetimes) t(value) WHERE value > stamp)
+       OR
+       EXISTS (SELECT value FROM unnest(opp.end_datetimes) t(value) WHERE value > stamp)
+       OR
+       (opp.recurrence <> 'once' AND opp.recurrence_until IS null)
+       OR
+       opp.recurrence_until > stamp
+   )
+ );
+END
+$func$ language plpgsql stable;
+
+create or replace function c_opportunity_search_update() returns trigger as
+$body$
+begin
+  insert into c_opportunity_search (
+    uid, slug, created, updated,
+    accepted, withdrawn, review_status,
+    start_datetimes, end_datetimes, recurrence, end_recurrence,
+    recurrence_until,
+    entity_type, title, tags, topics, descriptors,
+    partner, min_age, max_age, "cost", venue_type,
+    organization_name, is_online, location_type,
+    short_desc, description, image_url, image_credit,
+    fulltext_english, location_point, location_polygon,
+    opp_id
+  )
+  values (
+    new.uid,
+    new.slug,
+    new.created,
+    new.updated,
+    coalesce(new.accepted, false),
+    new.withdrawn,
+    new.review_status::t_review_status,
+    new.start_datetimes,
+    new.end_datetimes,
+    new.recurrence,
+    new.end_recurrence,
+    new.recurrence_until,
+    case
+      when starts_with(new.entity_type, '{"page":') then 'page'::t_entity_type
+      when starts_with(new.entity_type, 'page_') then 'page'::t_entity_type
+      else new.entity_type::t_entity_type
+    end,
+    new.title,
+    new.tags,
+    new.opp_topics,
+    new.opp_descriptor,
+    new.opp_partner,
+    new.min_age,
+    new.max_age,
+    new.cost::t_cost,
+    (select coalesce(array_agg(v::t_venue_type), '{}'::t_venue_type[]) from unnest(new.opp_venue) v),
+    new.organization_name,
+    new.is_online,
+    new.location_type::t_location_type,
+    new.short_desc,
+    new.description,
+    new.image_url,
+    new.image_credit,
+    (
+      to_tsvector('english', new.title) ||
+      to_tsvector('english', new.description) ||
+      to_tsvector('english', new.partner_name) ||
+      to_tsvector('english', new.organization_name) ||
+      to_tsvector('english', new.location_name) ||
+      to_tsvector('english', new.address_city) ||
+      to_tsvector('english', new.address_state) ||
+      to_tsvector('english', new.address_country) ||
+      to_tsvector('english', array_to_string(new.tags, ' ')) ||
+      to_tsvector('english', array_to_string(new.opp_topics, ' ')) ||
+      to_tsvector('english', array_to_string(new.opp_descriptor, ' ')) ||
+      to_tsvector('english', array_to_string(new.opp_hashtags, ' '))
+    ),
+    (
+      case
+        when new.location_point_geojson is not null
+          and (new.location_point_geojson->>'type') = 'Point'
+          and c_valid_geojson(new.location_point_geojson)
+        then c_make_valid_geography(st_geomfromgeojson(new.location_point_geojson))
+        else null
+      end
+    ),
+    (
+      case
+        when new.location_polygon_geojson is not null
+          and (new.location_polygon_geojson->>'type') = any(array['Polygon', 'MultiPolygon'])
+          and c_valid_geojson(new.location_polygon_geojson)
+        then c_make_valid_geography(st_geomfromgeojson(new.location_polygon_geojson))
+        else null
+      end
+    ),
+    new.id
+  )
+  on conflict (opp_id)
+  do update set
+    uid = excluded.uid,
+    slug = excluded.slug,
+    created = excluded.created,
+    updated = excluded.updated,
+    accepted = excluded.accepted,
+    withdrawn = excluded.withdrawn,
+    review_status = excluded.review_status,
+    start_datetimes = excluded.start_datetimes,
+    end_datetimes = excluded.end_datetimes,
+    recurrence = excluded.recurrence,
+    end_recurrence = excluded.end_recurrence,
+    recurrence_until = excluded.recurrence_until,
+    entity_type = excluded.entity_type,
+    title = excluded.title,
+    tags = excluded.tags,
+    topics = excluded.topics,
+    descriptors = excluded.descriptors,
+    partner = excluded.partner,
+    min_age = excluded.min_age,
+    max_age = excluded.max_age,
+    "cost" = excluded."cost",
+    venue_type = excluded.venue_type,
+    organization_name = excluded.organization_name,
+    is_online = excluded.is_online,
+    location_type = excluded.location_type,
+    short_desc = excluded.short_desc,
+    description = excluded.description,
+    image_url = excluded.image_url,
+    image_credit = excluded.image_credit,
+    fulltext_english = excluded.fulltext_english,
+    location_point = excluded.location_point,
+    location_polygon = excluded.location_polygon;
+    return new;
+end;
+$body$
+language plpgsql;
+
+commit;
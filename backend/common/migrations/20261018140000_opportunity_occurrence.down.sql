@@ -0,0 +1,34 @@
+begin;
+
+create or replace function c_opportunity_is_current_as_of(opp c_opportunity, stamp timestamptz) returns boolean as
+$func$
+BEGIN
+ RETURN (
+   coalesce(opp.review_status, 'not_required') IN ('publish', 'not_required')
+   AND
+   opp.accepted = true
+   AND
+   opp.withdrawn = false
+   AND
+   (
+       (
+          coalesce(array_length(opp.start_datetimes, 1), 0) <= 1
+          AND
+          coalesce(array_length(opp.end_datetimes, 1), 0) = 0
+       )
+       OR
+       EXISTS (SELECT value FROM unnest(opp.start_datetimes) t(value) WHERE value > stamp)
+       OR
+       EXISTS (SELECT value FROM unnest(opp.end_datetimes) t(value) WHERE value > stamp)
+       OR
+       (opp.recurrence <> 'once' AND opp.recurrence_until IS null)
+       OR
+       opp.recurrence_until > stamp
+   )
+ );
+END
+$func$ language plpgsql stable;
+
+drop table c_opportunity_occurrence;
+
+commit;
@@ -0,0 +1,72 @@
+begin;
+
+-- One row per concrete instance of each opportunity, covering the
+-- explicitly listed datetimes plus the expanded recurrence out to the
+-- materialization horizon. Maintained by Opportunity::store.
+create table c_opportunity_occurrence (
+  id serial primary key,
+  opportunity_id integer not null references c_opportunity(id) on delete cascade,
+  starts timestamptz not null,
+  ends timestamptz
+);
+
+create index c_opportunity_occurrence_by_opportunity on c_opportunity_occurrence (opportunity_id, starts);
+create index c_opportunity_occurrence_by_starts on c_opportunity_occurrence (starts);
+create index c_opportunity_occurrence_by_finish on c_opportunity_occurrence ((coalesce(ends, starts)));
+
+insert into c_opportunity_occurrence (opportunity_id, starts, ends)
+select
+  o.id,
+  s.value,
+  case when o.end_datetimes[s.idx] > s.value then o.end_datetimes[s.idx] else null end
+from c_opportunity o cross join unnest(o.start_datetimes) with ordinality s(value, idx);
+
+-- Opportunity::store expands RRULEs and RDATEs as well, but existing
+-- daily and weekly entries can be expanded here
+insert into c_opportunity_occurrence (opportunity_id, starts, ends)
+select
+  o.id,
+  g.value,
+  case
+    when o.end_datetimes[1] > o.start_datetimes[1]
+    then g.value + (o.end_datetimes[1] - o.start_datetimes[1])
+    else null
+  end
+from c_opportunity o cross join generate_series(
+  o.start_datetimes[1],
+  least(coalesce(o.end_recurrence, now() + interval '730 days'), now() + interval '730 days'),
+  case when o.recurrence = 'daily' then interval '1 day' else interval '7 days' end
+) g(value)
+where o.recurrence in ('daily', 'weekly')
+  and coalesce(array_length(o.start_datetimes, 1), 0) > 0
+  and g.value > now() - interval '365 days'
+  and not (g.value = any(o.start_datetimes));
+
+create or replace function c_opportunity_is_current_as_of(opp c_opportunity, stamp timestamptz) returns boolean as
+$func$
+BEGIN
+ RETURN (
+   coalesce(opp.review_status, 'not_required') IN ('publish', 'not_required')
+   AND
+   opp.accepted = true
+   AND
+   opp.withdrawn = false
+   AND
+   (
+       (
+          coalesce(array_length(opp.start_datetimes, 1), 0) <= 1
+          AND
+          coalesce(array_length(opp.end_datetimes, 1), 0) = 0
+       )
+       OR
+       EXISTS (SELECT 1 FROM c_opportunity_occurrence occ WHERE occ.opportunity_id = opp.id AND coalesce(occ.ends, occ.starts) > stamp)
+       OR
+       (opp.recurrence <> 'once' AND opp.recurrence_until IS null)
+       OR
+       opp.recurrence_until > stamp
+   )
+ );
+END
+$func$ language plpgsql stable;
+
+commit;
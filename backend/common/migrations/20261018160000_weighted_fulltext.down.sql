@@ -0,0 +1,156 @@
+begin;
+
+drop index if exists c_opportunity_search_via_fulltext;
+
+alter table c_opportunity_search drop column fulltext;
+
+alter table c_opportunity_search add column fulltext_english tsvector;
+
+update c_opportunity_search set fulltext_english = (
+  to_tsvector('english', c_opportunity.title) ||
+  to_tsvector('english', c_opportunity.description) ||
+  to_tsvector('english', c_opportunity.partner_name) ||
+  to_tsvector('english', c_opportunity.organization_name) ||
+  to_tsvector('english', c_opportunity.location_name) ||
+  to_tsvector('english', c_opportunity.address_city) ||
+  to_tsvector('english', c_opportunity.address_state) ||
+  to_tsvector('english', c_opportunity.address_country) ||
+  to_tsvector('english', array_to_string(c_opportunity.tags, ' ')) ||
+  to_tsvector('english', array_to_string(c_opportunity.opp_topics, ' ')) ||
+  to_tsvector('english', array_to_string(c_opportunity.opp_descriptor, ' ')) ||
+  to_tsvector('english', array_to_string(c_opportunity.opp_hashtags, ' '))
+)
+from c_opportunity
+where c_opportunity_search.opp_id = c_opportunity.id;
+
+create index c_opportunity_search_via_fulltext_english on c_opportunity_search using gin(fulltext_english);
+
+create or replace function c_opportunity_search_update() returns trigger as
+$body$
+begin
+  insert into c_opportunity_search (
+    uid, slug, created, updated,
+    accepted, withdrawn, review_status,
+    start_datetimes, end_datetimes, recurrence, end_recurrence,
+    recurrence_until,
+    entity_type, title, tags, topics, descriptors,
+    partner, min_age, max_age, "cost", venue_type,
+    organization_name, is_online, location_type,
+    short_desc, description, image_url, image_credit,
+    fulltext_english, location_point, location_polygon,
+    opp_id
+  )
+  values (
+    new.uid,
+    new.slug,
+    new.created,
+    new.updated,
+    coalesce(new.accepted, false),
+    new.withdrawn,
+    new.review_status::t_review_status,
+    new.start_datetimes,
+    new.end_datetimes,
+    new.recurrence,
+    new.end_recurrence,
+    new.recurrence_until,
+    case
+      when starts_with(new.entity_type, '{"page":') then 'page'::t_entity_type
+      when starts_with(new.entity_type, 'page_') then 'page'::t_entity_type
+      else new.entity_type::t_entity_type
+    end,
+    new.title,
+    new.tags,
+    new.opp_topics,
+    new.opp_descriptor,
+    new.opp_partner,
+    new.min_age,
+    new.max_age,
+    new.cost::t_cost,
+    (select coalesce(array_agg(v::t_venue_type), '{}'::t_venue_type[]) from unnest(new.opp_venue) v),
+    new.organization_name,
+    new.is_online,
+    new.location_type::t_location_type,
+    new.short_desc,
+    new.description,
+    new.image_url,
+    new.image_credit,
+    (
+      to_tsvector('english', new.title) ||
+      to_tsvector('english', new.description) ||
+      to_tsvector('english', new.partner_name) ||
+      to_tsvector('english', new.organization_name) ||
+      to_tsvector('english', new.location_name) ||
+      to_tsvector('english', new.address_city) ||
+      to_tsvector('english', new.address_state) ||
+      to_tsvector('english', new.address_country) ||
+      to_tsvector('english', array_to_string(new.tags, ' ')) ||
+      to_tsvector('english', array_to_string(new.opp_topics, ' ')) ||
+      to_tsvector('english', array_to_string(new.opp_descriptor, ' ')) ||
+      to_tsvector('english', array_to_string(new.opp_hashtags, ' '))
+    ),
+    (
+      case
+        when new.location_point_geojson is not null
+          and (new.location_point_geojson->>'type') = 'Point'
+          and c_valid_geojson(new.location_point_geojson)
+        then c_make_valid_geography(st_geomfromgeojson(new.location_point_geojson))
+        else null
+      end
+    ),
+    (
+      case
+        when new.location_polygon_geojson is not null
+          and (new.location_polygon_geojson->>'type') = any(array['Polygon', 'MultiPolygon'])
+          and c_valid_geojson(new.location_polygon_geojson)
+        then c_make_valid_geography(st_geomfromgeojson(new.location_polygon_geojson))
+        else null
+      end
+    ),
+    new.id
+  )
+  on conflict (opp_id)
+  do update set
+    uid = excluded.uid,
+    slug = excluded.slug,
+    created = excluded.created,
+    updated = excluded.updated,
+    accepted = excluded.accepted,
+    withdrawn = excluded.withdrawn,
+    review_status = excluded.review_status,
+    start_datetimes = excluded.start_datetimes,
+    end_datetimes = excluded.end_datetimes,
+    recurrence = excluded.recurrence,
+    end_recurrence = excluded.end_recurrence,
+    recurrence_until = excluded.recurrence_until,
+    entity_type = excluded.entity_type,
+    title = excluded.title,
+    tags = excluded.tags,
+    topics = excluded.topics,
+    descriptors = excluded.descriptors,
+    partner = excluded.partner,
+    min_age = excluded.min_age,
+    max_age = excluded.max_age,
+    "cost" = excluded."cost",
+    venue_type = excluded.venue_type,
+    organization_name = excluded.organization_name,
+    is_online = excluded.is_online,
+    location_type = excluded.location_type,
+    short_desc = excluded.short_desc,
+    description = excluded.description,
+    image_url = excluded.image_url,
+    image_credit = excluded.image_credit,
+    fulltext_english = excluded.fulltext_english,
+    location_point = excluded.location_point,
+    location_polygon = excluded.location_polygon;
+    return new;
+end;
+$body$
+language plpgsql;
+
+drop function c_opportunity_fulltext(c_opportunity);
+
+drop function c_search_tsquery(text, text);
+
+drop function c_language_regconfig(text);
+
+commit;
@@ -0,0 +1,240 @@
+begin;
+
+-- Maps the language codes used in c_opportunity.languages (for
+-- example 'en-US' or 'es') to a text search configuration, or null if
+-- PostgreSQL doesn't have stemming rules for the language.
+create or replace function c_language_regconfig(lang text) returns regconfig as
+$func$
+  select case lower(split_part(coalesce(lang, ''), '-', 1))
+    when 'ar' then 'arabic'
+    when 'hy' then 'armenian'
+    when 'eu' then 'basque'
+    when 'ca' then 'catalan'
+    when 'da' then 'danish'
+    when 'nl' then 'dutch'
+    when 'en' then 'english'
+    when 'fi' then 'finnish'
+    when 'fr' then 'french'
+    when 'de' then 'german'
+    when 'el' then 'greek'
+    when 'hi' then 'hindi'
+    when 'hu' then 'hungarian'
+    when 'id' then 'indonesian'
+    when 'ga' then 'irish'
+    when 'it' then 'italian'
+    when 'lt' then 'lithuanian'
+    when 'ne' then 'nepali'
+    when 'no' then 'norwegian'
+    when 'nb' then 'norwegian'
+    when 'pt' then 'portuguese'
+    when 'ro' then 'romanian'
+    when 'ru' then 'russian'
+    when 'sr' then 'serbian'
+    when 'es' then 'spanish'
+    when 'sv' then 'swedish'
+    when 'ta' then 'tamil'
+    when 'tr' then 'turkish'
+    when 'yi' then 'yiddish'
+    else null
+  end::regconfig;
+$func$ language sql immutable;
+
+-- Search text is stemmed according to the searcher's language,
+-- defaulting to English. Stemmers don't always agree on related words
+-- ('astronomy' becomes 'astronomi' while 'astronomer' becomes
+-- 'astronom'), so each lexeme is matched as a prefix, and longer
+-- lexemes are also matched without their final letter.
+create or replace function c_search_tsquery(search text, lang text) returns tsquery as
+$func$
+declare
+  remaining text := websearch_to_tsquery(coalesce(c_language_regconfig(lang), 'english'::regconfig), search)::text;
+  result text := '';
+  lexeme text;
+  pos integer;
+begin
+  if search is null then
+    return null;
+  end if;
+
+  loop
+    pos := strpos(remaining, '''');
+    exit when pos = 0;
+
+    result := result || left(remaining, pos - 1);
+    remaining := substr(remaining, pos + 1);
+
+    -- Quotes within a lexeme are doubled
+    lexeme := substring(remaining from '^((?:[^'']|'''')*)''');
+    remaining := substr(remaining, length(lexeme) + 2);
+
+    if length(lexeme) >= 6 and right(lexeme, 1) not in ('''', '\') then
+      result := result || '(''' || lexeme || ''':* | ''' || left(lexeme, -1) || ''':*)';
+    else
+      result := result || '''' || lexeme || '''';
+    end if;
+  end loop;
+
+  return (result || remaining)::tsquery;
+end
+$func$ language plpgsql immutable;
+
+-- Weighted search document for an opportunity. The text is stemmed
+-- in English, which is how most searches are made, as well as in each
+-- of the opportunity's own languages.
+create or replace function c_opportunity_fulltext(opp c_opportunity) returns tsvector as
+$func$
+declare
+  result tsvector := ''::tsvector;
+  config regconfig;
+begin
+  for config in
+    select distinct c from (
+      select 'english'::regconfig as c
+      union
+      select c_language_regconfig(l) from unnest(opp.languages) l
+    ) configs
+    where c is not null
+  loop
+    result := result ||
+      setweight(to_tsvector(config, coalesce(opp.title, '')), 'A') ||
+      setweight(to_tsvector(config, coalesce(opp.short_desc, '')), 'B') ||
+      setweight(to_tsvector(config, array_to_string(opp.tags, ' ')), 'B') ||
+      setweight(to_tsvector(config, coalesce(opp.description, '')), 'C') ||
+      setweight(to_tsvector(config, coalesce(opp.organization_name, '') || ' ' || coalesce(opp.partner_name, '')), 'C') ||
+      setweight(to_tsvector(config,
+        array_to_string(opp.opp_topics, ' ') || ' ' ||
+        array_to_string(opp.opp_descriptor, ' ') || ' ' ||
+        array_to_string(opp.opp_hashtags, ' ') || ' ' ||
+        coalesce(opp.location_name, '') || ' ' ||
+        coalesce(opp.address_city, '') || ' ' ||
+        coalesce(opp.address_state, '') || ' ' ||
+        coalesce(opp.address_country, '')
+      ), 'D');
+  end loop;
+
+  return result;
+end
+$func$ language plpgsql stable;
+
+drop index if exists c_opportunity_search_via_fulltext_english;
+
+alter table c_opportunity_search drop column fulltext_english;
+
+alter table c_opportunity_search add column fulltext tsvector;
+
+update c_opportunity_search set fulltext = c_opportunity_fulltext(c_opportunity)
+from c_opportunity
+where c_opportunity_search.opp_id = c_opportunity.id;
+
+create index c_opportunity_search_via_fulltext on c_opportunity_search using gin(fulltext);
+
+create or replace function c_opportunity_search_update() returns trigger as
+$body$
+begin
+  insert into c_opportunity_search (
+    uid, slug, created, updated,
+    accepted, withdrawn, review_status,
+    start_datetimes, end_datetimes, recurrence, end_recurrence,
+    recurrence_until,
+    entity_type, title, tags, topics, descriptors,
+    partner, min_age, max_age, "cost", venue_type,
+    organization_name, is_online, location_type,
+    short_desc, description, image_url, image_credit,
+    fulltext, location_point, location_polygon,
+    opp_id
+  )
+  values (
+    new.uid,
+    new.slug,
+    new.created,
+    new.updated,
+    coalesce(new.accepted, false),
+    new.withdrawn,
+    new.review_status::t_review_status,
+    new.start_datetimes,
+    new.end_datetimes,
+    new.recurrence,
+    new.end_recurrence,
+    new.recurrence_until,
+    case
+      when starts_with(new.entity_type, '{"page":') then 'page'::t_entity_type
+      when starts_with(new.entity_type, 'page_') then 'page'::t_entity_type
+      else new.entity_type::t_entity_type
+    end,
+    new.title,
+    new.tags,
+    new.opp_topics,
+    new.opp_descriptor,
+    new.opp_partner,
+    new.min_age,
+    new.max_age,
+    new.cost::t_cost,
+    (select coalesce(array_agg(v::t_venue_type), '{}'::t_venue_type[]) from unnest(new.opp_venue) v),
+    new.organization_name,
+    new.is_online,
+    new.location_type::t_location_type,
+    new.short_desc,
+    new.description,
+    new.image_url,
+    new.image_credit,
+    c_opportunity_fulltext(new),
+    (
+      case
+        when new.location_point_geojson is not null
+          and (new.location_point_geojson->>'type') = 'Point'
+          and c_valid_geojson(new.location_point_geojson)
+        then c_make_valid_geography(st_geomfromgeojson(new.location_point_geojson))
+        else null
+      end
+    ),
+    (
+      case
+        when new.location_polygon_geojson is not null
+          and (new.location_polygon_geojson->>'type') = any(array['Polygon', 'MultiPolygon'])
+          and c_valid_geojson(new.location_polygon_geojson)
+        then c_make_valid_geography(st_geomfromgeojson(new.location_polygon_geojson))
+        else null
+      end
+    ),
+    new.id
+  )
+  on conflict (opp_id)
+  do update set
+    uid = excluded.uid,
+    slug = excluded.slug,
+    created = excluded.created,
+    updated = excluded.updated,
+    accepted = excluded.accepted,
+    withdrawn = excluded.withdrawn,
+    review_status = excluded.review_status,
+    start_datetimes = excluded.start_datetimes,
+    end_datetimes = excluded.end_datetimes,
+    recurrence = excluded.recurrence,
+    end_recurrence = excluded.end_recurrence,
+    recurrence_until = excluded.recurrence_until,
+    entity_type = excluded.entity_type,
+    title = excluded.title,
+    tags = excluded.tags,
+    topics = excluded.topics,
+    descriptors = excluded.descriptors,
+    partner = excluded.partner,
+    min_age = excluded.min_age,
+    max_age = excluded.max_age,
+    "cost" = excluded."cost",
+    venue_type = excluded.venue_type,
+    organization_name = excluded.organization_name,
+    is_online = excluded.is_online,
+    location_type = excluded.location_type,
+    short_desc = excluded.short_desc,
+    description = excluded.description,
+    image_url = excluded.image_url,
+    image_credit = excluded.image_credit,
+    fulltext = excluded.fulltext,
+    location_point = excluded.location_point,
+    location_polygon = excluded.location_polygon;
+    return new;
+end;
+$body$
+language plpgsql;
+
+commit;
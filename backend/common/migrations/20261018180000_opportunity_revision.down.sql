@@ -0,0 +1,5 @@
+begin;
+
+drop table c_opportunity_revision;
+
+commit;
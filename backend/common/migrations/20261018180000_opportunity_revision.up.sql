@@ -0,0 +1,17 @@
+begin;
+
+-- A snapshot of an opportunity each time it's stored with changes,
+-- along with who stored it and by what route
+create table c_opportunity_revision (
+  id serial primary key,
+  opportunity_id integer not null references c_opportunity(id) on delete cascade,
+  created timestamptz not null default current_timestamp,
+  author_kind text not null,
+  author_uid uuid,
+  source text not null,
+  snapshot jsonb not null
+);
+
+create index c_opportunity_revision_by_opportunity on c_opportunity_revision (opportunity_id, id);
+
+commit;
@@ -0,0 +1,11 @@
+begin;
+
+drop table c_opportunity_redirect;
+
+drop table c_opportunity_duplicate;
+
+drop index c_opportunity_title_trgm;
+
+drop extension if exists pg_trgm;
+
+commit;
@@ -0,0 +1,39 @@
+begin;
+
+create extension if not exists pg_trgm;
+
+create index c_opportunity_title_trgm on c_opportunity using gin (title gin_trgm_ops);
+
+-- Pairs of opportunities which look like copies of the same thing,
+-- awaiting review. The lower id is always first, so that each pair is
+-- only recorded once.
+create table c_opportunity_duplicate (
+  id serial primary key,
+  first_id integer not null references c_opportunity(id) on delete cascade,
+  second_id integer not null references c_opportunity(id) on delete cascade,
+  score real not null,
+  title_similarity real not null,
+  distance double precision,
+  overlapping boolean not null,
+  status text not null default 'pending',
+  detected timestamptz not null default current_timestamp,
+  resolved timestamptz,
+  resolved_by uuid,
+  unique (first_id, second_id),
+  check (first_id < second_id)
+);
+
+create index c_opportunity_duplicate_by_status on c_opportunity_duplicate (status, score);
+create index c_opportunity_duplicate_by_second on c_opportunity_duplicate (second_id);
+
+-- Slugs of opportunities which were merged into another, so that
+-- links to them still lead somewhere useful
+create table c_opportunity_redirect (
+  slug text primary key,
+  opportunity_id integer not null references c_opportunity(id) on delete cascade,
+  created timestamptz not null default current_timestamp
+);
+
+create index c_opportunity_redirect_by_opportunity on c_opportunity_redirect (opportunity_id);
+
+commit;
@@ -0,0 +1,40 @@
+begin;
+
+create or replace function c_opportunity_fulltext(opp c_opportunity) returns tsvector as
+$func$
+declare
+  result tsvector := ''::tsvector;
+  config regconfig;
+begin
+  for config in
+    select distinct c from (
+      select 'english'::regconfig as c
+      union
+      select c_language_regconfig(l) from unnest(opp.languages) l
+    ) configs
+    where c is not null
+  loop
+    result := result ||
+      setweight(to_tsvector(config, coalesce(opp.title, '')), 'A') ||
+      setweight(to_tsvector(config, coalesce(opp.short_desc, '')), 'B') ||
+      setweight(to_tsvector(config, array_to_string(opp.tags, ' ')), 'B') ||
+      setweight(to_tsvector(config, coalesce(opp.description, '')), 'C') ||
+      setweight(to_tsvector(config, coalesce(opp.organization_name, '') || ' ' || coalesce(opp.partner_name, '')), 'C') ||
+      setweight(to_tsvector(config,
+        array_to_string(opp.opp_topics, ' ') || ' ' ||
+        array_to_string(opp.opp_descriptor, ' ') || ' ' ||
+        array_to_string(opp.opp_hashtags, ' ') || ' ' ||
+        coalesce(opp.location_name, '') || ' ' ||
+        coalesce(opp.address_city, '') || ' ' ||
+        coalesce(opp.address_state, '') || ' ' ||
+        coalesce(opp.address_country, '')
+      ), 'D');
+  end loop;
+
+  return result;
+end
+$func$ language plpgsql stable;
+
+alter table c_opportunity drop column translations;
+
+commit;
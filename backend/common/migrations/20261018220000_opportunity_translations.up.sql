@@ -0,0 +1,54 @@
+begin;
+
+-- Title, short_desc and description in other languages, keyed by
+-- language code, e.g. {"es": {"title": "...", "short_desc": "...", "description": "..."}}
+alter table c_opportunity add column translations jsonb not null default '{}'::jsonb;
+
+-- Same as before, plus each translation stemmed in its own language
+create or replace function c_opportunity_fulltext(opp c_opportunity) returns tsvector as
+$func$
+declare
+  result tsvector := ''::tsvector;
+  config regconfig;
+  translation record;
+begin
+  for config in
+    select distinct c from (
+      select 'english'::regconfig as c
+      union
+      select c_language_regconfig(l) from unnest(opp.languages) l
+    ) configs
+    where c is not null
+  loop
+    result := result ||
+      setweight(to_tsvector(config, coalesce(opp.title, '')), 'A') ||
+      setweight(to_tsvector(config, coalesce(opp.short_desc, '')), 'B') ||
+      setweight(to_tsvector(config, array_to_string(opp.tags, ' ')), 'B') ||
+      setweight(to_tsvector(config, coalesce(opp.description, '')), 'C') ||
+      setweight(to_tsvector(config, coalesce(opp.organization_name, '') || ' ' || coalesce(opp.partner_name, '')), 'C') ||
+      setweight(to_tsvector(config,
+        array_to_string(opp.opp_topics, ' ') || ' ' ||
+        array_to_string(opp.opp_descriptor, ' ') || ' ' ||
+        array_to_string(opp.opp_hashtags, ' ') || ' ' ||
+        coalesce(opp.location_name, '') || ' ' ||
+        coalesce(opp.address_city, '') || ' ' ||
+        coalesce(opp.address_state, '') || ' ' ||
+        coalesce(opp.address_country, '')
+      ), 'D');
+  end loop;
+
+  for translation in
+    select key as lang, value as text from jsonb_each(coalesce(opp.translations, '{}'::jsonb))
+  loop
+    config := coalesce(c_language_regconfig(translation.lang), 'simple'::regconfig);
+    result := result ||
+      setweight(to_tsvector(config, coalesce(translation.text->>'title', '')), 'A') ||
+      setweight(to_tsvector(config, coalesce(translation.text->>'short_desc', '')), 'B') ||
+      setweight(to_tsvector(config, coalesce(translation.text->>'description', '')), 'C');
+  end loop;
+
+  return result;
+end
+$func$ language plpgsql stable;
+
+commit;
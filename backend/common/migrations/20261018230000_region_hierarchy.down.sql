@@ -0,0 +1,10 @@
+begin;
+
+drop index if exists c_region_geometry;
+drop index if exists c_region_by_parent;
+
+alter table c_region drop column "updated";
+alter table c_region drop column "created";
+alter table c_region drop column "parent_id";
+
+commit;
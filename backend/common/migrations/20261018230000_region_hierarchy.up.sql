@@ -0,0 +1,12 @@
+begin;
+
+-- Regions can be nested, such as counties within a state, so that the
+-- detailed counts for a region can include the regions within it.
+alter table c_region add column "parent_id" integer references c_region ("id") on delete set null;
+alter table c_region add column "created" timestamptz not null default current_timestamp;
+alter table c_region add column "updated" timestamptz not null default current_timestamp;
+
+create index c_region_by_parent on c_region ("parent_id");
+create index c_region_geometry on c_region using gist ("geometry");
+
+commit;
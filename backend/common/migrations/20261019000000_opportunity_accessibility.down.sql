@@ -0,0 +1,5 @@
+begin;
+
+alter table c_opportunity drop column accessibility;
+
+commit;
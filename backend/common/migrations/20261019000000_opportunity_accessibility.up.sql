@@ -0,0 +1,6 @@
+begin;
+
+-- Accommodations offered to participants, e.g. wheelchair_accessible
+alter table c_opportunity add column accessibility text[] not null default '{}';
+
+commit;
@@ -0,0 +1,7 @@
+begin;
+
+drop table c_rsvp;
+
+alter table c_opportunity drop column capacity;
+
+commit;
@@ -0,0 +1,25 @@
+begin;
+
+-- Seats available at each occurrence when Science Near Me takes the
+-- registrations, or null when it doesn't
+alter table c_opportunity add column capacity integer check (capacity >= 0);
+
+-- A person's registration for one occurrence of an opportunity,
+-- identified by its start time since occurrences are rebuilt whenever
+-- the opportunity is stored
+create table c_rsvp (
+  id serial primary key,
+  opportunity_id integer not null references c_opportunity(id) on delete cascade,
+  occurrence timestamptz not null,
+  person_id integer not null references c_person(id) on delete cascade,
+  involvement_id integer references c_involvement(id) on delete set null,
+  status text not null check (status in ('confirmed', 'waitlisted', 'cancelled')),
+  created timestamptz not null default current_timestamp,
+  updated timestamptz not null default current_timestamp,
+  unique (opportunity_id, occurrence, person_id)
+);
+
+create index c_rsvp_by_occurrence on c_rsvp (opportunity_id, occurrence, status, created);
+create index c_rsvp_by_person on c_rsvp (person_id);
+
+commit;
@@ -0,0 +1,3 @@
+-- Enum values can not be dropped, so 'scheduled' remains in
+-- t_review_status. The publication schedule down migration moves
+-- any opportunities using it back to 'draft'.
@@ -0,0 +1,8 @@
+begin;
+
+-- This is separate from the publication schedule migration because
+-- PostgreSQL won't allow a new enum value to be used before the
+-- transaction which added it has committed.
+alter type t_review_status add value if not exists 'scheduled';
+
+commit;
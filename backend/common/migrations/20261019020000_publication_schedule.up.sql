@@ -0,0 +1,55 @@
+begin;
+
+-- Partners can stage an opportunity ahead of an embargo date by
+-- giving it the 'scheduled' status and a publish_at time, and can
+-- take it down again automatically with unpublish_at. The times are
+-- honoured immediately by c_opportunity_is_current_as_of, and a
+-- background task moves the review status along once they pass.
+-- The 'scheduled' review status itself is added by the previous
+-- migration, since a new enum value can't be used in the
+-- transaction that adds it.
+
+alter table c_opportunity add column publish_at timestamptz;
+
+alter table c_opportunity add column unpublish_at timestamptz;
+
+create index c_opportunity_by_publish_at on c_opportunity (publish_at) where review_status = 'scheduled';
+
+create index c_opportunity_by_unpublish_at on c_opportunity (unpublish_at) where unpublish_at is not null;
+
+create or replace function c_opportunity_is_current_as_of(opp c_opportunity, stamp timestamptz) returns boolean as
+$func$
+BEGIN
+ RETURN (
+   (
+       coalesce(opp.review_status, 'not_required') IN ('publish', 'not_required')
+       OR
+       (opp.review_status = 'scheduled' AND opp.publish_at IS NOT null)
+   )
+   AND
+   (opp.publish_at IS null OR opp.publish_at <= stamp)
+   AND
+   (opp.unpublish_at IS null OR opp.unpublish_at > stamp)
+   AND
+   opp.accepted = true
+   AND
+   opp.withdrawn = false
+   AND
+   (
+       (
+          coalesce(array_length(opp.start_datetimes, 1), 0) <= 1
+          AND
+          coalesce(array_length(opp.end_datetimes, 1), 0) = 0
+       )
+       OR
+       EXISTS (SELECT 1 FROM c_opportunity_occurrence occ WHERE occ.opportunity_id = opp.id AND coalesce(occ.ends, occ.starts) > stamp)
+       OR
+       (opp.recurrence <> 'once' AND opp.recurrence_until IS null)
+       OR
+       opp.recurrence_until > stamp
+   )
+ );
+END
+$func$ language plpgsql stable;
+
+commit;
@@ -0,0 +1,7 @@
+begin;
+
+drop table c_moderation;
+
+drop table c_moderation_comment;
+
+commit;
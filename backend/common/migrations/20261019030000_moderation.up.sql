@@ -0,0 +1,29 @@
+begin;
+
+-- The conversation between an opportunity's submitter and the
+-- people reviewing it. Review status transitions are recorded in the
+-- thread too, with whatever note accompanied them.
+create table c_moderation_comment (
+  id serial primary key,
+  opportunity_id integer not null references c_opportunity(id) on delete cascade,
+  author_id integer references c_person(id) on delete set null,
+  body text not null default '',
+  from_status text,
+  to_status text,
+  created timestamptz not null default current_timestamp
+);
+
+create index c_moderation_comment_by_opportunity on c_moderation_comment (opportunity_id, created);
+
+-- Who is looking after an opportunity's review, and when its review
+-- status last changed
+create table c_moderation (
+  opportunity_id integer primary key references c_opportunity(id) on delete cascade,
+  assignee_id integer references c_person(id) on delete set null,
+  status_since timestamptz not null default current_timestamp
+);
+
+insert into c_moderation (opportunity_id, status_since)
+select id, updated from c_opportunity where review_status = 'pending';
+
+commit;
@@ -0,0 +1,7 @@
+begin;
+
+drop function if exists c_open_at(jsonb, text, timestamptz);
+
+drop function if exists c_open_hours_on(jsonb, date);
+
+commit;
@@ -0,0 +1,67 @@
+begin;
+
+-- Attraction hours are stored as a JSON object with an entry for
+-- each day of the week, plus a list of dated exceptions which either
+-- close the attraction for the day (hours is null) or replace its
+-- usual hours. Hours which close at or before the time they open run
+-- past midnight into the next day.
+
+-- The hours which apply on a particular local date, or null if the
+-- attraction is closed that day
+create or replace function c_open_hours_on(hours jsonb, day date) returns jsonb as
+$func$
+DECLARE
+ special jsonb;
+BEGIN
+ IF jsonb_typeof(hours->'exceptions') = 'array' THEN
+   SELECT e INTO special FROM jsonb_array_elements(hours->'exceptions') e WHERE e->>'date' = day::text LIMIT 1;
+
+   IF FOUND THEN
+     RETURN nullif(special->'hours', 'null'::jsonb);
+   END IF;
+ END IF;
+
+ RETURN nullif(hours->to_char(day, 'FMday'), 'null'::jsonb);
+END
+$func$ language plpgsql stable;
+
+-- True if the attraction is open at the given moment, judged by the
+-- wall clock in its timezone. Malformed hours or an unknown timezone
+-- count as closed.
+create or replace function c_open_at(hours jsonb, timezone text, moment timestamptz) returns boolean as
+$func$
+DECLARE
+ here timestamp;
+ today jsonb;
+ yesterday jsonb;
+BEGIN
+ IF hours IS null THEN
+   RETURN false;
+ END IF;
+
+ here := moment AT TIME ZONE coalesce(nullif(timezone, ''), 'UTC');
+ today := c_open_hours_on(hours, here::date);
+ yesterday := c_open_hours_on(hours, here::date - 1);
+
+ IF today IS NOT null
+    AND (today->>'opens')::time <= here::time
+    AND ((today->>'closes')::time > here::time OR (today->>'closes')::time <= (today->>'opens')::time)
+ THEN
+   RETURN true;
+ END IF;
+
+ IF yesterday IS NOT null
+    AND (yesterday->>'closes')::time <= (yesterday->>'opens')::time
+    AND here::time < (yesterday->>'closes')::time
+ THEN
+   RETURN true;
+ END IF;
+
+ RETURN false;
+EXCEPTION
+ WHEN others THEN
+   RETURN false;
+END
+$func$ language plpgsql stable;
+
+commit;
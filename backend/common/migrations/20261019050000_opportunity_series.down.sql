@@ -0,0 +1,7 @@
+begin;
+
+drop index if exists c_opportunity_by_parent_uid;
+
+alter table c_opportunity drop column if exists parent_uid;
+
+commit;
@@ -0,0 +1,11 @@
+begin;
+
+-- An opportunity may belong to a series, such as the events of a
+-- science festival, by naming the umbrella opportunity as its
+-- parent. There's no foreign key, because the parent and children
+-- are often imported separately and in no particular order.
+alter table c_opportunity add column parent_uid uuid;
+
+create index c_opportunity_by_parent_uid on c_opportunity (parent_uid) where parent_uid is not null;
+
+commit;
@@ -0,0 +1,42 @@
+begin;
+
+create or replace function c_opportunity_is_current_as_of(opp c_opportunity, stamp timestamptz) returns boolean as
+$func$
+BEGIN
+ RETURN (
+   (
+       coalesce(opp.review_status, 'not_required') IN ('publish', 'not_required')
+       OR
+       (opp.review_status = 'scheduled' AND opp.publish_at IS NOT null)
+   )
+   AND
+   (opp.publish_at IS null OR opp.publish_at <= stamp)
+   AND
+   (opp.unpublish_at IS null OR opp.unpublish_at > stamp)
+   AND
+   opp.accepted = true
+   AND
+   opp.withdrawn = false
+   AND
+   (
+       (
+          coalesce(array_length(opp.start_datetimes, 1), 0) <= 1
+          AND
+          coalesce(array_length(opp.end_datetimes, 1), 0) = 0
+       )
+       OR
+       EXISTS (SELECT 1 FROM c_opportunity_occurrence occ WHERE occ.opportunity_id = opp.id AND coalesce(occ.ends, occ.starts) > stamp)
+       OR
+       (opp.recurrence <> 'once' AND opp.recurrence_until IS null)
+       OR
+       opp.recurrence_until > stamp
+   )
+ );
+END
+$func$ language plpgsql stable;
+
+drop index if exists c_opportunity_by_trashed_at;
+
+alter table c_opportunity drop column if exists trashed_at;
+
+commit;
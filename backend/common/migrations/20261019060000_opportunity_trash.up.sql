@@ -0,0 +1,48 @@
+begin;
+
+-- Deleting an opportunity moves it to the trash, from which it can
+-- be restored until it is purged for good after the retention
+-- period. Trashed opportunities are never current, and are left out
+-- of searches.
+alter table c_opportunity add column trashed_at timestamptz;
+
+create index c_opportunity_by_trashed_at on c_opportunity (trashed_at) where trashed_at is not null;
+
+create or replace function c_opportunity_is_current_as_of(opp c_opportunity, stamp timestamptz) returns boolean as
+$func$
+BEGIN
+ RETURN (
+   (
+       coalesce(opp.review_status, 'not_required') IN ('publish', 'not_required')
+       OR
+       (opp.review_status = 'scheduled' AND opp.publish_at IS NOT null)
+   )
+   AND
+   (opp.publish_at IS null OR opp.publish_at <= stamp)
+   AND
+   (opp.unpublish_at IS null OR opp.unpublish_at > stamp)
+   AND
+   opp.trashed_at IS null
+   AND
+   opp.accepted = true
+   AND
+   opp.withdrawn = false
+   AND
+   (
+       (
+          coalesce(array_length(opp.start_datetimes, 1), 0) <= 1
+          AND
+          coalesce(array_length(opp.end_datetimes, 1), 0) = 0
+       )
+       OR
+       EXISTS (SELECT 1 FROM c_opportunity_occurrence occ WHERE occ.opportunity_id = opp.id AND coalesce(occ.ends, occ.starts) > stamp)
+       OR
+       (opp.recurrence <> 'once' AND opp.recurrence_until IS null)
+       OR
+       opp.recurrence_until > stamp
+   )
+ );
+END
+$func$ language plpgsql stable;
+
+commit;
@@ -191,6 +191,62 @@ struct OverviewDomain {
     value: i64,
 }
 
+/// Counts of the opportunities within each of the regions directly
+/// within the named region, for drilling down from a region to the
+/// regions it contains
+async fn region_children_counts(
+    db: &Database,
+    name: &str,
+) -> Result<BTreeMap<String, OverviewState>, sqlx::Error> {
+    use sqlx::Row;
+
+    // Like the regional overview, this has to be a runtime query
+    // because of SQLx's difficulty with the EXPLAIN output
+    sqlx::query(
+        r#"
+SELECT
+  r."name" AS "name!",
+  (
+    SELECT COUNT(x.*)
+    FROM (
+      SELECT DISTINCT ON (o.title, o.opp_partner) 1
+      FROM c_opportunity o
+      WHERE
+        c_opportunity_is_current(o) AND
+        ST_Intersects(r.geometry, o.location_point)
+      ORDER BY o.title, o.opp_partner
+    ) x
+  ) AS "point!",
+  (
+    SELECT COUNT(x.*)
+    FROM (
+      SELECT DISTINCT ON (o.title, o.opp_partner) 1
+      FROM c_opportunity o
+      WHERE
+        c_opportunity_is_current(o) AND
+        ST_Intersects(r.geometry, o.location_polygon)
+      ORDER BY o.title, o.opp_partner
+    ) x
+  ) AS "polygon!"
+FROM c_region r JOIN c_region p ON r.parent_id = p.id
+WHERE p."name" = $1
+"#,
+    )
+    .bind(name)
+    .map(|row: sqlx::postgres::PgRow| {
+        (
+            row.get("name!"),
+            OverviewState {
+                point: row.get("point!"),
+                polygon: row.get("polygon!"),
+            },
+        )
+    })
+    .fetch_all(db)
+    .await
+    .map(|rows| rows.into_iter().collect())
+}
+
 /// Unlike the actual search function, this function and related
 /// geo-explorer functions collapse opportunities of the same name
 /// from the same partner. That produces a result more suitable for
@@ -238,6 +294,11 @@ ORDER BY "total!" DESC
     .fetch_all(&db)
     .await?;
 
+    let children = match &name {
+        Some(name) => region_children_counts(&db, name).await?,
+        None => BTreeMap::new(),
+    };
+
     let (points_total, points_domains, polygons_total, polygons_domains) = if let Some(name) = name
     {
         let points_total = sqlx::query_scalar!(
@@ -433,6 +494,7 @@ ORDER BY "total!" DESC
                 "total": anywhere_total,
                 "domains": anywhere_domains,
             },
+            "children": children,
         }
     ))
 }
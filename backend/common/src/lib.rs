@@ -10,6 +10,7 @@ pub mod emails;
 pub mod geo;
 pub mod jwt;
 pub mod model;
+pub mod shapefile;
 pub mod time;
 pub mod timezones;
 
@@ -162,6 +163,38 @@ pub static LANGUAGES: Lazy<BTreeMap<String, String>> = Lazy::new(|| {
     .collect()
 });
 
+/// Parses a list of language tags in the format of an Accept-Language
+/// header, such as `es-MX, es;q=0.9, en;q=0.5`, into the tags in order
+/// of preference. Wildcards and tags with zero quality are dropped.
+pub fn language_preferences(header: &str) -> Vec<String> {
+    let mut weighted: Vec<(f32, &str)> = header
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let tag = parts.next()?.trim();
+
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if tag.is_empty() || tag == "*" || quality <= 0.0 {
+                None
+            } else {
+                Some((quality, tag))
+            }
+        })
+        .collect();
+
+    // The sort is stable, so equally weighted tags keep their order
+    weighted.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    weighted
+        .into_iter()
+        .map(|(_, tag)| tag.to_string())
+        .collect()
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Email error: {0}")]
@@ -415,8 +448,8 @@ pub fn html_to_md(input: &str) -> String {
     let output = EMPHATIC.replace_all(&output, "*");
 
     let output = LINK.replace_all(&output, |c: &Captures<'_>| {
-        if c.name("link").map_or(true, |m| m.as_str().is_empty())
-            || c.name("text").map_or(true, |m| m.as_str().is_empty())
+        if c.name("link").map_or("", |m| m.as_str()).is_empty()
+            || c.name("text").map_or("", |m| m.as_str()).is_empty()
         {
             String::new()
         } else {
@@ -450,4 +483,14 @@ mod tests {
         // This 'test' is supposed to fail, so we can see the output
         panic!("HTML->Markdown:\n{}", html_to_md(soup));
     }
+
+    #[test]
+    fn language_preferences_follow_quality() {
+        assert_eq!(
+            language_preferences("en;q=0.5, es-MX, *;q=0.1, es;q=0.9, fr;q=0"),
+            vec!["es-MX", "es", "en"]
+        );
+        assert_eq!(language_preferences("es"), vec!["es"]);
+        assert!(language_preferences("").is_empty());
+    }
 }
@@ -1,36 +1,39 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// (west, south, east, north) in degrees
+pub type BoundingBox = (f32, f32, f32, f32);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Point {
     pub coordinates: Vec<f32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiPoint {
     pub coordinates: Vec<Vec<f32>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LineString {
     pub coordinates: Vec<Vec<f32>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiLineString {
     pub coordinates: Vec<Vec<Vec<f32>>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Polygon {
     pub coordinates: Vec<Vec<Vec<f32>>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultiPolygon {
     pub coordinates: Vec<Vec<Vec<Vec<f32>>>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum Geometry {
     Point(Point),
@@ -42,23 +45,23 @@ pub enum Geometry {
     GeometryCollection(GeometryCollection),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeometryCollection {
     pub geometries: Vec<Geometry>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Feature {
     pub geometry: Option<Geometry>,
     pub properties: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureCollection {
     pub features: Vec<Feature>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum GeoJSON {
     Point(Point),
@@ -2,6 +2,7 @@ use once_cell::sync::Lazy;
 pub use opportunity::{Opportunity, OpportunityExterior, OpportunityInterior};
 pub use partner::Partner;
 pub use person::Person;
+pub use region::Region;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -14,6 +15,8 @@ pub mod opportunity;
 pub mod participation;
 pub mod partner;
 pub mod person;
+pub mod region;
+pub mod schema;
 pub mod serde_helpers;
 
 pub static ROOT_NAMESPACE: Lazy<uuid::Uuid> =
@@ -43,12 +46,22 @@ pub enum Error {
     NoSuch(&'static str),
 }
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum Pagination {
     All,
     One,
-    Page { index: u32, size: u32 },
+    Page {
+        index: u32,
+        size: u32,
+    },
+    /// Keyset pagination, continuing from an opaque cursor returned
+    /// along with a previous page. Without a cursor, starts from the
+    /// beginning of the results.
+    Cursor {
+        cursor: Option<String>,
+        size: u32,
+    },
 }
 
 impl Default for Pagination {
@@ -73,10 +86,21 @@ impl Pagination {
                 (last_index as f64 / *size as f64).floor() as u32,
                 *size,
             ),
+            Pagination::Cursor { size, .. } => {
+                (0, (last_index as f64 / *size as f64).floor() as u32, *size)
+            }
         }
     }
 }
 
+/// Opaque cursors for the pages before and after a page loaded with
+/// `Pagination::Cursor`
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PageCursors {
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
 /// Returns a string containing spaces inserted before each capital
 /// letter (except the first, if the first capital is also the first
 /// letter of the input string)
@@ -0,0 +1,449 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::types::PgInterval;
+use uuid::Uuid;
+
+use super::{Opportunity, RevisionAuthor};
+use crate::model::Error;
+use crate::{Database, ToFixedOffset};
+
+/// Pairs scoring at least this much are queued for review
+pub const DUPLICATE_THRESHOLD: f32 = 0.7;
+
+/// Locations further apart than this many meters don't count towards
+/// the score at all
+pub const DUPLICATE_RADIUS: f64 = 2000.0;
+
+/// Occurrences which start within this many minutes of each other are
+/// considered to overlap, even if neither has an end time
+pub const DUPLICATE_SLACK_MINUTES: i64 = 60;
+
+const TITLE_WEIGHT: f32 = 0.4;
+const LOCATION_WEIGHT: f32 = 0.4;
+const TIME_WEIGHT: f32 = 0.2;
+
+/// The evidence that two opportunities are copies of each other
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateScore {
+    /// Trigram similarity of the titles, from 0 to 1
+    pub title_similarity: f32,
+    /// Meters between the location points, or None if either lacks
+    /// one. Two online opportunities are zero meters apart.
+    pub distance: Option<f64>,
+    /// Whether any of their occurrences overlap. Two opportunities
+    /// without any scheduled times also count as overlapping.
+    pub overlapping: bool,
+}
+
+impl DuplicateScore {
+    /// Combines the evidence into a single score from 0 to 1
+    pub fn score(&self) -> f32 {
+        let nearness = match self.distance {
+            Some(distance) => (1.0 - distance / DUPLICATE_RADIUS).max(0.0) as f32,
+            None => 0.0,
+        };
+
+        let overlap = if self.overlapping { 1.0 } else { 0.0 };
+
+        TITLE_WEIGHT * self.title_similarity.clamp(0.0, 1.0)
+            + LOCATION_WEIGHT * nearness
+            + TIME_WEIGHT * overlap
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateStatus {
+    Pending,
+    Merged,
+    Dismissed,
+}
+
+impl DuplicateStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateStatus::Pending => "pending",
+            DuplicateStatus::Merged => "merged",
+            DuplicateStatus::Dismissed => "dismissed",
+        }
+    }
+
+    fn from_str(status: &str) -> DuplicateStatus {
+        match status {
+            "merged" => DuplicateStatus::Merged,
+            "dismissed" => DuplicateStatus::Dismissed,
+            _ => DuplicateStatus::Pending,
+        }
+    }
+}
+
+/// Enough of an opportunity to tell which one it is in the review queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateSide {
+    pub uid: Uuid,
+    pub slug: String,
+    pub title: String,
+    pub partner_name: String,
+}
+
+/// A pair of opportunities which appear to be the same thing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Duplicate {
+    pub id: i32,
+    pub first: DuplicateSide,
+    pub second: DuplicateSide,
+    pub score: f32,
+    pub evidence: DuplicateScore,
+    pub status: DuplicateStatus,
+    pub detected: DateTime<FixedOffset>,
+}
+
+macro_rules! duplicate_from_row {
+    ($row:expr) => {
+        Duplicate {
+            id: $row.id,
+            first: DuplicateSide {
+                uid: $row.first_uid,
+                slug: $row.first_slug,
+                title: $row.first_title,
+                partner_name: $row.first_partner_name,
+            },
+            second: DuplicateSide {
+                uid: $row.second_uid,
+                slug: $row.second_slug,
+                title: $row.second_title,
+                partner_name: $row.second_partner_name,
+            },
+            score: $row.score,
+            evidence: DuplicateScore {
+                title_similarity: $row.title_similarity,
+                distance: $row.distance,
+                overlapping: $row.overlapping,
+            },
+            status: DuplicateStatus::from_str(&$row.status),
+            detected: $row.detected.to_fixed_offset(),
+        }
+    };
+}
+
+impl Duplicate {
+    /// All pairs with the given status, highest scoring first
+    pub async fn load_with_status(
+        db: &Database,
+        status: DuplicateStatus,
+    ) -> Result<Vec<Duplicate>, Error> {
+        Ok(
+            sqlx::query_file!("db/opportunity/duplicates.sql", status.as_str())
+                .map(|row| duplicate_from_row!(row))
+                .fetch_all(db)
+                .await?,
+        )
+    }
+
+    pub async fn load_by_id(db: &Database, id: i32) -> Result<Duplicate, Error> {
+        sqlx::query_file!("db/opportunity/get_duplicate.sql", id)
+            .map(|row| duplicate_from_row!(row))
+            .fetch_optional(db)
+            .await?
+            .ok_or(Error::NoSuch("duplicate"))
+    }
+
+    /// Marks the pair as not actually being duplicates, so that it
+    /// isn't queued again
+    pub async fn dismiss(&mut self, db: &Database, by: Option<Uuid>) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+UPDATE c_opportunity_duplicate
+SET status = $2, resolved = CURRENT_TIMESTAMP, resolved_by = $3
+WHERE id = $1
+"#,
+            self.id,
+            DuplicateStatus::Dismissed.as_str(),
+            by,
+        )
+        .execute(db)
+        .await?;
+
+        self.status = DuplicateStatus::Dismissed;
+
+        Ok(())
+    }
+}
+
+impl Opportunity {
+    /// Scores the opportunity against others with similar titles, and
+    /// updates the review queue to hold the pairs which look like
+    /// duplicates. Pairs which were already merged or dismissed are
+    /// left alone.
+    pub(super) async fn detect_duplicates(db: &Database, id: i32) -> Result<(), Error> {
+        let slack = PgInterval {
+            months: 0,
+            days: 0,
+            microseconds: DUPLICATE_SLACK_MINUTES * 60 * 1_000_000,
+        };
+
+        let candidates = sqlx::query_file!("db/opportunity/duplicate_candidates.sql", id, slack)
+            .fetch_all(db)
+            .await?;
+
+        let mut kept = Vec::new();
+
+        for candidate in candidates {
+            let evidence = DuplicateScore {
+                title_similarity: candidate.title_similarity,
+                distance: candidate.distance,
+                overlapping: candidate.overlapping,
+            };
+
+            let score = evidence.score();
+
+            if score < DUPLICATE_THRESHOLD {
+                continue;
+            }
+
+            sqlx::query_file!(
+                "db/opportunity/upsert_duplicate.sql",
+                id,
+                candidate.id,
+                score,
+                evidence.title_similarity,
+                evidence.distance,
+                evidence.overlapping,
+            )
+            .execute(db)
+            .await?;
+
+            kept.push(candidate.id);
+        }
+
+        sqlx::query_file!("db/opportunity/clear_duplicates.sql", id, &kept as &[i32])
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Folds the `duplicate` opportunity into the `canonical` one.
+    /// Involvements, reviews and likes are moved over, keeping the
+    /// canonical record's where a person has both, and the duplicate's
+    /// slug is redirected to the canonical opportunity. The duplicate
+    /// itself is marked as not accepted rather than deleted, so that
+    /// importing it again doesn't bring it back.
+    pub async fn merge(
+        db: &Database,
+        canonical: &Uuid,
+        duplicate: &Uuid,
+        author: &RevisionAuthor,
+    ) -> Result<Opportunity, Error> {
+        if canonical == duplicate {
+            return Err(Error::Value("duplicate".to_string()));
+        }
+
+        let canonical = Opportunity::load_by_uid(db, canonical).await?;
+        let mut duplicate = Opportunity::load_by_uid(db, duplicate).await?;
+
+        let (Some(canonical_id), Some(duplicate_id)) = (canonical.id, duplicate.id) else {
+            return Err(Error::NoSuch("opportunity"));
+        };
+
+        let mut tx = db.begin().await?;
+
+        sqlx::query!(
+            r#"
+UPDATE c_involvement c
+SET mode = greatest(c.mode, d.mode), first = least(c.first, d.first), latest = greatest(c.latest, d.latest)
+FROM c_involvement d
+WHERE c.opportunity = $1 AND d.opportunity = $2 AND c.participant = d.participant
+"#,
+            canonical.exterior.uid,
+            duplicate.exterior.uid,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+DELETE FROM c_involvement d
+WHERE d.opportunity = $2 AND EXISTS (
+  SELECT 1 FROM c_involvement c WHERE c.opportunity = $1 AND c.participant = d.participant
+)
+"#,
+            canonical.exterior.uid,
+            duplicate.exterior.uid,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE c_involvement SET opportunity = $1 WHERE opportunity = $2",
+            canonical.exterior.uid,
+            duplicate.exterior.uid,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+DELETE FROM c_opportunity_review d
+WHERE d.opportunity_id = $2 AND EXISTS (
+  SELECT 1 FROM c_opportunity_review c WHERE c.opportunity_id = $1 AND c.person = d.person
+)
+"#,
+            canonical_id,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE c_opportunity_review SET opportunity_id = $1 WHERE opportunity_id = $2",
+            canonical_id,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Where someone registered for the same occurrence of both,
+        // keep the registration on the canonical opportunity
+        sqlx::query!(
+            r#"
+DELETE FROM c_rsvp d
+WHERE d.opportunity_id = $2 AND EXISTS (
+  SELECT 1 FROM c_rsvp c WHERE c.opportunity_id = $1 AND c.occurrence = d.occurrence AND c.person_id = d.person_id
+)
+"#,
+            canonical_id,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE c_rsvp SET opportunity_id = $1 WHERE opportunity_id = $2",
+            canonical_id,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // Anonymous likes have a null person, which never matches, so
+        // they all move over
+        sqlx::query!(
+            r#"
+DELETE FROM c_opportunity_like d
+WHERE d.opportunity_id = $2 AND EXISTS (
+  SELECT 1 FROM c_opportunity_like c WHERE c.opportunity_id = $1 AND c.person = d.person
+)
+"#,
+            canonical_id,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE c_opportunity_like SET opportunity_id = $1 WHERE opportunity_id = $2",
+            canonical_id,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE c_opportunity_redirect SET opportunity_id = $1 WHERE opportunity_id = $2",
+            canonical_id,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+INSERT INTO c_opportunity_redirect (slug, opportunity_id)
+VALUES ($1, $2)
+ON CONFLICT (slug) DO UPDATE SET opportunity_id = $2
+"#,
+            duplicate.exterior.slug,
+            canonical_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+INSERT INTO c_opportunity_duplicate (first_id, second_id, score, title_similarity, overlapping, status, resolved, resolved_by)
+VALUES (least($1::integer, $2::integer), greatest($1::integer, $2::integer), 0, 0, false, $3, CURRENT_TIMESTAMP, $4)
+ON CONFLICT (first_id, second_id) DO UPDATE
+SET status = $3, resolved = CURRENT_TIMESTAMP, resolved_by = $4
+"#,
+            canonical_id,
+            duplicate_id,
+            DuplicateStatus::Merged.as_str(),
+            author.uid(),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+DELETE FROM c_opportunity_duplicate
+WHERE status = 'pending' AND (first_id = $1 OR second_id = $1)
+"#,
+            duplicate_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        duplicate.interior.accepted = Some(false);
+        duplicate.store_by(db, author, "merge").await?;
+
+        Ok(canonical)
+    }
+
+    /// The current slug of the opportunity which `slug` was merged
+    /// into, if it was
+    pub async fn redirect_for_slug(db: &Database, slug: &str) -> Result<Option<String>, Error> {
+        Ok(
+            sqlx::query_file_scalar!("db/opportunity/redirect_by_slug.sql", slug)
+                .fetch_optional(db)
+                .await?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_weighs_title_place_and_time() {
+        let same = DuplicateScore {
+            title_similarity: 1.0,
+            distance: Some(0.0),
+            overlapping: true,
+        };
+        assert!((same.score() - 1.0).abs() < 1e-6);
+
+        let far = DuplicateScore {
+            distance: Some(DUPLICATE_RADIUS * 3.0),
+            ..same
+        };
+        assert!(far.score() < DUPLICATE_THRESHOLD);
+
+        let unplaced = DuplicateScore {
+            title_similarity: 0.6,
+            distance: None,
+            overlapping: true,
+        };
+        assert!(unplaced.score() < DUPLICATE_THRESHOLD);
+
+        let nearby = DuplicateScore {
+            title_similarity: 0.6,
+            distance: Some(DUPLICATE_RADIUS / 4.0),
+            overlapping: true,
+        };
+        assert!(nearby.score() >= DUPLICATE_THRESHOLD);
+    }
+}
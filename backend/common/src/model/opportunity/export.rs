@@ -0,0 +1,425 @@
+//! Opportunities as schema.org `Event` JSON-LD, for search engine rich
+//! results and for exchange with other aggregators, and as GeoJSON
+//! features for mapping tools. The JSON-LD is shaped so that the
+//! importer's `LdJson` structure can read it back in.
+
+use chrono::{DateTime, FixedOffset, Weekday};
+use serde_json::{json, Map, Value};
+
+use super::feed::{geo_point, SITE};
+use super::recurrence::Frequency;
+use super::{Cost, LocationType, OpportunityExterior};
+use crate::model::SelectOption;
+
+const SCHEMA_ORG: &str = "https://schema.org";
+
+fn schema_org(term: &str) -> Value {
+    Value::String(format!("{}/{}", SCHEMA_ORG, term))
+}
+
+fn day_of_week(weekday: Weekday) -> Value {
+    schema_org(match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    })
+}
+
+fn timestamps(times: &[DateTime<FixedOffset>]) -> Vec<String> {
+    times.iter().map(|dt| dt.to_rfc3339()).collect()
+}
+
+/// Inserts `value` unless it is an empty string
+fn insert_text(obj: &mut Map<String, Value>, key: &str, value: &str) {
+    if !value.trim().is_empty() {
+        obj.insert(key.to_string(), Value::String(value.to_string()));
+    }
+}
+
+impl OpportunityExterior {
+    /// The page for this opportunity on Science Near Me
+    pub fn public_url(&self) -> String {
+        format!("{}/{}", SITE, self.slug)
+    }
+
+    fn json_ld_address(&self) -> Value {
+        let mut address = Map::new();
+        address.insert("@type".to_string(), json!("PostalAddress"));
+        insert_text(&mut address, "streetAddress", &self.address_street);
+        insert_text(&mut address, "addressLocality", &self.address_city);
+        insert_text(&mut address, "addressRegion", &self.address_state);
+        insert_text(&mut address, "postalCode", &self.address_zip);
+        insert_text(&mut address, "addressCountry", &self.address_country);
+        Value::Object(address)
+    }
+
+    fn json_ld_location(&self) -> Value {
+        let mut places = Vec::new();
+
+        if self.location_type != LocationType::Any || !self.is_online {
+            let mut place = Map::new();
+            place.insert("@type".to_string(), json!("Place"));
+            place.insert("name".to_string(), json!(self.location_name));
+            place.insert("address".to_string(), self.json_ld_address());
+
+            if let Some((latitude, longitude)) = geo_point(self) {
+                place.insert(
+                    "geo".to_string(),
+                    json!({
+                        "@type": "GeoCoordinates",
+                        "latitude": latitude,
+                        "longitude": longitude,
+                    }),
+                );
+            }
+
+            places.push(Value::Object(place));
+        }
+
+        if self.is_online {
+            places.push(json!({
+                "@type": "VirtualLocation",
+                "url": self.partner_opp_url.clone().unwrap_or_else(|| self.public_url()),
+            }));
+        }
+
+        match places.len() {
+            1 => places.remove(0),
+            _ => Value::Array(places),
+        }
+    }
+
+    fn json_ld_attendance_mode(&self) -> Value {
+        schema_org(match (self.is_online, self.location_type) {
+            (false, _) => "OfflineEventAttendanceMode",
+            (true, LocationType::Any) => "OnlineEventAttendanceMode",
+            (true, _) => "MixedEventAttendanceMode",
+        })
+    }
+
+    /// The recurrence as a schema.org `Schedule`. Rules which select
+    /// the nth weekday of a month or year, or use BYSETPOS, can not be
+    /// expressed as a `Schedule` and are left out.
+    fn json_ld_schedule(&self) -> Option<Value> {
+        let rule = self.recurrence_rule()?;
+
+        if !rule.by_set_pos.is_empty() || rule.by_day.iter().any(|day| day.ordinal.is_some()) {
+            return None;
+        }
+
+        let start = self.start_datetimes.first()?;
+
+        let mut schedule = Map::new();
+        schedule.insert("@type".to_string(), json!("Schedule"));
+        schedule.insert("startDate".to_string(), json!(start.date_naive()));
+        schedule.insert("startTime".to_string(), json!(start.time()));
+
+        if let Some(end) = self.end_datetimes.first() {
+            schedule.insert("endTime".to_string(), json!(end.time()));
+        }
+
+        schedule.insert(
+            "repeatFrequency".to_string(),
+            Value::String(format!(
+                "P{}{}",
+                rule.interval,
+                match rule.frequency {
+                    Frequency::Daily => "D",
+                    Frequency::Weekly => "W",
+                    Frequency::Monthly => "M",
+                    Frequency::Yearly => "Y",
+                }
+            )),
+        );
+
+        if !rule.by_day.is_empty() {
+            schedule.insert(
+                "byDay".to_string(),
+                rule.by_day
+                    .iter()
+                    .map(|day| day_of_week(day.weekday))
+                    .collect(),
+            );
+        }
+
+        if !rule.by_month_day.is_empty() {
+            schedule.insert("byMonthDay".to_string(), json!(rule.by_month_day));
+        }
+
+        if !rule.by_month.is_empty() {
+            schedule.insert("byMonth".to_string(), json!(rule.by_month));
+        }
+
+        if let Some(count) = rule.count {
+            schedule.insert("repeatCount".to_string(), json!(count));
+        }
+
+        if let Some(until) = rule.until {
+            schedule.insert("endDate".to_string(), json!(until.date_naive()));
+        }
+
+        if !self.recurrence_exdates.is_empty() {
+            schedule.insert(
+                "exceptDate".to_string(),
+                json!(timestamps(&self.recurrence_exdates)),
+            );
+        }
+
+        if let Some(timezone) = &self.timezone {
+            schedule.insert("scheduleTimezone".to_string(), json!(timezone));
+        }
+
+        Some(Value::Object(schedule))
+    }
+
+    /// The opportunity as a schema.org `Event`
+    pub fn to_json_ld(&self) -> Value {
+        let url = self.public_url();
+
+        let mut event = Map::new();
+        event.insert("@context".to_string(), json!(SCHEMA_ORG));
+        event.insert("@type".to_string(), json!("Event"));
+        event.insert("@id".to_string(), json!(url));
+        event.insert("identifier".to_string(), json!(self.uid));
+        event.insert("name".to_string(), json!(self.title));
+        event.insert("description".to_string(), json!(self.description));
+        event.insert("url".to_string(), json!(url));
+        event.insert("image".to_string(), json!(self.image_url));
+        event.insert("eventStatus".to_string(), schema_org("EventScheduled"));
+        event.insert(
+            "eventAttendanceMode".to_string(),
+            self.json_ld_attendance_mode(),
+        );
+        event.insert("location".to_string(), self.json_ld_location());
+
+        if let Some(same_as) = &self.partner_opp_url {
+            event.insert("sameAs".to_string(), json!(same_as));
+        }
+
+        if let Some(start) = self.start_datetimes.first() {
+            event.insert("startDate".to_string(), json!(start.to_rfc3339()));
+        }
+
+        if let Some(end) = self.end_datetimes.first() {
+            event.insert("endDate".to_string(), json!(end.to_rfc3339()));
+        }
+
+        if self.start_datetimes.len() > 1 {
+            event.insert(
+                "subEvent".to_string(),
+                self.start_datetimes
+                    .iter()
+                    .enumerate()
+                    .map(|(i, start)| {
+                        let mut sub = json!({
+                            "@type": "Event",
+                            "name": self.title,
+                            "startDate": start.to_rfc3339(),
+                        });
+
+                        if let Some(end) = self.end_datetimes.get(i) {
+                            sub["endDate"] = json!(end.to_rfc3339());
+                        }
+
+                        sub
+                    })
+                    .collect(),
+            );
+        }
+
+        if let Some(schedule) = self.json_ld_schedule() {
+            event.insert("eventSchedule".to_string(), schedule);
+        }
+
+        let (organizer_name, organizer_url) = if self.organization_name.trim().is_empty() {
+            (&self.partner_name, &self.partner_website)
+        } else {
+            (&self.organization_name, &self.organization_website)
+        };
+
+        event.insert(
+            "organizer".to_string(),
+            json!({
+                "@type": "Organization",
+                "name": organizer_name,
+                "url": organizer_url.clone().unwrap_or_default(),
+            }),
+        );
+
+        if self.cost != Cost::Unknown {
+            event.insert(
+                "isAccessibleForFree".to_string(),
+                json!(self.cost == Cost::Free),
+            );
+
+            let mut offer = json!({
+                "@type": "Offer",
+                "url": self.partner_opp_url.clone().unwrap_or_else(|| url.clone()),
+            });
+
+            // We only know the price when it's nothing
+            if self.cost == Cost::Free {
+                offer["price"] = json!("0");
+                offer["priceCurrency"] = json!("USD");
+            }
+
+            event.insert("offers".to_string(), offer);
+        }
+
+        if self.min_age > 0 || self.max_age < 999 {
+            event.insert(
+                "typicalAgeRange".to_string(),
+                json!(if self.max_age < 999 {
+                    format!("{}-{}", self.min_age, self.max_age)
+                } else {
+                    format!("{}-", self.min_age)
+                }),
+            );
+        }
+
+        if let Some(capacity) = self.capacity {
+            event.insert("maximumAttendeeCapacity".to_string(), json!(capacity));
+        }
+
+        if !self.languages.is_empty() {
+            event.insert("inLanguage".to_string(), json!(self.languages));
+        }
+
+        let keywords: Vec<String> = self
+            .opp_topics
+            .iter()
+            .map(|topic| topic.to_option().1)
+            .collect();
+
+        if !keywords.is_empty() {
+            event.insert("keywords".to_string(), json!(keywords.join(", ")));
+        }
+
+        Value::Object(event)
+    }
+
+    /// The opportunity as a GeoJSON `Feature`, located at its point
+    /// or, failing that, its polygon. Online-only opportunities have
+    /// no geometry.
+    pub fn to_geojson(&self) -> Value {
+        let geometry = self
+            .location_point
+            .clone()
+            .or_else(|| self.location_polygon.clone())
+            .unwrap_or(Value::Null);
+
+        json!({
+            "type": "Feature",
+            "id": self.uid,
+            "geometry": geometry,
+            "properties": {
+                "uid": self.uid,
+                "slug": self.slug,
+                "url": self.public_url(),
+                "title": self.title,
+                "short_desc": self.short_desc,
+                "image_url": self.image_url,
+                "partner": self.partner,
+                "partner_name": self.partner_name,
+                "organization_name": self.organization_name,
+                "entity_type": self.entity_type,
+                "is_online": self.is_online,
+                "location_type": self.location_type,
+                "location_name": self.location_name,
+                "address_street": self.address_street,
+                "address_city": self.address_city,
+                "address_state": self.address_state,
+                "address_zip": self.address_zip,
+                "address_country": self.address_country,
+                "start_datetimes": self.start_datetimes,
+                "end_datetimes": self.end_datetimes,
+                "timezone": self.timezone,
+                "cost": self.cost,
+                "min_age": self.min_age,
+                "max_age": self.max_age,
+                "opp_topics": self.opp_topics,
+            },
+        })
+    }
+}
+
+/// Several opportunities as one JSON-LD document, with an `Event` for
+/// each in its `@graph`
+pub fn json_ld_graph(opps: &[OpportunityExterior]) -> Value {
+    json!({
+        "@context": SCHEMA_ORG,
+        "@graph": opps.iter().map(OpportunityExterior::to_json_ld).collect::<Vec<_>>(),
+    })
+}
+
+/// Several opportunities as a GeoJSON `FeatureCollection`
+pub fn feature_collection(opps: &[OpportunityExterior]) -> Value {
+    json!({
+        "type": "FeatureCollection",
+        "features": opps.iter().map(OpportunityExterior::to_geojson).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::opportunity::{Recurrence, Topic};
+    use uuid::Uuid;
+
+    fn dt(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    #[test]
+    fn weekly_rule_becomes_schedule() {
+        let opp = OpportunityExterior {
+            uid: Uuid::nil(),
+            slug: "star-party".to_string(),
+            title: "Star Party".to_string(),
+            timezone: Some("America/New_York".to_string()),
+            start_datetimes: vec![dt("2026-10-20T19:00:00-04:00")],
+            end_datetimes: vec![dt("2026-10-20T22:00:00-04:00")],
+            recurrence: Recurrence::Rule(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU;COUNT=4".parse().unwrap(),
+            ),
+            opp_topics: vec![Topic::AstronomyAndSpace],
+            ..Default::default()
+        };
+
+        let event = opp.to_json_ld();
+
+        assert_eq!(event["@type"], "Event");
+        assert_eq!(event["url"], "https://sciencenearme.org/star-party");
+        assert_eq!(event["startDate"], "2026-10-20T19:00:00-04:00");
+        assert_eq!(event["eventSchedule"]["repeatFrequency"], "P2W");
+        assert_eq!(
+            event["eventSchedule"]["byDay"],
+            json!(["https://schema.org/Tuesday"])
+        );
+        assert_eq!(event["eventSchedule"]["repeatCount"], 4);
+        assert_eq!(event["eventSchedule"]["startTime"], "19:00:00");
+        assert_eq!(event["keywords"], "Astronomy And Space");
+    }
+
+    #[test]
+    fn online_opportunities_have_no_geometry() {
+        let opp = OpportunityExterior {
+            is_online: true,
+            location_type: LocationType::Any,
+            ..Default::default()
+        };
+
+        let collection = feature_collection(&[opp]);
+
+        assert_eq!(collection["type"], "FeatureCollection");
+        assert_eq!(collection["features"][0]["geometry"], Value::Null);
+        assert_eq!(
+            collection["features"][0]["properties"]["is_online"],
+            json!(true)
+        );
+    }
+}
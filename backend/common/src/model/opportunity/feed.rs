@@ -0,0 +1,604 @@
+//! Opportunities as iCalendar (RFC 5545) and Atom (RFC 4287) feeds,
+//! for subscribing to a search from calendar apps and newsletter
+//! tools. Each opportunity with a start time becomes a VEVENT, with
+//! its recurrence expressed as RRULE, RDATE and EXDATE so that
+//! calendar apps expand it themselves.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Offset, SecondsFormat, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+use uuid::Uuid;
+
+use super::{LocationType, Opportunity, OpportunityExterior};
+use crate::model::{Error, SelectOption};
+use crate::{Database, ToFixedOffset};
+
+pub(super) const SITE: &str = "https://sciencenearme.org";
+
+// Longest iCalendar content line, in octets, before it must be folded
+const LINE_LIMIT: usize = 75;
+
+// Number of years after the current one which VTIMEZONE components
+// describe the transitions of
+const TIMEZONE_YEARS_AHEAD: i32 = 2;
+
+/// An opportunity included in a feed, along with when it was last
+/// changed
+pub struct FeedEntry {
+    pub opportunity: OpportunityExterior,
+    pub updated: DateTime<FixedOffset>,
+}
+
+impl FeedEntry {
+    fn url(&self) -> String {
+        format!("{}/{}", SITE, self.opportunity.slug)
+    }
+}
+
+impl Opportunity {
+    /// Pairs each opportunity with the time it was last stored, for
+    /// inclusion in a feed
+    pub async fn feed_entries(
+        db: &Database,
+        opps: Vec<Opportunity>,
+    ) -> Result<Vec<FeedEntry>, Error> {
+        let uids: Vec<Uuid> = opps.iter().map(|opp| opp.exterior.uid).collect();
+
+        let updated: HashMap<Uuid, DateTime<FixedOffset>> =
+            sqlx::query_file!("db/opportunity/updated.sql", &uids)
+                .map(|row| (row.uid, row.updated.to_fixed_offset()))
+                .fetch_all(db)
+                .await?
+                .into_iter()
+                .collect();
+
+        Ok(opps
+            .into_iter()
+            .filter_map(|opp| {
+                Some(FeedEntry {
+                    updated: *updated.get(&opp.exterior.uid)?,
+                    opportunity: opp.exterior,
+                })
+            })
+            .collect())
+    }
+}
+
+/// The most recent change among the entries
+pub fn last_updated(entries: &[FeedEntry]) -> Option<DateTime<FixedOffset>> {
+    entries.iter().map(|entry| entry.updated).max()
+}
+
+/// Where the opportunity takes place, as a single line of text
+fn location_text(opp: &OpportunityExterior) -> String {
+    if opp.is_online && opp.location_type == LocationType::Any {
+        return "Online".to_string();
+    }
+
+    [
+        &opp.location_name,
+        &opp.address_street,
+        &opp.address_city,
+        &opp.address_state,
+        &opp.address_zip,
+        &opp.address_country,
+    ]
+    .iter()
+    .map(|part| part.trim())
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Latitude and longitude of the opportunity's GeoJSON point
+pub(super) fn geo_point(opp: &OpportunityExterior) -> Option<(f64, f64)> {
+    let coords = opp.location_point.as_ref()?.get("coordinates")?;
+    Some((coords.get(1)?.as_f64()?, coords.get(0)?.as_f64()?))
+}
+
+/// The value and display name of each of the opportunity's topics
+fn topics(opp: &OpportunityExterior) -> Vec<(String, String)> {
+    opp.opp_topics
+        .iter()
+        .map(|topic| {
+            let (value, name, _) = topic.to_option();
+            (value, name)
+        })
+        .collect()
+}
+
+fn zone(opp: &OpportunityExterior) -> Option<Tz> {
+    opp.timezone.as_deref().and_then(|name| name.parse().ok())
+}
+
+/// Escapes a value of the iCalendar TEXT type
+fn ical_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn utc_stamp(dt: &DateTime<FixedOffset>) -> String {
+    dt.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The parameters and value of a DATE-TIME property: local time with
+/// a TZID when the opportunity names its zone, or UTC otherwise
+fn ical_times(zone: Option<Tz>, times: &[DateTime<FixedOffset>]) -> (String, String) {
+    match zone {
+        Some(tz) => (
+            format!(";TZID={}", tz.name()),
+            times
+                .iter()
+                .map(|dt| dt.with_timezone(&tz).format("%Y%m%dT%H%M%S").to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        ),
+        None => (
+            String::new(),
+            times.iter().map(utc_stamp).collect::<Vec<_>>().join(","),
+        ),
+    }
+}
+
+fn ical_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    format!("{}{:02}{:02}", sign, seconds / 3600, (seconds % 3600) / 60)
+}
+
+/// Accumulates content lines, folding them at 75 octets
+struct Lines(String);
+
+impl Lines {
+    fn push(&mut self, name: &str, value: &str) {
+        let line = format!("{}:{}", name, value);
+        let mut width = 0;
+
+        for c in line.chars() {
+            if width + c.len_utf8() > LINE_LIMIT {
+                self.0.push_str("\r\n ");
+                width = 1;
+            }
+            self.0.push(c);
+            width += c.len_utf8();
+        }
+
+        self.0.push_str("\r\n");
+    }
+}
+
+/// A VTIMEZONE describing the offsets of `tz` from the beginning of
+/// `from_year` through `to_year`. Each transition is listed as its
+/// own observance, which avoids needing to express the zone's rules.
+fn push_timezone(lines: &mut Lines, tz: Tz, from_year: i32, to_year: i32) {
+    let begin = tz
+        .with_ymd_and_hms(from_year, 1, 1, 0, 0, 0)
+        .earliest()
+        .expect("Zones do not skip over New Year's midnight")
+        .with_timezone(&Utc);
+    let end = Utc
+        .with_ymd_and_hms(to_year + 1, 1, 1, 0, 0, 0)
+        .single()
+        .expect("January 1st midnight UTC always exists");
+
+    let offset_at = |at: DateTime<Utc>| tz.offset_from_utc_datetime(&at.naive_utc());
+
+    let mut observances = vec![(begin, offset_at(begin), offset_at(begin))];
+
+    let mut at = begin;
+    while at < end {
+        let next = at + Duration::days(1);
+        let (before, after) = (offset_at(at), offset_at(next));
+
+        if before.fix() != after.fix() {
+            // Narrow down to the first second with the new offset
+            let (mut lo, mut hi) = (at, next);
+            while hi - lo > Duration::seconds(1) {
+                let mid = lo + (hi - lo) / 2;
+                if offset_at(mid).fix() == before.fix() {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            observances.push((hi, before, after));
+        }
+
+        at = next;
+    }
+
+    lines.push("BEGIN", "VTIMEZONE");
+    lines.push("TZID", tz.name());
+
+    for (onset, from, to) in observances {
+        let kind = if to.dst_offset() == Duration::zero() {
+            "STANDARD"
+        } else {
+            "DAYLIGHT"
+        };
+
+        lines.push("BEGIN", kind);
+        lines.push(
+            "DTSTART",
+            &onset
+                .with_timezone(&from.fix())
+                .format("%Y%m%dT%H%M%S")
+                .to_string(),
+        );
+        lines.push("TZOFFSETFROM", &ical_offset(from.fix()));
+        lines.push("TZOFFSETTO", &ical_offset(to.fix()));
+        lines.push("TZNAME", to.abbreviation());
+        lines.push("END", kind);
+    }
+
+    lines.push("END", "VTIMEZONE");
+}
+
+fn push_event(lines: &mut Lines, entry: &FeedEntry, index: usize) {
+    let opp = &entry.opportunity;
+    let zone = zone(opp);
+    let start = opp.start_datetimes[index];
+
+    lines.push("BEGIN", "VEVENT");
+
+    if index == 0 {
+        lines.push("UID", &format!("{}@sciencenearme.org", opp.uid));
+    } else {
+        lines.push("UID", &format!("{}-{}@sciencenearme.org", opp.uid, index));
+    }
+
+    lines.push("DTSTAMP", &utc_stamp(&entry.updated));
+    lines.push("LAST-MODIFIED", &utc_stamp(&entry.updated));
+
+    let (params, value) = ical_times(zone, &[start]);
+    lines.push(&format!("DTSTART{}", params), &value);
+
+    if let Some(end) = opp.end_datetimes.get(index).filter(|end| **end > start) {
+        let (params, value) = ical_times(zone, &[*end]);
+        lines.push(&format!("DTEND{}", params), &value);
+    }
+
+    // The recurrence belongs to the first start time, while any
+    // further start times are single occurrences
+    if index == 0 {
+        if let Some(rule) = opp.recurrence_rule() {
+            lines.push("RRULE", &rule.to_string());
+        }
+
+        if !opp.recurrence_rdates.is_empty() {
+            let (params, value) = ical_times(zone, &opp.recurrence_rdates);
+            lines.push(&format!("RDATE{}", params), &value);
+        }
+
+        if !opp.recurrence_exdates.is_empty() {
+            let (params, value) = ical_times(zone, &opp.recurrence_exdates);
+            lines.push(&format!("EXDATE{}", params), &value);
+        }
+    }
+
+    lines.push("SUMMARY", &ical_text(&opp.title));
+
+    if !opp.short_desc.is_empty() {
+        lines.push("DESCRIPTION", &ical_text(&opp.short_desc));
+    }
+
+    lines.push("URL", &entry.url());
+
+    let location = location_text(opp);
+    if !location.is_empty() {
+        lines.push("LOCATION", &ical_text(&location));
+    }
+
+    if let Some((latitude, longitude)) = geo_point(opp) {
+        lines.push("GEO", &format!("{};{}", latitude, longitude));
+    }
+
+    let topics = topics(opp);
+    if !topics.is_empty() {
+        lines.push(
+            "CATEGORIES",
+            &topics
+                .iter()
+                .map(|(_, name)| ical_text(name))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    lines.push("END", "VEVENT");
+}
+
+/// An iCalendar document with an event for each start time of each
+/// entry. Entries without start times, such as most attractions,
+/// are left out.
+pub fn ical(name: &str, entries: &[FeedEntry], now: &DateTime<FixedOffset>) -> String {
+    let mut lines = Lines(String::new());
+
+    lines.push("BEGIN", "VCALENDAR");
+    lines.push("VERSION", "2.0");
+    lines.push("PRODID", "-//Science Near Me//Opportunity Feed//EN");
+    lines.push("CALSCALE", "GREGORIAN");
+    lines.push("METHOD", "PUBLISH");
+    lines.push("X-WR-CALNAME", &ical_text(name));
+
+    // Earliest year in which each named zone is used
+    let mut zones: BTreeMap<&str, (Tz, i32)> = BTreeMap::new();
+    for entry in entries {
+        let opp = &entry.opportunity;
+        if let (Some(tz), Some(first)) = (zone(opp), opp.start_datetimes.iter().min()) {
+            let year = first.with_timezone(&tz).year();
+            let slot = zones.entry(tz.name()).or_insert((tz, year));
+            slot.1 = slot.1.min(year);
+        }
+    }
+
+    let to_year = now.year() + TIMEZONE_YEARS_AHEAD;
+    for (tz, from_year) in zones.into_values() {
+        push_timezone(&mut lines, tz, from_year.min(to_year), to_year);
+    }
+
+    for entry in entries {
+        for index in 0..entry.opportunity.start_datetimes.len() {
+            push_event(&mut lines, entry, index);
+        }
+    }
+
+    lines.push("END", "VCALENDAR");
+
+    lines.0
+}
+
+/// Escapes text for inclusion in XML content or attribute values
+fn xml_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// HTML describing when and where the opportunity next takes place,
+/// followed by its description
+fn atom_content(opp: &OpportunityExterior, now: &DateTime<FixedOffset>) -> String {
+    let mut html = String::new();
+
+    let next = opp
+        .occurrences_between(
+            now,
+            &(*now + Duration::days(super::MAX_OCCURRENCE_WINDOW_DAYS)),
+        )
+        .into_iter()
+        .next();
+
+    if let Some(occurrence) = next {
+        let when = match zone(opp) {
+            Some(tz) => occurrence
+                .start
+                .with_timezone(&tz)
+                .format("%A, %B %-d, %Y at %-I:%M %p %Z")
+                .to_string(),
+            None => occurrence
+                .start
+                .format("%A, %B %-d, %Y at %-I:%M %p UTC%:z")
+                .to_string(),
+        };
+        let _ = write!(html, "<p><strong>When:</strong> {}</p>", xml_text(&when));
+    }
+
+    let location = location_text(opp);
+    if !location.is_empty() {
+        let _ = write!(
+            html,
+            "<p><strong>Where:</strong> {}</p>",
+            xml_text(&location)
+        );
+    }
+
+    // Descriptions are sanitized HTML by the time they are stored
+    html.push_str(&opp.description);
+
+    html
+}
+
+/// An Atom feed with an entry for each opportunity. `self_url` is
+/// where the feed itself can be retrieved, and also serves as its id.
+pub fn atom(
+    title: &str,
+    self_url: &str,
+    entries: &[FeedEntry],
+    now: &DateTime<FixedOffset>,
+) -> String {
+    let mut xml = String::new();
+
+    let updated = last_updated(entries).unwrap_or(*now);
+
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    let _ = writeln!(xml, "  <id>{}</id>", xml_text(self_url));
+    let _ = writeln!(xml, "  <title>{}</title>", xml_text(title));
+    let _ = writeln!(
+        xml,
+        "  <updated>{}</updated>",
+        updated.to_rfc3339_opts(SecondsFormat::Secs, true)
+    );
+    let _ = writeln!(
+        xml,
+        "  <link rel=\"self\" href=\"{}\"/>",
+        xml_text(self_url)
+    );
+    let _ = writeln!(xml, "  <link rel=\"alternate\" href=\"{}/\"/>", SITE);
+    xml.push_str("  <generator>Science Near Me</generator>\n");
+
+    for entry in entries {
+        let opp = &entry.opportunity;
+
+        let author = if opp.organization_name.is_empty() {
+            &opp.partner_name
+        } else {
+            &opp.organization_name
+        };
+
+        xml.push_str("  <entry>\n");
+        let _ = writeln!(xml, "    <id>urn:uuid:{}</id>", opp.uid);
+        let _ = writeln!(xml, "    <title>{}</title>", xml_text(&opp.title));
+        let _ = writeln!(
+            xml,
+            "    <link rel=\"alternate\" href=\"{}\"/>",
+            xml_text(&entry.url())
+        );
+        let _ = writeln!(
+            xml,
+            "    <updated>{}</updated>",
+            entry.updated.to_rfc3339_opts(SecondsFormat::Secs, true)
+        );
+        let _ = writeln!(
+            xml,
+            "    <author><name>{}</name></author>",
+            xml_text(author)
+        );
+
+        for (value, name) in topics(opp) {
+            let _ = writeln!(
+                xml,
+                "    <category term=\"{}\" label=\"{}\"/>",
+                xml_text(&value),
+                xml_text(&name)
+            );
+        }
+
+        if !opp.short_desc.is_empty() {
+            let _ = writeln!(xml, "    <summary>{}</summary>", xml_text(&opp.short_desc));
+        }
+
+        let _ = writeln!(
+            xml,
+            "    <content type=\"html\">{}</content>",
+            xml_text(&atom_content(opp, now))
+        );
+
+        if !opp.image_url.is_empty() {
+            let _ = writeln!(
+                xml,
+                "    <link rel=\"enclosure\" href=\"{}\"/>",
+                xml_text(&opp.image_url)
+            );
+        }
+
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::opportunity::{Recurrence, RecurrenceRule, Topic};
+
+    fn dt(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    fn star_party() -> FeedEntry {
+        let opp = OpportunityExterior {
+            uid: Uuid::nil(),
+            slug: "star-party".to_string(),
+            title: "Star Party; bring a telescope, or don't".to_string(),
+            short_desc: "Look up".to_string(),
+            timezone: Some("America/New_York".to_string()),
+            start_datetimes: vec![dt("2026-10-20T19:00:00-04:00")],
+            end_datetimes: vec![dt("2026-10-20T22:00:00-04:00")],
+            recurrence: Recurrence::Rule("FREQ=WEEKLY;COUNT=8".parse::<RecurrenceRule>().unwrap()),
+            recurrence_exdates: vec![dt("2026-11-03T19:00:00-05:00")],
+            location_name: "Riverside Park".to_string(),
+            address_city: "Springfield".to_string(),
+            location_point: Some(
+                serde_json::json!({"type": "Point", "coordinates": [-72.5, 42.1]}),
+            ),
+            opp_topics: vec![Topic::AstronomyAndSpace],
+            ..Default::default()
+        };
+
+        FeedEntry {
+            opportunity: opp,
+            updated: dt("2026-10-01T12:00:00Z"),
+        }
+    }
+
+    fn unfold(text: &str) -> String {
+        text.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn events_carry_zone_recurrence_and_location() {
+        let now = dt("2026-10-18T12:00:00Z");
+        let text = ical("Astronomy", &[star_party()], &now);
+        let unfolded = unfold(&text);
+
+        assert!(text.lines().all(|line| line.len() <= LINE_LIMIT + 1));
+        assert!(unfolded.contains("BEGIN:VTIMEZONE\r\nTZID:America/New_York\r\n"));
+        assert!(unfolded.contains("TZOFFSETFROM:-0400\r\nTZOFFSETTO:-0500\r\nTZNAME:EST\r\n"));
+        assert!(unfolded.contains("DTSTART;TZID=America/New_York:20261020T190000\r\n"));
+        assert!(unfolded.contains("DTEND;TZID=America/New_York:20261020T220000\r\n"));
+        assert!(unfolded.contains("RRULE:FREQ=WEEKLY;COUNT=8\r\n"));
+        assert!(unfolded.contains("EXDATE;TZID=America/New_York:20261103T190000\r\n"));
+        assert!(unfolded.contains("SUMMARY:Star Party\\; bring a telescope\\, or don't\r\n"));
+        assert!(unfolded.contains("LOCATION:Riverside Park\\, Springfield\r\n"));
+        assert!(unfolded.contains("GEO:42.1;-72.5\r\n"));
+        assert!(unfolded.contains("URL:https://sciencenearme.org/star-party\r\n"));
+    }
+
+    #[test]
+    fn long_lines_fold_between_characters() {
+        let mut lines = Lines(String::new());
+        lines.push("SUMMARY", &"é".repeat(60));
+
+        for line in lines.0.split("\r\n") {
+            assert!(line.len() <= LINE_LIMIT);
+        }
+        assert_eq!(unfold(&lines.0), format!("SUMMARY:{}\r\n", "é".repeat(60)));
+    }
+
+    #[test]
+    fn atom_entries_escape_their_text() {
+        let now = dt("2026-10-18T12:00:00Z");
+        let xml = atom(
+            "Astronomy & more",
+            "https://sciencenearme.org/api/v1/opportunity/feed.atom?cost=free&topics[]=astronomy_and_space",
+            &[star_party()],
+            &now,
+        );
+
+        assert!(xml.contains("<title>Astronomy &amp; more</title>"));
+        assert!(xml.contains("<updated>2026-10-01T12:00:00Z</updated>"));
+        assert!(xml.contains("<id>urn:uuid:00000000-0000-0000-0000-000000000000</id>"));
+        assert!(xml.contains("Tuesday, October 20, 2026 at 7:00 PM EDT"));
+        assert!(!xml.contains("cost=free&topics"));
+    }
+}
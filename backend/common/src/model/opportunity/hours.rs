@@ -0,0 +1,252 @@
+//! When an attraction is open. The weekly `OpenDays` schedule can be
+//! overridden on particular dates, to close for a holiday or to keep
+//! different hours for a special event. Hours are wall clock times in
+//! the attraction's timezone, and hours which close at or before the
+//! time they open run past midnight into the next day.
+//!
+//! The `c_open_at` database function implements the same rules for
+//! the `open_at` search filter.
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{TimeZone, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use super::{IssueCode, OpenDays, OpenHours, OpportunityExterior, ValidationReport};
+
+/// Different hours for a single date. Without hours, the attraction
+/// is closed all day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenException {
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub hours: Option<OpenHours>,
+    /// Why the hours are different, such as "Thanksgiving"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+}
+
+// Hours are sometimes written with a UTC offset, which we ignore in
+// favor of the attraction's timezone
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.trim().trim_end_matches('Z');
+    let text = match text.find(['+', '-']) {
+        Some(i) => &text[..i],
+        None => text,
+    };
+
+    NaiveTime::parse_from_str(text, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(text, "%H:%M"))
+        .ok()
+}
+
+impl OpenHours {
+    /// The opening and closing times, if both can be understood
+    pub fn times(&self) -> Option<(NaiveTime, NaiveTime)> {
+        Some((parse_time(&self.opens)?, parse_time(&self.closes)?))
+    }
+
+    fn overnight(&self) -> bool {
+        matches!(self.times(), Some((opens, closes)) if closes <= opens)
+    }
+}
+
+impl OpenDays {
+    fn weekly(&self, weekday: Weekday) -> Option<&OpenHours> {
+        match weekday {
+            Weekday::Mon => self.monday.as_ref(),
+            Weekday::Tue => self.tuesday.as_ref(),
+            Weekday::Wed => self.wednesday.as_ref(),
+            Weekday::Thu => self.thursday.as_ref(),
+            Weekday::Fri => self.friday.as_ref(),
+            Weekday::Sat => self.saturday.as_ref(),
+            Weekday::Sun => self.sunday.as_ref(),
+        }
+    }
+
+    /// The hours which apply on a date, or `None` if closed all day
+    pub fn hours_on(&self, date: NaiveDate) -> Option<&OpenHours> {
+        match self.exceptions.iter().find(|exc| exc.date == date) {
+            Some(exception) => exception.hours.as_ref(),
+            None => self.weekly(date.weekday()),
+        }
+    }
+
+    /// Reports hours which can't be understood as times of day, and
+    /// dates with more than one exception
+    pub(super) fn validate(&self, report: &mut ValidationReport) {
+        let weekly = [
+            ("monday", &self.monday),
+            ("tuesday", &self.tuesday),
+            ("wednesday", &self.wednesday),
+            ("thursday", &self.thursday),
+            ("friday", &self.friday),
+            ("saturday", &self.saturday),
+            ("sunday", &self.sunday),
+        ];
+
+        let exceptions = self
+            .exceptions
+            .iter()
+            .enumerate()
+            .map(|(i, exc)| (format!("exceptions[{}].hours", i), &exc.hours));
+
+        for (path, hours) in weekly
+            .into_iter()
+            .map(|(day, hours)| (day.to_string(), hours))
+            .chain(exceptions)
+        {
+            let Some(hours) = hours else {
+                continue;
+            };
+
+            for (field, value) in [("opens", &hours.opens), ("closes", &hours.closes)] {
+                if parse_time(value).is_none() {
+                    report.error(
+                        format!("$.attraction_hours.{}.{}", path, field),
+                        IssueCode::OutOfRange,
+                        format!("{:?} is not a time of day such as 09:30", value),
+                    );
+                }
+            }
+        }
+
+        for (i, exc) in self.exceptions.iter().enumerate() {
+            if self.exceptions[..i]
+                .iter()
+                .any(|prev| prev.date == exc.date)
+            {
+                report.error(
+                    format!("$.attraction_hours.exceptions[{}].date", i),
+                    IssueCode::NotAllowed,
+                    format!("there is already an exception for {}", exc.date),
+                );
+            }
+        }
+    }
+
+    /// True if open at the given local date and time
+    pub fn is_open_at(&self, local: NaiveDateTime) -> bool {
+        let (date, time) = (local.date(), local.time());
+
+        if let Some((opens, closes)) = self.hours_on(date).and_then(OpenHours::times) {
+            if opens <= time && (time < closes || closes <= opens) {
+                return true;
+            }
+        }
+
+        if let Some(hours) = self.hours_on(date - Duration::days(1)) {
+            if let (true, Some((_, closes))) = (hours.overnight(), hours.times()) {
+                return time < closes;
+            }
+        }
+
+        false
+    }
+}
+
+impl OpportunityExterior {
+    /// True if the attraction is open at the given moment, judged by
+    /// the wall clock in its timezone (UTC if none is set)
+    pub fn is_open_at(&self, moment: &DateTime<FixedOffset>) -> bool {
+        let Some(hours) = &self.attraction_hours else {
+            return false;
+        };
+
+        let zone: Tz = self
+            .timezone
+            .as_deref()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(Tz::UTC);
+
+        hours.is_open_at(zone.from_utc_datetime(&moment.naive_utc()).naive_local())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hours(opens: &str, closes: &str) -> Option<OpenHours> {
+        Some(OpenHours {
+            opens: opens.to_string(),
+            closes: closes.to_string(),
+        })
+    }
+
+    fn museum() -> OpportunityExterior {
+        OpportunityExterior {
+            timezone: Some("America/New_York".to_string()),
+            attraction_hours: Some(OpenDays {
+                thursday: hours("10:00", "17:00"),
+                friday: hours("10:00", "17:00"),
+                saturday: hours("10:00:00-05:00", "17:00:00-05:00"),
+                exceptions: vec![
+                    OpenException {
+                        date: NaiveDate::from_ymd_opt(2026, 11, 26).unwrap(),
+                        hours: None,
+                        label: "Thanksgiving".to_string(),
+                    },
+                    OpenException {
+                        date: NaiveDate::from_ymd_opt(2026, 11, 27).unwrap(),
+                        hours: hours("18:00", "01:00"),
+                        label: "Late night".to_string(),
+                    },
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn at(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    #[test]
+    fn weekly_hours_follow_local_time_across_dst() {
+        let opp = museum();
+
+        // Saturdays at 2pm on either side of the end of DST
+        assert!(opp.is_open_at(&at("2026-10-31T14:00:00-04:00")));
+        assert!(opp.is_open_at(&at("2026-11-07T14:00:00-05:00")));
+
+        // 9:30am local, which would be 10:30am with the summer offset
+        assert!(!opp.is_open_at(&at("2026-11-07T14:30:00Z")));
+
+        // Closed on Sundays
+        assert!(!opp.is_open_at(&at("2026-11-08T14:00:00-05:00")));
+    }
+
+    #[test]
+    fn malformed_hours_are_reported() {
+        let mut days = museum().attraction_hours.unwrap();
+        days.monday = hours("9am", "17:00");
+        days.exceptions.push(days.exceptions[0].clone());
+
+        let mut report = ValidationReport::default();
+        days.validate(&mut report);
+
+        let paths: Vec<&str> = report.errors().map(|issue| issue.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "$.attraction_hours.monday.opens",
+                "$.attraction_hours.exceptions[2].date"
+            ]
+        );
+    }
+
+    #[test]
+    fn exceptions_replace_weekly_hours() {
+        let opp = museum();
+
+        assert!(opp.is_open_at(&at("2026-11-19T12:00:00-05:00")));
+        assert!(!opp.is_open_at(&at("2026-11-26T12:00:00-05:00")));
+
+        assert!(!opp.is_open_at(&at("2026-11-27T12:00:00-05:00")));
+        assert!(opp.is_open_at(&at("2026-11-27T22:00:00-05:00")));
+        assert!(opp.is_open_at(&at("2026-11-28T00:30:00-05:00")));
+        assert!(!opp.is_open_at(&at("2026-11-28T01:30:00-05:00")));
+    }
+}
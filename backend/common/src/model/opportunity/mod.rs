@@ -1,12 +1,31 @@
+pub mod duplicate;
+pub mod export;
+pub mod feed;
 pub mod for_slug;
-
+pub mod hours;
+pub mod moderation;
+pub mod recommend;
+pub mod recurrence;
+pub mod revision;
+pub mod rsvp;
+pub mod schedule;
+pub mod series;
+pub mod tile;
+pub mod translation;
+pub mod trash;
+pub mod validation;
+
+use super::geojson::{BoundingBox, Geometry};
 use super::person::PermitAction;
-use super::serde_helpers::{deserialize_enum, deserialize_enum_vec, serialize_enum, serialize_enum_vec};
+use super::serde_helpers::{
+    deserialize_bbox, deserialize_enum, deserialize_enum_vec, deserialize_geometry, serialize_enum,
+    serialize_enum_vec,
+};
 use super::Error;
 use crate::model::involvement;
 use crate::{geo, Database, ToFixedOffset};
 
-use chrono::{DateTime, Duration, FixedOffset, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
 use inflections::Inflect;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -15,13 +34,27 @@ use serde_json::{json, Value};
 use sqlx::postgres::{PgArguments, PgHasArrayType, PgTypeInfo};
 use sqlx::query::Query;
 use sqlx::{prelude::*, Postgres};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::convert::AsRef;
 use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, EnumIter, EnumString};
 use uuid::Uuid;
 
-use super::{Pagination, PARTNER_NAMESPACE};
+use super::{PageCursors, Pagination, PARTNER_NAMESPACE};
+
+pub use duplicate::{Duplicate, DuplicateScore, DuplicateSide, DuplicateStatus};
+pub use hours::OpenException;
+pub use moderation::{ModerationComment, ModerationNotice, ModerationQueueEntry};
+pub use recommend::{Interests, RecommendCandidate, RecommendScore};
+pub use recurrence::{Occurrence, Recurrence, RecurrenceRule};
+pub use revision::{FieldChange, Revision, RevisionAuthor};
+pub use rsvp::{
+    Attendee, Rsvp, RsvpAvailability, RsvpCancellation, RsvpNotice, RsvpRegistration, RsvpStatus,
+};
+pub use schedule::ScheduledTransition;
+pub use tile::TileCoordinates;
+pub use translation::Translation;
+pub use validation::{IssueCode, Severity, ValidationIssue, ValidationReport};
 
 // This regular expression matches any sequence of characters that
 // does not consist of letters, numbers, or the dash character. The
@@ -31,25 +64,39 @@ use super::{Pagination, PARTNER_NAMESPACE};
 pub static SLUGIFY_REPLACE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"[^\pL\pN-]+").expect("Unable to compile SLUGIFY_REPLACE regex"));
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Serialize, Deserialize, EnumIter, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ReviewStatus {
     Draft,
     Pending,
     Reject,
     Publish,
+    /// Approved for publication at `publish_at`, after which it
+    /// becomes `Publish`
+    Scheduled,
     #[default]
     NotRequired,
 }
 
 impl ReviewStatus {
-    pub fn public(&self) -> bool {
+    /// Whether an opportunity with this status and publication
+    /// schedule is visible to the public at `now`
+    pub fn public(
+        &self,
+        publish_at: Option<&DateTime<FixedOffset>>,
+        unpublish_at: Option<&DateTime<FixedOffset>>,
+        now: &DateTime<FixedOffset>,
+    ) -> bool {
+        let in_window = !matches!(publish_at, Some(dt) if dt > now)
+            && !matches!(unpublish_at, Some(dt) if dt <= now);
+
         match self {
             ReviewStatus::Draft => false,
             ReviewStatus::Pending => false,
             ReviewStatus::Reject => false,
-            ReviewStatus::Publish => true,
-            ReviewStatus::NotRequired => true,
+            ReviewStatus::Publish => in_window,
+            ReviewStatus::Scheduled => publish_at.is_some() && in_window,
+            ReviewStatus::NotRequired => in_window,
         }
     }
 
@@ -59,31 +106,12 @@ impl ReviewStatus {
             ReviewStatus::Pending => false,
             ReviewStatus::Reject => true,
             ReviewStatus::Publish => true,
+            ReviewStatus::Scheduled => true,
             ReviewStatus::NotRequired => true,
         }
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum Recurrence {
-    #[default]
-    Once,
-    Daily,
-    Weekly,
-}
-
-impl Recurrence {
-    pub fn delta(&self) -> Duration {
-        use Recurrence::*;
-        match self {
-            Once => Duration::days(0),
-            Daily => Duration::days(1),
-            Weekly => Duration::days(7),
-        }
-    }
-}
-
 #[derive(Debug, Serialize)]
 pub struct Review {
     pub id: i32,
@@ -216,12 +244,10 @@ impl super::SelectOption for EntityType {
             EntityType::Attraction.to_option(),
             EntityType::Page(PageOptions {
                 layout: PageLayout::JustContent,
-                ..Default::default()
             })
             .to_option(),
             EntityType::Page(PageOptions {
                 layout: PageLayout::AddOpportunities,
-                ..Default::default()
             })
             .to_option(),
             EntityType::Unspecified.to_option(),
@@ -528,6 +554,52 @@ impl Topic {
     }
 }
 
+/// Accommodations available to participants with disabilities or
+/// other access needs
+#[derive(Debug, Serialize, Deserialize, EnumIter, EnumString, AsRefStr, Copy, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Accessibility {
+    WheelchairAccessible,
+    StepFreeEntrance,
+    AccessibleRestrooms,
+    AccessibleParking,
+    SeatingAvailable,
+    ServiceAnimalsWelcome,
+    SignLanguageInterpretation,
+    Captioning,
+    AudioDescription,
+    LargePrint,
+    SensoryFriendly,
+    QuietSpace,
+    LactationRoom,
+}
+
+impl super::SelectOption for Accessibility {
+    fn all_options() -> Vec<(String, String, Accessibility)> {
+        Accessibility::iter().map(|x| x.to_option()).collect()
+    }
+
+    fn to_option(&self) -> (String, String, Accessibility) {
+        let code = self.as_ref();
+        let name = super::separate_camel_case(code);
+
+        (
+            serde_json::to_string(&self)
+                .expect("Serializing simple enum variants should never fail")
+                .trim_matches('"')
+                .to_owned(),
+            name,
+            *self,
+        )
+    }
+}
+
+impl Accessibility {
+    fn db_repr(&self) -> String {
+        self.as_ref().to_snake_case()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenHours {
     pub opens: String,
@@ -544,6 +616,8 @@ pub struct OpenDays {
     pub friday: Option<OpenHours>,
     pub saturday: Option<OpenHours>,
     pub sunday: Option<OpenHours>,
+    /// Dates on which the weekly hours don't apply
+    pub exceptions: Vec<OpenException>,
 }
 
 #[derive(
@@ -633,6 +707,16 @@ fn nineninetynine() -> i16 {
     999
 }
 
+// Recurring opportunities have their occurrences materialized in
+// the database this many days into the future whenever they're stored
+pub const RECURRENCE_HORIZON_DAYS: i64 = 730;
+
+// Longest window that Opportunity::load_occurrences_between will accept
+pub const MAX_OCCURRENCE_WINDOW_DAYS: i64 = 366;
+
+// Number of upcoming occurrences included in annotated exteriors
+const ANNOTATED_OCCURRENCES: usize = 20;
+
 fn en_us() -> Vec<String> {
     vec!["en-US".to_string()]
 }
@@ -663,6 +747,9 @@ pub struct OpportunityExterior {
     pub tags: HashSet<String>,
     pub opp_topics: Vec<Topic>,
     pub ticket_required: bool,
+    /// Seats available at each occurrence when Science Near Me takes
+    /// the registrations, or None when it doesn't
+    pub capacity: Option<i32>,
     pub title: String,
     pub description: String,
     pub short_desc: String,
@@ -675,11 +762,18 @@ pub struct OpportunityExterior {
     pub end_datetimes: Vec<DateTime<FixedOffset>>,
     pub recurrence: Recurrence,
     pub end_recurrence: Option<DateTime<FixedOffset>>,
+    pub recurrence_rdates: Vec<DateTime<FixedOffset>>,
+    pub recurrence_exdates: Vec<DateTime<FixedOffset>>,
     pub timezone: Option<String>,
     pub attraction_hours: Option<OpenDays>,
     pub cost: Cost,
     #[serde(default = "en_us")]
     pub languages: Vec<String>,
+    /// The title, short_desc and description in other languages, keyed
+    /// by language code
+    pub translations: BTreeMap<String, Translation>,
+    /// Accommodations available to participants
+    pub accessibility: Vec<Accessibility>,
     pub is_online: bool,
     pub location_type: LocationType,
     pub location_name: String,
@@ -693,6 +787,9 @@ pub struct OpportunityExterior {
     pub opp_hashtags: Vec<String>,
     pub opp_social_handles: HashMap<String, String>,
     pub partner: Uuid, // uid of the Partner entry which controls this entry
+    /// uid of the umbrella opportunity, such as a festival, when this
+    /// is one of the events in a series
+    pub parent: Option<Uuid>,
 }
 
 impl OpportunityExterior {
@@ -705,6 +802,142 @@ impl OpportunityExterior {
             short_desc: self.short_desc,
         }
     }
+
+    /// The recurrence rule in effect, if any, bounded by end_recurrence
+    pub fn recurrence_rule(&self) -> Option<RecurrenceRule> {
+        self.recurrence.rule(self.end_recurrence)
+    }
+
+    // Recurring instances last as long as the first listed instance
+    fn occurrence_duration(&self) -> Option<Duration> {
+        match (self.start_datetimes.first(), self.end_datetimes.first()) {
+            (Some(start), Some(end)) if end > start => Some(*end - *start),
+            _ => None,
+        }
+    }
+
+    fn explicit_occurrences(&self) -> impl Iterator<Item = Occurrence> + '_ {
+        self.start_datetimes
+            .iter()
+            .enumerate()
+            .map(|(i, start)| Occurrence {
+                start: *start,
+                end: self
+                    .end_datetimes
+                    .get(i)
+                    .filter(|end| *end > start)
+                    .copied(),
+            })
+    }
+
+    fn recurring_occurrences(&self) -> impl Iterator<Item = Occurrence> + '_ {
+        let duration = self.occurrence_duration();
+
+        let from_rule = match (self.recurrence_rule(), self.start_datetimes.first()) {
+            (Some(rule), Some(dtstart)) => Some(rule.iter(*dtstart, self.timezone.as_deref())),
+            _ => None,
+        };
+
+        from_rule
+            .into_iter()
+            .flatten()
+            .map(move |start| Occurrence {
+                start,
+                end: duration.map(|d| start + d),
+            })
+            .filter(|occ| !self.recurrence_exdates.contains(&occ.start))
+    }
+
+    fn additional_occurrences(&self) -> impl Iterator<Item = Occurrence> + '_ {
+        let duration = self.occurrence_duration();
+
+        self.recurrence_rdates
+            .iter()
+            .filter(|start| !self.recurrence_exdates.contains(start))
+            .map(move |start| Occurrence {
+                start: *start,
+                end: duration.map(|d| *start + d),
+            })
+    }
+
+    /// Every occurrence which is still in progress at `begin` or
+    /// starts later, but before `end`. This combines the explicitly
+    /// listed datetimes, the expanded recurrence rule and the RDATEs,
+    /// less any EXDATEs.
+    pub fn occurrences_between(
+        &self,
+        begin: &DateTime<FixedOffset>,
+        end: &DateTime<FixedOffset>,
+    ) -> Vec<Occurrence> {
+        let mut found: Vec<Occurrence> = self
+            .explicit_occurrences()
+            .chain(self.additional_occurrences())
+            .filter(|occ| occ.overlaps(begin, end))
+            .collect();
+
+        found.extend(
+            self.recurring_occurrences()
+                .take_while(|occ| &occ.start < end)
+                .filter(|occ| occ.overlaps(begin, end)),
+        );
+
+        found.sort();
+        found.dedup_by_key(|occ| occ.start);
+        found
+    }
+
+    /// The first occurrence which has not finished as of `now`
+    pub fn next_occurrence(&self, now: &DateTime<FixedOffset>) -> Option<Occurrence> {
+        let unfinished = |occ: &Occurrence| occ.end.as_ref().unwrap_or(&occ.start) > now;
+
+        self.explicit_occurrences()
+            .chain(self.additional_occurrences())
+            .filter(unfinished)
+            .chain(self.recurring_occurrences().find(unfinished))
+            .min()
+    }
+
+    /// The occurrences to be stored in c_opportunity_occurrence: every
+    /// explicitly listed instance, plus instances from the recurrence
+    /// rule and RDATEs from a year before `now` out to the
+    /// materialization horizon. Also returns the time when the last
+    /// occurrence ends, which is `None` if the recurrence continues
+    /// indefinitely or there is no recurrence at all.
+    pub fn materialized_occurrences(
+        &self,
+        now: &DateTime<FixedOffset>,
+    ) -> (Vec<Occurrence>, Option<DateTime<FixedOffset>>) {
+        let begin = *now - Duration::days(365);
+        let horizon = *now + Duration::days(RECURRENCE_HORIZON_DAYS);
+
+        let mut found: Vec<Occurrence> = self
+            .additional_occurrences()
+            .chain(
+                self.recurring_occurrences()
+                    .skip_while(|occ| occ.start < begin)
+                    .take_while(|occ| occ.start < horizon),
+            )
+            .filter(|occ| occ.start >= begin && occ.start < horizon)
+            .chain(self.explicit_occurrences())
+            .collect();
+        found.sort();
+        found.dedup_by_key(|occ| occ.start);
+
+        let until = match self.recurrence_rule() {
+            Some(rule) if rule.is_unbounded() => None,
+            Some(_) => self
+                .recurring_occurrences()
+                .chain(self.additional_occurrences())
+                .map(|occ| occ.end.unwrap_or(occ.start))
+                .max(),
+            None => self
+                .additional_occurrences()
+                .map(|occ| occ.end.unwrap_or(occ.start))
+                .max(),
+        };
+
+        (found, until)
+    }
 }
 
 impl std::fmt::Debug for OpportunityExterior {
@@ -728,6 +961,7 @@ pub struct AnnotatedOpportunityExterior {
     pub current: bool,
     pub authorized: PermitAction,
     pub review_status: ReviewStatus,
+    pub occurrences: Vec<Occurrence>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -737,10 +971,15 @@ pub struct OpportunityInterior {
     pub withdrawn: bool,
     pub submitted_by: Option<Uuid>,
     pub review_status: ReviewStatus,
+    pub publish_at: Option<DateTime<FixedOffset>>,
+    pub unpublish_at: Option<DateTime<FixedOffset>>,
     pub contact_name: String,
     pub contact_email: String,
     pub contact_phone: String,
     pub extra_data: serde_json::Value,
+    /// When the opportunity was moved to the trash, if it has been.
+    /// Only changed by `trash` and `restore_from_trash`.
+    pub trashed_at: Option<DateTime<FixedOffset>>,
 }
 
 impl Default for OpportunityInterior {
@@ -750,10 +989,13 @@ impl Default for OpportunityInterior {
             withdrawn: false,      // partner has withdrawn it from publication
             submitted_by: None,
             review_status: ReviewStatus::NotRequired,
+            publish_at: None,
+            unpublish_at: None,
             contact_name: Default::default(),
             contact_email: Default::default(),
             contact_phone: Default::default(),
             extra_data: serde_json::json!({}),
+            trashed_at: None,
         }
     }
 }
@@ -792,6 +1034,7 @@ pub struct OpportunityForCsv {
     pub tags: String,
     pub opp_topics: String,
     pub ticket_required: bool,
+    pub capacity: Option<i32>,
     pub title: String,
     pub description: String,
     pub short_desc: String,
@@ -802,9 +1045,13 @@ pub struct OpportunityForCsv {
     pub end_datetimes: String,
     pub recurrence: Recurrence,
     pub end_recurrence: Option<String>,
+    pub recurrence_rdates: String,
+    pub recurrence_exdates: String,
+    pub occurrences: String,
     pub timezone: Option<String>,
     pub cost: Cost,
     pub languages: String,
+    pub accessibility: String,
     pub is_online: bool,
     pub location_type: LocationType,
     pub location_name: String,
@@ -817,6 +1064,7 @@ pub struct OpportunityForCsv {
     pub address_zip: String,
     pub opp_hashtags: String,
     pub partner: Uuid,
+    pub parent: Option<Uuid>,
     pub accepted: Option<bool>,
     pub withdrawn: bool,
     pub contact_name: String,
@@ -825,8 +1073,24 @@ pub struct OpportunityForCsv {
     pub extra_data: String,
 }
 
+fn join_datetimes<'a>(datetimes: impl IntoIterator<Item = &'a DateTime<FixedOffset>>) -> String {
+    datetimes.into_iter().fold(String::new(), |mut accum, add| {
+        if !accum.is_empty() {
+            accum.push_str(", ");
+        }
+        accum.push_str(&add.to_rfc3339());
+        accum
+    })
+}
+
 impl From<Opportunity> for OpportunityForCsv {
     fn from(opp: Opportunity) -> Self {
+        let occurrences = join_datetimes(
+            opp.upcoming_occurrences(ANNOTATED_OCCURRENCES)
+                .iter()
+                .map(|occ| &occ.start),
+        );
+
         OpportunityForCsv {
             uid: opp.exterior.uid,
             slug: opp.exterior.slug,
@@ -887,6 +1151,7 @@ impl From<Opportunity> for OpportunityForCsv {
                 },
             ),
             ticket_required: opp.exterior.ticket_required,
+            capacity: opp.exterior.capacity,
             title: opp.exterior.title,
             description: opp.exterior.description,
             short_desc: opp.exterior.short_desc,
@@ -915,9 +1180,22 @@ impl From<Opportunity> for OpportunityForCsv {
             ),
             recurrence: opp.exterior.recurrence,
             end_recurrence: opp.exterior.end_recurrence.map(|dt| dt.to_rfc3339()),
+            recurrence_rdates: join_datetimes(&opp.exterior.recurrence_rdates),
+            recurrence_exdates: join_datetimes(&opp.exterior.recurrence_exdates),
+            occurrences,
             timezone: opp.exterior.timezone,
             cost: opp.exterior.cost,
             languages: opp.exterior.languages.join(", "),
+            accessibility: opp.exterior.accessibility.into_iter().fold(
+                String::new(),
+                |mut accum, add| {
+                    if !accum.is_empty() {
+                        accum.push_str(", ");
+                    }
+                    accum.push_str(add.as_ref());
+                    accum
+                },
+            ),
             is_online: opp.exterior.is_online,
             location_type: opp.exterior.location_type,
             location_name: opp.exterior.location_name,
@@ -936,6 +1214,7 @@ impl From<Opportunity> for OpportunityForCsv {
             address_zip: opp.exterior.address_zip,
             opp_hashtags: opp.exterior.opp_hashtags.join(", "),
             partner: opp.exterior.partner,
+            parent: opp.exterior.parent,
             accepted: opp.interior.accepted,
             withdrawn: opp.interior.withdrawn,
             contact_name: opp.interior.contact_name,
@@ -970,6 +1249,7 @@ pub fn opportunity_from_row(
     tags: Vec<String>,
     opp_topics: Vec<String>,
     ticket_required: bool,
+    capacity: Option<i32>,
     title: String,
     description: String,
     short_desc: String,
@@ -980,10 +1260,14 @@ pub fn opportunity_from_row(
     end_datetimes: Vec<DateTime<Utc>>,
     recurrence: String,
     end_recurrence: Option<DateTime<Utc>>,
+    recurrence_rdates: Vec<DateTime<Utc>>,
+    recurrence_exdates: Vec<DateTime<Utc>>,
     timezone: Option<String>,
     attraction_hours: Option<serde_json::Value>,
     cost: String,
     languages: Vec<String>,
+    translations: serde_json::Value,
+    accessibility: Vec<String>,
     is_online: bool,
     location_type: String,
     location_name: String,
@@ -1001,10 +1285,14 @@ pub fn opportunity_from_row(
     withdrawn: bool,
     submitted_by: Option<Uuid>,
     review_status: String,
+    publish_at: Option<DateTime<Utc>>,
+    unpublish_at: Option<DateTime<Utc>>,
     contact_name: String,
     contact_email: String,
     contact_phone: String,
     extra_data: serde_json::Value,
+    parent_uid: Option<Uuid>,
+    trashed_at: Option<DateTime<Utc>>,
 ) -> Result<Opportunity, Error> {
     Ok(Opportunity {
         id: Some(id),
@@ -1030,6 +1318,7 @@ pub fn opportunity_from_row(
             tags: tags.into_iter().collect(),
             opp_topics: deserialize_enum_vec(&opp_topics),
             ticket_required,
+            capacity,
             title,
             description,
             short_desc,
@@ -1040,11 +1329,15 @@ pub fn opportunity_from_row(
             end_datetimes: end_datetimes.into_iter().map(|dt| dt.to_fixed_offset()).collect(),
             recurrence: deserialize_enum(&recurrence).unwrap_or_default(),
             end_recurrence: end_recurrence.map(|dt| dt.to_fixed_offset()),
+            recurrence_rdates: recurrence_rdates.into_iter().map(|dt| dt.to_fixed_offset()).collect(),
+            recurrence_exdates: recurrence_exdates.into_iter().map(|dt| dt.to_fixed_offset()).collect(),
             timezone,
             attraction_hours: attraction_hours
                 .map(|v| serde_json::from_value(v).unwrap_or_default()),
             cost: deserialize_enum(&cost).unwrap_or_default(),
             languages,
+            translations: serde_json::from_value(translations).unwrap_or_default(),
+            accessibility: deserialize_enum_vec(&accessibility),
             is_online,
             location_type: deserialize_enum(&location_type).unwrap_or_default(),
             location_name,
@@ -1058,16 +1351,20 @@ pub fn opportunity_from_row(
             opp_hashtags,
             opp_social_handles: serde_json::from_value(opp_social_handles).unwrap_or_default(),
             partner: opp_partner,
+            parent: parent_uid,
         },
         interior: OpportunityInterior {
             accepted,
             withdrawn,
             submitted_by,
             review_status: deserialize_enum(&review_status).unwrap_or_default(),
+            publish_at: publish_at.map(|dt| dt.to_fixed_offset()),
+            unpublish_at: unpublish_at.map(|dt| dt.to_fixed_offset()),
             contact_name,
             contact_email,
             contact_phone,
             extra_data,
+            trashed_at: trashed_at.map(|dt| dt.to_fixed_offset()),
         },
     })
 }
@@ -1146,6 +1443,55 @@ impl std::fmt::Display for OpportunityReference {
     }
 }
 
+/// Highlighted excerpts showing where an opportunity matched a
+/// full-text search. Matched terms are wrapped in `<mark>` tags.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct OpportunitySnippet {
+    pub title: String,
+    pub snippet: String,
+}
+
+/// The number of matching opportunities with a particular value
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FacetCount<T> {
+    pub value: T,
+    pub count: u32,
+}
+
+/// Counts of matching opportunities for each value of the fields
+/// which searches can be narrowed by. Each facet is counted with its
+/// own filter removed from the query, so that the counts show how
+/// many results there would be for each alternative choice.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OpportunityFacets {
+    pub topics: Vec<FacetCount<Topic>>,
+    pub descriptors: Vec<FacetCount<Descriptor>>,
+    pub cost: Vec<FacetCount<Cost>>,
+    pub venue_type: Vec<FacetCount<VenueType>>,
+    pub domain: Vec<FacetCount<Domain>>,
+    pub entity_type: Vec<FacetCount<EntityType>>,
+    pub physical: Vec<FacetCount<OpportunityQueryPhysical>>,
+    pub temporal: Vec<FacetCount<OpportunityQueryTemporal>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct OpportunityOccurrence {
+    pub opportunity: OpportunityReference,
+    #[serde(flatten)]
+    pub occurrence: Occurrence,
+}
+
+/// The occurrences of matching opportunities within a window of time,
+/// along with the number of occurrences taking place on each day of
+/// the window, for use in month views.
+#[derive(Serialize, Deserialize)]
+pub struct OccurrenceWindow {
+    pub begin: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub occurrences: Vec<OpportunityOccurrence>,
+    pub days: BTreeMap<NaiveDate, u32>,
+}
+
 #[derive(Serialize, Default, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum OpportunityQueryPhysical {
@@ -1175,6 +1521,9 @@ pub enum OpportunityQueryOrdering {
     Native,
     Unique,
     PartnerName,
+    /// Best full-text matches for `OpportunityQuery.text` first. Falls
+    /// back to `Closest` when there's no text.
+    Relevance,
 }
 
 /// Each field represents one of the database fields by which
@@ -1184,7 +1533,7 @@ pub enum OpportunityQueryOrdering {
 /// ```
 /// Opportunity::load_matching(db.acquire().await?, OpportunityQuery { title_contains: "hello".to_string(), ..Default::default() })
 /// ```
-#[derive(Default, Serialize, Deserialize, Debug)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct OpportunityQuery {
     pub uid: Option<Uuid>,
     pub slug: Option<String>,
@@ -1203,6 +1552,11 @@ pub struct OpportunityQuery {
     pub physical: Option<OpportunityQueryPhysical>,
     pub temporal: Option<OpportunityQueryTemporal>,
     pub text: Option<String>,
+    /// Language code used to stem `text`, defaulting to English
+    pub text_language: Option<String>,
+    /// Languages to show results in where translations are available,
+    /// most preferred first, in the format of an Accept-Language header
+    pub language: Option<String>,
     pub beginning: Option<DateTime<FixedOffset>>,
     pub ending: Option<DateTime<FixedOffset>>,
     pub min_age: Option<i16>,
@@ -1210,6 +1564,8 @@ pub struct OpportunityQuery {
     pub kids_only: Option<bool>,
     pub adults_only: Option<bool>,
     pub descriptors: Option<Vec<Descriptor>>,
+    /// Only match opportunities offering all of these accommodations
+    pub accessibility: Option<Vec<Accessibility>>,
     pub cost: Option<Cost>,
     pub venue_type: Option<VenueType>,
     pub host: Option<String>,
@@ -1224,14 +1580,195 @@ pub struct OpportunityQuery {
     pub exclude: Option<Vec<Uuid>>,
     pub current: Option<bool>,
     pub calendar: Option<(u32, u8)>,
+    /// Only match attractions whose hours, including any exceptions
+    /// for that date, say they are open at this moment
+    pub open_at: Option<DateTime<FixedOffset>>,
+    /// Only match the events in the series under this opportunity
+    pub parent: Option<Uuid>,
+    /// Leave out the events in a series, so that each series is
+    /// represented by its parent alone. Events whose parent is missing
+    /// or not visible are still included.
+    pub hide_children: Option<bool>,
+    /// Match the opportunities in the trash instead of the ones
+    /// outside of it
+    pub trashed: Option<bool>,
     pub region: Option<String>,
+    /// Map viewport as (west, south, east, north) in degrees. West may
+    /// be greater than east when the viewport crosses the antimeridian.
+    #[serde(default, deserialize_with = "deserialize_bbox")]
+    pub within_bbox: Option<BoundingBox>,
+    /// Search area drawn by the user, which must be a Polygon or
+    /// MultiPolygon
+    #[serde(default, deserialize_with = "deserialize_geometry")]
+    pub within_polygon: Option<Geometry>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum Facet {
+    Topic,
+    Descriptor,
+    Cost,
+    VenueType,
+    Domain,
+    EntityType,
+    Physical,
+    Temporal,
+}
+
+impl Facet {
+    const ALL: [Facet; 8] = [
+        Facet::Topic,
+        Facet::Descriptor,
+        Facet::Cost,
+        Facet::VenueType,
+        Facet::Domain,
+        Facet::EntityType,
+        Facet::Physical,
+        Facet::Temporal,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Facet::Topic => "topic",
+            Facet::Descriptor => "descriptor",
+            Facet::Cost => "cost",
+            Facet::VenueType => "venue_type",
+            Facet::Domain => "domain",
+            Facet::EntityType => "entity_type",
+            Facet::Physical => "physical",
+            Facet::Temporal => "temporal",
+        }
+    }
+
+    /// The query with this facet's own filter removed, or `None` if
+    /// the query doesn't filter on this facet
+    fn unfiltered(&self, query: &OpportunityQuery) -> Option<OpportunityQuery> {
+        let mut query = query.clone();
+
+        let filtered = match self {
+            Facet::Topic => query.topics.take().is_some(),
+            Facet::Descriptor => query.descriptors.take().is_some(),
+            Facet::Cost => query.cost.take().is_some(),
+            Facet::VenueType => query.venue_type.take().is_some(),
+            Facet::Domain => false,
+            Facet::EntityType => query.entity_type.take().is_some(),
+            Facet::Physical => !matches!(
+                query.physical.take(),
+                None | Some(OpportunityQueryPhysical::InPersonOrOnline)
+            ),
+            Facet::Temporal => !matches!(
+                query.temporal.take(),
+                None | Some(OpportunityQueryTemporal::OnDemandOrScheduled)
+            ),
+        };
+
+        if filtered {
+            Some(query)
+        } else {
+            None
+        }
+    }
+
+    /// Selects (facet, value, count) rows from the matched CTE built
+    /// by facet_query
+    fn select(&self) -> String {
+        let name = self.name();
+
+        match self {
+            Facet::Topic => format!("SELECT '{name}', value, count(*) FROM matched, unnest(matched.topics) value GROUP BY value"),
+            Facet::Descriptor => format!("SELECT '{name}', value, count(*) FROM matched, unnest(matched.descriptors) value GROUP BY value"),
+            Facet::Cost => format!("SELECT '{name}', matched.cost::text, count(*) FROM matched GROUP BY matched.cost"),
+            Facet::VenueType => format!("SELECT '{name}', value::text, count(*) FROM matched, unnest(matched.venue_type) value GROUP BY value"),
+            Facet::Domain => format!("SELECT '{name}', matched.pes_domain, count(*) FROM matched GROUP BY matched.pes_domain"),
+            Facet::EntityType => format!("SELECT '{name}', matched.entity_type::text, count(*) FROM matched GROUP BY matched.entity_type"),
+            Facet::Physical => format!(
+                r#"SELECT '{name}', 'in-person-or-online', count(*) FROM matched
+                   UNION ALL SELECT '{name}', 'in-person', count(*) FILTER (WHERE matched._in_person) FROM matched
+                   UNION ALL SELECT '{name}', 'online', count(*) FILTER (WHERE matched._online) FROM matched"#
+            ),
+            Facet::Temporal => format!(
+                r#"SELECT '{name}', 'on-demand-or-scheduled', count(*) FROM matched
+                   UNION ALL SELECT '{name}', 'scheduled', count(*) FILTER (WHERE matched._scheduled) FROM matched
+                   UNION ALL SELECT '{name}', 'on-demand', count(*) FILTER (WHERE matched._on_demand) FROM matched"#
+            ),
+        }
+    }
+}
+
+/// Builds a query which counts the values of each of `facets` among
+/// the opportunities matching `query`
+fn facet_query(
+    query: &OpportunityQuery,
+    facets: &[Facet],
+) -> Result<(String, Vec<ParamValue>), Error> {
+    let in_person = format!("{IN_PERSON_CLAUSE} AS _in_person");
+    let online = format!("{ONLINE_CLAUSE} AS _online");
+    let scheduled = format!("{SCHEDULED_CLAUSE} AS _scheduled");
+    let on_demand = format!("{ON_DEMAND_CLAUSE} AS _on_demand");
+
+    let (matched, params) = build_matching_query(
+        &[
+            "search.topics",
+            "search.descriptors",
+            "search.cost",
+            "search.venue_type",
+            "primary_table.pes_domain",
+            "search.entity_type",
+            &in_person,
+            &online,
+            &scheduled,
+            &on_demand,
+        ],
+        query,
+        OpportunityQueryOrdering::Any,
+        &Pagination::All,
+    )?;
+
+    let selects: Vec<String> = facets.iter().map(|f| f.select()).collect();
+
+    Ok((
+        format!(
+            "WITH matched AS ({}) {};",
+            matched.trim_end_matches(';'),
+            selects.join(" UNION ALL ")
+        ),
+        params,
+    ))
+}
+
+/// Converts the counts for one facet into typed values, including a
+/// zero count for each option which wasn't found. The most common
+/// values come first.
+fn facet_counts<T>(found: &[(String, u32)]) -> Vec<FacetCount<T>>
+where
+    T: super::SelectOption + AsRef<str>,
+{
+    let mut counts: Vec<(String, FacetCount<T>)> = T::all_options()
+        .into_iter()
+        .map(|(code, _, value)| (code, FacetCount { value, count: 0 }))
+        .collect();
+
+    for (found_value, found_count) in found {
+        // Values are stored either as their option code or as their
+        // snake case variant name
+        if let Some((_, existing)) = counts.iter_mut().find(|(code, c)| {
+            code == found_value || c.value.as_ref().to_snake_case() == *found_value
+        }) {
+            existing.count += found_count;
+        }
+    }
+
+    counts.sort_by_key(|(_, c)| std::cmp::Reverse(c.count));
+    counts.into_iter().map(|(_, c)| c).collect()
 }
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 enum ParamValue {
     // Raw here means it's not converted to JSON before sending it to
     // the database.
     RawString(String),
+    RawOptionString(Option<String>),
     RawFloat(f32),
     RawInt(i32),
     RawBool(bool),
@@ -1254,6 +1791,7 @@ impl ParamValue {
     ) -> Result<Query<Postgres, PgArguments>, Error> {
         Ok(match self {
             ParamValue::RawString(val) => query.bind(val),
+            ParamValue::RawOptionString(val) => query.bind(val),
             ParamValue::RawFloat(val) => query.bind(val),
             ParamValue::RawInt(val) => query.bind(val),
             ParamValue::RawBool(val) => query.bind(val),
@@ -1269,7 +1807,7 @@ impl ParamValue {
         })
     }
 
-    fn add_all_to_query<'req>(
+    fn add_all_to_query(
         params: Vec<ParamValue>,
         mut query: Query<Postgres, PgArguments>,
     ) -> Result<Query<Postgres, PgArguments>, Error> {
@@ -1281,11 +1819,276 @@ impl ParamValue {
     }
 }
 
+// Matches opportunities which take place in person, at a specific
+// location smaller than 10,000 square miles
+const IN_PERSON_CLAUSE: &str = r#"
+    (
+      (search.is_online = false)
+      and
+      (search.location_polygon is null or ST_Area(search.location_polygon, false) <= 25899752356)
+      and
+      (search.location_type not in ('any', 'unknown'))
+    )"#;
+
+// Matches opportunities which can be done online, or from anywhere
+// within a very large area
+const ONLINE_CLAUSE: &str = r#"
+    (
+      (search.is_online = true)
+      or
+      (search.location_polygon is not null and ST_Area(search.location_polygon, false) > 25899752356)
+    )"#;
+
+// Matches opportunities which aren't part of a series, or whose
+// parent is there to represent them. Children are often imported
+// before their parent, and shouldn't vanish from searches meanwhile.
+const NOT_REPRESENTED_CHILD_CLAUSE: &str = r#"
+    (
+      primary_table.parent_uid is null
+      or
+      not exists (
+        select 1 from c_opportunity parent
+        where parent.uid = primary_table.parent_uid
+          and parent.trashed_at is null
+          and c_opportunity_is_current(parent)
+      )
+    )"#;
+
+// Matches opportunities with specific times of a week or less
+const SCHEDULED_CLAUSE: &str = r#"
+    (coalesce(array_length(search.start_datetimes, 1), 0) > 1
+     or
+     coalesce(array_length(search.end_datetimes, 1), 0) > 1
+     or (
+       coalesce(array_length(search.start_datetimes, 1), 0) = 1
+       and
+       coalesce(array_length(search.end_datetimes, 1), 0) = 1
+       and
+       age(search.end_datetimes[1], search.start_datetimes[1]) <= interval '7 days'
+     )
+    )"#;
+
+// Matches opportunities which are available any time, or over a long
+// span of time
+const ON_DEMAND_CLAUSE: &str = r#"
+    (
+      coalesce(array_length(search.start_datetimes, 1), 0) <= 1
+      and
+      coalesce(array_length(search.end_datetimes, 1), 0) <= 1
+      and (
+        coalesce(array_length(search.start_datetimes, 1), 0) != 1
+        or
+        coalesce(array_length(search.end_datetimes, 1), 0) != 1
+        or
+        age(search.end_datetimes[1], search.start_datetimes[1]) > interval '7 days'
+      )
+    )"#;
+
+/// Position of a row within the results of a particular ordering,
+/// passed to and from clients as an opaque hex string
+#[derive(Serialize, Deserialize, Debug)]
+struct OpportunityCursor {
+    #[serde(rename = "o")]
+    ordering: OpportunityQueryOrdering,
+    /// Values of the ordering's sort keys, as produced by the database
+    #[serde(rename = "k")]
+    keys: Value,
+    /// The time that time-based sort keys are relative to
+    #[serde(rename = "t")]
+    now: DateTime<FixedOffset>,
+    /// Whether the cursor selects the rows before the position, rather
+    /// than after it
+    #[serde(rename = "b", default)]
+    backward: bool,
+}
+
+impl OpportunityCursor {
+    fn encode(&self) -> String {
+        hex::encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(token: &str) -> Result<OpportunityCursor, Error> {
+        let bytes = hex::decode(token).map_err(|_| Error::Value("cursor".to_string()))?;
+        serde_json::from_slice(&bytes).map_err(|_| Error::Value("cursor".to_string()))
+    }
+
+    fn from_pagination(pagination: &Pagination) -> Result<Option<OpportunityCursor>, Error> {
+        match pagination {
+            Pagination::Cursor {
+                cursor: Some(token),
+                ..
+            } if !token.is_empty() => Ok(Some(OpportunityCursor::decode(token)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The expressions (and their SQL types) which together uniquely
+/// determine the position of a row in the given ordering, all sorted
+/// ascending. Orderings which can't be resumed from a cursor return
+/// None.
+fn sort_keys(
+    ordering: OpportunityQueryOrdering,
+    tsquery: Option<&str>,
+) -> Option<Vec<(String, &'static str)>> {
+    let keys: Vec<(&str, &'static str)> = match ordering {
+        OpportunityQueryOrdering::Alphabetical => vec![("search.title", "text")],
+        OpportunityQueryOrdering::Closest => vec![
+            ("-_sort_preferential", "int"),
+            ("_sort_location_priority", "int"),
+            ("_sort_distance + sqrt(_sort_area)", "float8"),
+            ("_sort_time", "timestamptz"),
+        ],
+        OpportunityQueryOrdering::Soonest => vec![
+            ("-_sort_preferential", "int"),
+            ("_sort_location_priority", "int"),
+            ("_sort_time", "timestamptz"),
+            ("_sort_distance + sqrt(_sort_area)", "float8"),
+        ],
+        OpportunityQueryOrdering::PartnerName => vec![
+            (r#"coalesce(c_partner."name", '')"#, "text"),
+            ("search.title", "text"),
+        ],
+        OpportunityQueryOrdering::Relevance => {
+            if let Some(expr) = tsquery {
+                return Some(vec![
+                    ("-_sort_preferential".to_string(), "int"),
+                    (format!("-ts_rank(search.fulltext, {expr}, 1)"), "real"),
+                    ("_sort_location_priority".to_string(), "int"),
+                    ("_sort_time".to_string(), "timestamptz"),
+                    ("search.uid".to_string(), "uuid"),
+                ]);
+            } else {
+                return sort_keys(OpportunityQueryOrdering::Closest, None);
+            }
+        }
+        OpportunityQueryOrdering::Native => vec![("primary_table.id", "int")],
+        OpportunityQueryOrdering::Any | OpportunityQueryOrdering::Unique => return None,
+    };
+
+    Some(
+        keys.into_iter()
+            .map(|(expr, ty)| (expr.to_string(), ty))
+            .chain(std::iter::once(("search.uid".to_string(), "uuid")))
+            .collect(),
+    )
+}
+
+/// Trims the extra row fetched for `Pagination::Cursor`, restores the
+/// order of pages loaded backward, and builds the cursors for the
+/// neighboring pages. Other kinds of pagination have no cursors.
+fn paginate<T>(
+    mut rows: Vec<(T, Option<String>)>,
+    ordering: OpportunityQueryOrdering,
+    pagination: &Pagination,
+) -> Result<(Vec<T>, PageCursors), Error> {
+    let size = if let Pagination::Cursor { size, .. } = pagination {
+        *size as usize
+    } else {
+        return Ok((
+            rows.into_iter().map(|(row, _)| row).collect(),
+            PageCursors::default(),
+        ));
+    };
+
+    let cursor = OpportunityCursor::from_pagination(pagination)?;
+    let backward = cursor.as_ref().map(|c| c.backward).unwrap_or(false);
+    let more = rows.len() > size;
+
+    rows.truncate(size);
+
+    if backward {
+        rows.reverse();
+    }
+
+    // A page loaded forward from a cursor has rows before it, and a
+    // page loaded backward from a cursor has rows after it
+    let (has_next, has_prev) = if backward {
+        (true, more)
+    } else {
+        (more, cursor.is_some())
+    };
+
+    let at = |position: &Option<String>, backward: bool| {
+        position
+            .as_ref()
+            .and_then(|p| serde_json::from_str(p).ok())
+            .map(|(keys, now)| {
+                OpportunityCursor {
+                    ordering,
+                    keys,
+                    now,
+                    backward,
+                }
+                .encode()
+            })
+    };
+
+    let cursors = PageCursors {
+        next: rows
+            .last()
+            .filter(|_| has_next)
+            .and_then(|(_, position)| at(position, false)),
+        prev: rows
+            .first()
+            .filter(|_| has_prev)
+            .and_then(|(_, position)| at(position, true)),
+    };
+
+    Ok((rows.into_iter().map(|(row, _)| row).collect(), cursors))
+}
+
+// Matches opportunities with either a point or polygon location
+// intersecting the geometry, compared in planar lon/lat coordinates
+// the way the area appears on the map
+fn intersects_clause(geometry: &str) -> String {
+    format!(
+        "(ST_Intersects(search.location_point::geometry, {geometry}) OR ST_Intersects(search.location_polygon::geometry, {geometry}))"
+    )
+}
+
+/// Checks that every ring of a search polygon could be understood by
+/// PostGIS: at least four positions, the last the same as the first,
+/// all within the range of longitude and latitude
+fn check_search_polygon(geometry: &Geometry) -> Result<(), Error> {
+    let polygons = match geometry {
+        Geometry::Polygon(poly) => vec![&poly.coordinates],
+        Geometry::MultiPolygon(multi) => multi.coordinates.iter().collect(),
+        _ => return Err(Error::Value("within_polygon".to_string())),
+    };
+
+    if polygons.is_empty() {
+        return Err(Error::Value("within_polygon".to_string()));
+    }
+
+    for rings in polygons {
+        if rings.is_empty() {
+            return Err(Error::Value("within_polygon".to_string()));
+        }
+
+        for ring in rings {
+            if ring.len() < 4 || ring.iter().any(|pos| pos.len() < 2) || ring.first() != ring.last()
+            {
+                return Err(Error::Value("within_polygon".to_string()));
+            }
+
+            if ring
+                .iter()
+                .any(|pos| !(-180.0..=180.0).contains(&pos[0]) || !(-90.0..=90.0).contains(&pos[1]))
+            {
+                return Err(Error::OutOfBounds("within_polygon".to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn build_matching_query(
     fields: &[&str],
     query: &OpportunityQuery,
     mut ordering: OpportunityQueryOrdering,
-    pagination: Pagination,
+    pagination: &Pagination,
 ) -> Result<(String, Vec<ParamValue>), Error> {
     let mut clauses = Vec::new();
     let mut params = Vec::new();
@@ -1324,6 +2127,13 @@ fn build_matching_query(
         }
     }
 
+    // Trashed opportunities are only ever matched when asked for
+    if query.trashed.unwrap_or(false) {
+        clauses.push(String::from("primary_table.trashed_at is not null"));
+    } else {
+        clauses.push(String::from("primary_table.trashed_at is null"));
+    }
+
     if let Some(region) = &query.region {
         clauses.push(format!(
             r#"
@@ -1339,6 +2149,44 @@ FROM c_region WHERE "name" = ${})
         ));
     }
 
+    if let Some((west, south, east, north)) = query.within_bbox {
+        if !(-90.0..=90.0).contains(&south) || !(-90.0..=90.0).contains(&north) || south > north {
+            return Err(Error::OutOfBounds("within_bbox".to_string()));
+        }
+
+        if !(-180.0..=180.0).contains(&west) || !(-180.0..=180.0).contains(&east) {
+            return Err(Error::OutOfBounds("within_bbox".to_string()));
+        }
+
+        let west = ParamValue::RawFloat(west).append(&mut params);
+        let south = ParamValue::RawFloat(south).append(&mut params);
+        let east = ParamValue::RawFloat(east).append(&mut params);
+        let north = ParamValue::RawFloat(north).append(&mut params);
+
+        clauses.push(intersects_clause(&format!(
+            r#"
+CASE WHEN ${west} <= ${east}
+ THEN ST_MakeEnvelope(${west}, ${south}, ${east}, ${north}, 4326)
+ ELSE ST_Collect(
+  ST_MakeEnvelope(${west}, ${south}, 180, ${north}, 4326),
+  ST_MakeEnvelope(-180, ${south}, ${east}, ${north}, 4326)
+ )
+END"#
+        )));
+    }
+
+    if let Some(geometry) = &query.within_polygon {
+        check_search_polygon(geometry)?;
+
+        let geometry = ParamValue::RawString(serde_json::to_string(geometry)?).append(&mut params);
+
+        // Well formed rings can still cross themselves, which would
+        // make ST_Intersects fail, so the polygon is repaired first
+        clauses.push(intersects_clause(&format!(
+            "ST_MakeValid(ST_SetSRID(ST_GeomFromGeoJSON(${geometry}), 4326))"
+        )));
+    }
+
     if let Some((year, month)) = query.calendar {
         let (next_year, next_month) = if month > 11 {
             (year + 1, 1)
@@ -1352,26 +2200,39 @@ FROM c_region WHERE "name" = ${})
         let begin_param = ParamValue::RawString(begin).append(&mut params);
         let end_param = ParamValue::RawString(end).append(&mut params);
 
+        // Occurrences are materialized in c_opportunity_occurrence
+        // up to a horizon. Past the horizon, we assume that a
+        // recurrence which hasn't ended yet still produces
+        // occurrences.
         clauses.push(format!(
             r#"(
-                (
-                 exists (select value from unnest(search.start_datetimes) t(value) where value > ${}::timestamptz and value < ${}::timestamptz)
-                 and
-                 exists (select value from unnest(search.end_datetimes) t(value) where value > ${}::timestamptz and value < ${}::timestamptz)
-                )
+                exists (select 1 from c_opportunity_occurrence occ where occ.opportunity_id = search.opp_id and occ.starts < ${end_param}::timestamptz and coalesce(occ.ends, occ.starts) >= ${begin_param}::timestamptz)
                 or
                 (
-                 coalesce(search.end_recurrence, '0001-01-01')::timestamptz > ${}::timestamptz
+                 search.recurrence <> 'once'
+                 and
+                 coalesce((select max(occ.starts) from c_opportunity_occurrence occ where occ.opportunity_id = search.opp_id), '0001-01-01')::timestamptz < ${begin_param}::timestamptz
+                 and
+                 search.start_datetimes[1] < ${end_param}::timestamptz
+                 and
+                 (search.recurrence_until is null or search.recurrence_until > ${begin_param}::timestamptz)
                 )
-               )"#,
-            begin_param, end_param, begin_param, end_param, begin_param
+               )"#
         ));
     } else {
         if let Some(val) = query.current {
             clauses.push(format!(
                 r#"
                 ${} = coalesce(
-                  coalesce(search.review_status, 'not_required') in ('publish', 'not_required')
+                  (
+                    coalesce(search.review_status, 'not_required') in ('publish', 'not_required')
+                    or
+                    (search.review_status = 'scheduled' and primary_table.publish_at is not null)
+                  )
+                  and
+                  (primary_table.publish_at is null or primary_table.publish_at <= now())
+                  and
+                  (primary_table.unpublish_at is null or primary_table.unpublish_at > now())
                   and
                   search.accepted = true
                   and
@@ -1384,15 +2245,11 @@ FROM c_region WHERE "name" = ${})
                       coalesce(array_length(search.end_datetimes, 1), 0) = 0
                     )
                     or
-                    exists (select value from unnest(search.start_datetimes) t(value) where value > now())
+                    exists (select 1 from c_opportunity_occurrence occ where occ.opportunity_id = search.opp_id and coalesce(occ.ends, occ.starts) > now())
                     or
-                    exists (select value from unnest(search.end_datetimes) t(value) where value > now())
+                    (search.recurrence <> 'once' and search.recurrence_until is null)
                     or
-                    (
-                      (search.recurrence = 'daily' or search.recurrence = 'weekly')
-                      and
-                      (search.end_recurrence is null or search.end_recurrence > now())
-                    )
+                    search.recurrence_until > now()
                   )
                 , false)"#,
                 ParamValue::RawBool(val).append(&mut params)
@@ -1440,10 +2297,20 @@ FROM c_region WHERE "name" = ${})
         ));
     }
 
-    if let Some(val) = &query.title_contains {
+    // Full-text matching is stemmed according to the searcher's
+    // language, so that "astronomy" matches "astronomer"
+    let language_param = if query.text.is_some() || query.title_contains.is_some() {
+        Some(ParamValue::RawOptionString(query.text_language.clone()).append(&mut params))
+    } else {
+        None
+    };
+
+    if let (Some(val), Some(language_param)) = (&query.title_contains, language_param) {
+        let like_param = ParamValue::RawString(format!("%{}%", val)).append(&mut params);
+        let text_param = ParamValue::RawString(val.to_string()).append(&mut params);
+
         clauses.push(format!(
-            "search.title ilike ${}",
-            ParamValue::RawString(format!("%{}%", val)).append(&mut params)
+            "(search.title ilike ${like_param} or ts_filter(search.fulltext, '{{a}}') @@ c_search_tsquery(${text_param}, ${language_param}))"
         ));
     }
 
@@ -1468,6 +2335,31 @@ FROM c_region WHERE "name" = ${})
         ));
     }
 
+    if let Some(val) = &query.accessibility {
+        clauses.push(format!(
+            "${} <@ primary_table.accessibility", // <@ means 'is contained by', so every listed accommodation must be offered
+            ParamValue::RawVecString(val.iter().map(|v| v.db_repr()).collect()).append(&mut params)
+        ));
+    }
+
+    if let Some(moment) = &query.open_at {
+        clauses.push(format!(
+            "c_open_at(primary_table.attraction_hours, primary_table.timezone, ${}::timestamptz)",
+            ParamValue::RawString(moment.to_rfc3339()).append(&mut params)
+        ));
+    }
+
+    if let Some(parent) = query.parent {
+        clauses.push(format!(
+            "primary_table.parent_uid = ${}",
+            ParamValue::RawUuid(parent).append(&mut params)
+        ));
+    }
+
+    if let Some(true) = query.hide_children {
+        clauses.push(String::from(NOT_REPRESENTED_CHILD_CLAUSE));
+    }
+
     if let Some(val) = &query.partner {
         match (&query.include_tags, &query.include_partners) {
             (None, None) => clauses.push(format!(
@@ -1494,7 +2386,7 @@ FROM c_region WHERE "name" = ${})
     }
 
     if let Some(val) = &query.partner_member {
-        let uuid_param = ParamValue::RawUuid(val.clone()).append(&mut params);
+        let uuid_param = ParamValue::RawUuid(*val).append(&mut params);
         clauses.push(format!(
             r#"
             (
@@ -1511,11 +2403,13 @@ FROM c_region WHERE "name" = ${})
         ));
     }
 
-    if let Some(text) = &query.text {
-        clauses.push(format!(
-            "search.fulltext_english @@ websearch_to_tsquery(${})",
-            ParamValue::RawString(text.to_string()).append(&mut params)
-        ));
+    let mut tsquery = None;
+
+    if let (Some(text), Some(language_param)) = (&query.text, language_param) {
+        let text_param = ParamValue::RawString(text.to_string()).append(&mut params);
+        let expr = format!("c_search_tsquery(${text_param}, ${language_param})");
+        clauses.push(format!("search.fulltext @@ {expr}"));
+        tsquery = Some(expr);
     }
 
     if let Some(beginning) = &query.beginning {
@@ -1524,21 +2418,17 @@ FROM c_region WHERE "name" = ${})
         clauses.push(format!(
             r#"
             (
-              exists(select value from unnest(search.start_datetimes) t(value) where value > ${}::timestamptz)
+              exists (select 1 from c_opportunity_occurrence occ where occ.opportunity_id = search.opp_id and coalesce(occ.ends, occ.starts) > ${time_param}::timestamptz)
               or
-              exists(select value from unnest(search.end_datetimes) t(value) where value > ${}::timestamptz)
-              or (
-                (search.recurrence = 'daily' or search.recurrence = 'weekly')
-                and
-                (search.end_recurrence is null or search.end_recurrence > ${}::timestamptz)
-              )
+              (search.recurrence <> 'once' and search.recurrence_until is null)
+              or
+              search.recurrence_until > ${time_param}::timestamptz
               or (
                coalesce(array_length(search.start_datetimes, 1), 0) <= 1
                and
                coalesce(array_length(search.end_datetimes, 1), 0) = 0
               )
-            )"#,
-            time_param, time_param, time_param
+            )"#
         ));
     }
 
@@ -1548,11 +2438,8 @@ FROM c_region WHERE "name" = ${})
         clauses.push(format!(
             r#"
             (
-              not exists (select value from unnest(search.start_datetimes) t(value) where value > ${}::timestamptz)
-              and
-              not exists (select value from unnest(search.end_datetimes) t(value) where value > ${}::timestamptz)
-            )"#,
-            time_param, time_param
+              not exists (select 1 from c_opportunity_occurrence occ where occ.opportunity_id = search.opp_id and coalesce(occ.ends, occ.starts) > ${time_param}::timestamptz)
+            )"#
         ));
     }
 
@@ -1585,15 +2472,15 @@ FROM c_region WHERE "name" = ${})
 
     if let Some(cost) = &query.cost {
         clauses.push(format!(
-            "search.cost = ${}",
+            "search.cost = ${}::t_cost",
             ParamValue::RawString(cost.db_repr()).append(&mut params)
         ));
     }
 
     if let Some(venue_type) = &query.venue_type {
         clauses.push(format!(
-            "any(search.venue_type) = ${}",
-            ParamValue::RawVenueType(venue_type.clone()).append(&mut params)
+            "${} = any(search.venue_type)",
+            ParamValue::RawVenueType(*venue_type).append(&mut params)
         ));
     }
 
@@ -1608,26 +2495,10 @@ FROM c_region WHERE "name" = ${})
         match physical {
             OpportunityQueryPhysical::InPersonOrOnline => {}
             OpportunityQueryPhysical::InPerson => {
-                clauses.push(String::from(
-                    r#"
-                    (
-                      (search.is_online = false)
-                      and
-                      (search.location_polygon is null or ST_Area(search.location_polygon, false) <= 25899752356)
-                      and
-                      (search.location_type not in ('any', 'unknown'))
-                    )"#,
-                ));
+                clauses.push(String::from(IN_PERSON_CLAUSE));
             }
             OpportunityQueryPhysical::Online => {
-                clauses.push(String::from(
-                    r#"
-                    (
-                      (search.is_online = true)
-                      or
-                      (search.location_polygon is not null and ST_Area(search.location_polygon, false) > 25899752356)
-                    )"#
-                ));
+                clauses.push(String::from(ONLINE_CLAUSE));
             }
         }
     }
@@ -1636,35 +2507,10 @@ FROM c_region WHERE "name" = ${})
         match temporal {
             OpportunityQueryTemporal::OnDemandOrScheduled => {}
             OpportunityQueryTemporal::Scheduled => {
-                clauses.push(String::from(
-                    r#"
-                    (coalesce(array_length(search.start_datetimes, 1), 0) > 1
-                     or
-                     coalesce(array_length(search.end_datetimes, 1), 0) > 1
-                     or (
-                       coalesce(array_length(search.start_datetimes, 1), 0) = 1
-                       and
-                       coalesce(array_length(search.end_datetimes, 1), 0) = 1
-                       and
-                       age(search.end_datetimes[1], search.start_datetimes[1]) <= interval '7 days'
-                     )
-                    )"#,
-                ));
+                clauses.push(String::from(SCHEDULED_CLAUSE));
             }
             OpportunityQueryTemporal::OnDemand => {
-                clauses.push(String::from(
-                    r#"
-                    coalesce(array_length(search.start_datetimes, 1), 0) <= 1
-                    and
-                    coalesce(array_length(search.end_datetimes, 1), 0) <= 1
-                    and (
-                      coalesce(array_length(search.start_datetimes, 1), 0) != 1
-                      or
-                      coalesce(array_length(search.end_datetimes, 1), 0) != 1
-                      or
-                      age(search.end_datetimes[1], search.start_datetimes[1]) > interval '7 days'
-                    )"#,
-                ));
+                clauses.push(String::from(ON_DEMAND_CLAUSE));
             }
         }
     }
@@ -1683,18 +2529,78 @@ FROM c_region WHERE "name" = ${})
         ));
     }
 
-    let mut query_string = "SELECT ".to_string();
+    let cursor = OpportunityCursor::from_pagination(pagination)?;
 
-    if ordering == OpportunityQueryOrdering::Unique {
-        query_string.push_str("DISTINCT ON (search.title, search.partner) ");
+    if let Some(cursor) = &cursor {
+        if cursor.ordering != ordering {
+            return Err(Error::Value("cursor".to_string()));
+        }
     }
 
-    match fields.len() {
-        0 => query_string.push_str("primary_table.*"),
-        1 => query_string.push_str(fields[0]),
+    if let (Some(_), OpportunityQueryOrdering::Closest) = (query.calendar, ordering) {
+        ordering = OpportunityQueryOrdering::Soonest;
+    }
+
+    // Later pages sort times relative to the moment when the first
+    // page was loaded, so that the sort keys don't drift between
+    // requests
+    let sort_now = match &cursor {
+        Some(cursor) => format!(
+            "${}::timestamptz",
+            ParamValue::RawString(cursor.now.to_rfc3339()).append(&mut params)
+        ),
+        None => "CURRENT_TIMESTAMP".to_string(),
+    };
+
+    let keys = if let Pagination::Cursor { .. } = pagination {
+        let keys = sort_keys(ordering, tsquery.as_deref())
+            .ok_or_else(|| Error::Value("sort".to_string()))?;
+
+        if let Some(cursor) = &cursor {
+            let keys_param = ParamValue::RawString(cursor.keys.to_string()).append(&mut params);
+
+            clauses.push(format!(
+                "({}) {} ({})",
+                keys.iter()
+                    .map(|(expr, _)| expr.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                if cursor.backward { "<" } else { ">" },
+                keys.iter()
+                    .enumerate()
+                    .map(|(i, (_, ty))| format!("(${keys_param}::jsonb->>{i})::{ty}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        Some(keys)
+    } else {
+        None
+    };
+
+    let mut query_string = "SELECT ".to_string();
+
+    if ordering == OpportunityQueryOrdering::Unique {
+        query_string.push_str("DISTINCT ON (search.title, search.partner) ");
+    }
+
+    match fields.len() {
+        0 => query_string.push_str("primary_table.*"),
+        1 => query_string.push_str(fields[0]),
         _ => query_string.push_str(fields.join(", ").as_str()),
     }
 
+    if let Some(keys) = &keys {
+        query_string.push_str(&format!(
+            ", json_build_array(json_build_array({}), {sort_now})::text AS _cursor",
+            keys.iter()
+                .map(|(expr, _)| expr.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
     query_string.push_str(
         r#" FROM c_opportunity_search search JOIN (
                     SELECT
@@ -1707,7 +2613,7 @@ FROM c_region WHERE "name" = ${})
     if let Some(uid) = &query.prefer_partner {
         query_string.push_str(&format!(
             ", (c_opportunity.opp_partner = ${})::int AS _sort_preferential",
-            ParamValue::RawUuid(uid.clone()).append(&mut params)
+            ParamValue::RawUuid(*uid).append(&mut params)
         ));
     } else {
         query_string.push_str(", 0 as _sort_preferential");
@@ -1745,17 +2651,17 @@ FROM c_region WHERE "name" = ${})
     query_string.push_str(", CASE WHEN c_opportunity.location_polygon IS NOT NULL THEN ST_Area(c_opportunity.location_polygon, false) ELSE 0 END AS _sort_area");
 
     // We bump ongoing opportunities so that they sort as a week in the future, to give actual timely opportunities priority
-    query_string.push_str(r#",
+    query_string.push_str(&format!(r#",
             CASE
               WHEN coalesce(array_length(c_opportunity.start_datetimes, 1), 0) = 0 AND coalesce(array_length(c_opportunity.end_datetimes, 1), 0) = 0
-              THEN CURRENT_TIMESTAMP + INTERVAL '7 days'
-              WHEN EXISTS (SELECT 1 FROM unnest(c_opportunity.start_datetimes) t(value) WHERE value > CURRENT_TIMESTAMP)
-              THEN (SELECT MIN(value) FROM unnest(c_opportunity.start_datetimes) t(value) WHERE value > CURRENT_TIMESTAMP LIMIT 1)
-              WHEN EXISTS (SELECT 1 FROM unnest(c_opportunity.end_datetimes) t(value) WHERE value > CURRENT_TIMESTAMP)
-              THEN CURRENT_TIMESTAMP + INTERVAL '7 days'
+              THEN {sort_now} + INTERVAL '7 days'
+              WHEN EXISTS (SELECT 1 FROM c_opportunity_occurrence occ WHERE occ.opportunity_id = c_opportunity.id AND occ.starts > {sort_now})
+              THEN (SELECT MIN(occ.starts) FROM c_opportunity_occurrence occ WHERE occ.opportunity_id = c_opportunity.id AND occ.starts > {sort_now})
+              WHEN EXISTS (SELECT 1 FROM c_opportunity_occurrence occ WHERE occ.opportunity_id = c_opportunity.id AND occ.ends > {sort_now})
+              THEN {sort_now} + INTERVAL '7 days'
               ELSE '100000-01-01T00:00:00.0+00:00'::timestamptz
             END AS _sort_time
-        "#);
+        "#));
 
     query_string.push_str(" FROM c_opportunity LEFT JOIN c_opportunity_overlay ON c_opportunity.id = c_opportunity_overlay.opportunity_id) AS primary_table ON search.opp_id = primary_table.id");
 
@@ -1781,40 +2687,72 @@ FROM c_region WHERE "name" = ${})
         query_string.push_str(&clause);
     }
 
-    if let (Some(_), OpportunityQueryOrdering::Closest) = (query.calendar, ordering) {
-        ordering = OpportunityQueryOrdering::Soonest;
-    }
+    if let Some(keys) = &keys {
+        // Backward cursors walk the ordering in reverse from the
+        // cursor position, so that the limit applies to the nearest
+        // rows. The page is put back in order after it's loaded.
+        let direction = if cursor.as_ref().map(|c| c.backward).unwrap_or(false) {
+            "DESC"
+        } else {
+            "ASC"
+        };
 
-    match ordering {
-        OpportunityQueryOrdering::Alphabetical => {
-            query_string.push_str(" ORDER BY (search.title) ASC");
-        }
-        OpportunityQueryOrdering::Closest => {
-            query_string.push_str(
-                " ORDER BY _sort_preferential DESC, _sort_location_priority ASC, _sort_distance + sqrt(_sort_area) ASC, _sort_time ASC",
-            );
-        }
-        OpportunityQueryOrdering::Soonest => {
-            query_string.push_str(
-                " ORDER BY _sort_preferential DESC, _sort_location_priority ASC, _sort_time ASC, _sort_distance + sqrt(_sort_area) ASC",
-            );
-        }
-        OpportunityQueryOrdering::Native => query_string.push_str(" ORDER BY id ASC"),
-        OpportunityQueryOrdering::Any => {}
-        OpportunityQueryOrdering::Unique => {
-            query_string.push_str(" ORDER BY search.title, search.partner ASC")
-        }
-        OpportunityQueryOrdering::PartnerName => {
-            query_string.push_str(r#" ORDER BY c_partner."name" ASC, search.title ASC"#)
+        query_string.push_str(&format!(
+            " ORDER BY {}",
+            keys.iter()
+                .map(|(expr, _)| format!("{expr} {direction}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    } else {
+        match ordering {
+            OpportunityQueryOrdering::Alphabetical => {
+                query_string.push_str(" ORDER BY (search.title) ASC");
+            }
+            OpportunityQueryOrdering::Closest => {
+                query_string.push_str(
+                    " ORDER BY _sort_preferential DESC, _sort_location_priority ASC, _sort_distance + sqrt(_sort_area) ASC, _sort_time ASC",
+                );
+            }
+            OpportunityQueryOrdering::Soonest => {
+                query_string.push_str(
+                    " ORDER BY _sort_preferential DESC, _sort_location_priority ASC, _sort_time ASC, _sort_distance + sqrt(_sort_area) ASC",
+                );
+            }
+            OpportunityQueryOrdering::Native => {
+                query_string.push_str(" ORDER BY primary_table.id ASC")
+            }
+            OpportunityQueryOrdering::Any => {}
+            OpportunityQueryOrdering::Unique => {
+                query_string.push_str(" ORDER BY search.title, search.partner ASC")
+            }
+            OpportunityQueryOrdering::PartnerName => {
+                query_string.push_str(r#" ORDER BY c_partner."name" ASC, search.title ASC"#)
+            }
+            OpportunityQueryOrdering::Relevance => {
+                if let Some(expr) = tsquery {
+                    query_string.push_str(&format!(
+                        " ORDER BY _sort_preferential DESC, ts_rank(search.fulltext, {expr}, 1) DESC, _sort_location_priority ASC, _sort_time ASC"
+                    ));
+                } else {
+                    query_string.push_str(
+                        " ORDER BY _sort_preferential DESC, _sort_location_priority ASC, _sort_distance + sqrt(_sort_area) ASC, _sort_time ASC",
+                    );
+                }
+            }
         }
     }
 
     match pagination {
-        Pagination::All => query_string.push_str(";"),
+        Pagination::All => query_string.push(';'),
         Pagination::One => query_string.push_str(" LIMIT 1;"),
         Pagination::Page { index, size } => {
             query_string.push_str(format!(" LIMIT {} OFFSET {};", size, index * size).as_ref())
         }
+        // One extra row tells us whether there's another page
+        Pagination::Cursor { size, .. } => {
+            query_string.push_str(format!(" LIMIT {};", size + 1).as_ref())
+        }
     };
 
     println!("Constructed query: {}", &query_string);
@@ -1867,9 +2805,7 @@ pub struct OpportunityPseudoIter {
 
 impl OpportunityPseudoIter {
     pub async fn get_next(&mut self, db: &Database) -> Option<Opportunity> {
-        let Some(uid) = self.uids.pop_front() else {
-            return None;
-        };
+        let uid = self.uids.pop_front()?;
         Opportunity::load_by_uid(db, &uid).await.ok()
     }
 }
@@ -1887,6 +2823,7 @@ impl Opportunity {
 
     pub fn into_annotated_exterior(self, authorized: PermitAction) -> AnnotatedOpportunityExterior {
         let current = self.current();
+        let occurrences = self.upcoming_occurrences(ANNOTATED_OCCURRENCES);
         AnnotatedOpportunityExterior {
             exterior: self.exterior,
             accepted: self.interior.accepted.unwrap_or(false),
@@ -1894,17 +2831,27 @@ impl Opportunity {
             review_status: self.interior.review_status,
             current,
             authorized,
+            occurrences,
         }
     }
 
+    /// Up to `limit` occurrences which have not yet finished, within
+    /// the materialization horizon
+    pub fn upcoming_occurrences(&self, limit: usize) -> Vec<Occurrence> {
+        let now = chrono::Utc::now().to_fixed_offset();
+        let mut found = self
+            .exterior
+            .occurrences_between(&now, &(now + Duration::days(RECURRENCE_HORIZON_DAYS)));
+        found.truncate(limit);
+        found
+    }
+
     pub fn current_as_of(&self, now: &DateTime<FixedOffset>) -> bool {
-        let reviewed = match self.interior.review_status {
-            ReviewStatus::Draft => false,
-            ReviewStatus::Pending => false,
-            ReviewStatus::Reject => false,
-            ReviewStatus::Publish => true,
-            ReviewStatus::NotRequired => true,
-        };
+        let reviewed = self.interior.review_status.public(
+            self.interior.publish_at.as_ref(),
+            self.interior.unpublish_at.as_ref(),
+            now,
+        );
 
         let publish = self.interior.accepted == Some(true) && !self.interior.withdrawn;
 
@@ -1912,7 +2859,11 @@ impl Opportunity {
         let num_ends = self.exterior.end_datetimes.len();
         let start_in_future = self.exterior.start_datetimes.iter().any(|dt| dt > now);
         let end_in_future = self.exterior.end_datetimes.iter().any(|dt| dt > now);
-        let upcoming = (num_starts == 1 && num_ends == 0) || start_in_future || end_in_future;
+        let recurs_in_future = self.exterior.next_occurrence(now).is_some();
+        let upcoming = (num_starts == 1 && num_ends == 0)
+            || start_in_future
+            || end_in_future
+            || recurs_in_future;
 
         reviewed && publish && upcoming
     }
@@ -1937,7 +2888,7 @@ impl Opportunity {
             &["count(primary_table.*) as matches"],
             query,
             OpportunityQueryOrdering::Any,
-            Pagination::One,
+            &Pagination::One,
         )?;
 
         let query_obj = ParamValue::add_all_to_query(query_params, sqlx::query(&query_string))?;
@@ -1949,12 +2900,106 @@ impl Opportunity {
             .unwrap_or(0) as u32)
     }
 
+    /// Counts the opportunities matching `query` for each possible
+    /// value of the topic, descriptor, cost, venue type, domain,
+    /// entity type, physical and temporal facets
+    pub async fn facets(
+        db: &Database,
+        query: &OpportunityQuery,
+    ) -> Result<OpportunityFacets, Error> {
+        // Facets which the query doesn't filter on can all be
+        // counted together, while each of the others needs its own
+        // filter removed
+        let mut passes: Vec<(OpportunityQuery, Vec<Facet>)> = Vec::new();
+        let mut unfiltered = Vec::new();
+
+        for facet in Facet::ALL {
+            if let Some(without) = facet.unfiltered(query) {
+                passes.push((without, vec![facet]));
+            } else {
+                unfiltered.push(facet);
+            }
+        }
+
+        if !unfiltered.is_empty() {
+            passes.push((query.clone(), unfiltered));
+        }
+
+        let mut found: HashMap<String, Vec<(String, u32)>> = HashMap::new();
+
+        for (pass_query, pass_facets) in passes {
+            let (query_string, query_params) = facet_query(&pass_query, &pass_facets)?;
+            let query_obj = ParamValue::add_all_to_query(query_params, sqlx::query(&query_string))?;
+
+            for row in query_obj.fetch_all(db).await? {
+                let facet: String = row.try_get(0)?;
+                let value: Option<String> = row.try_get(1)?;
+                let count: i64 = row.try_get(2)?;
+
+                if let Some(value) = value {
+                    found.entry(facet).or_default().push((value, count as u32));
+                }
+            }
+        }
+
+        let found_for = |facet: Facet| found.get(facet.name()).cloned().unwrap_or_default();
+
+        Ok(OpportunityFacets {
+            topics: facet_counts(&found_for(Facet::Topic)),
+            descriptors: facet_counts(&found_for(Facet::Descriptor)),
+            cost: facet_counts(&found_for(Facet::Cost)),
+            venue_type: facet_counts(&found_for(Facet::VenueType)),
+            domain: facet_counts(&found_for(Facet::Domain)),
+            entity_type: found_for(Facet::EntityType)
+                .into_iter()
+                .map(|(value, count)| FacetCount {
+                    value: match value.as_str() {
+                        "page" => EntityType::Page(PageOptions::default()),
+                        other => deserialize_enum(other).unwrap_or_default(),
+                    },
+                    count,
+                })
+                .collect(),
+            physical: found_for(Facet::Physical)
+                .into_iter()
+                .filter_map(|(value, count)| {
+                    deserialize_enum(&value)
+                        .ok()
+                        .map(|value| FacetCount { value, count })
+                })
+                .collect(),
+            temporal: found_for(Facet::Temporal)
+                .into_iter()
+                .filter_map(|(value, count)| {
+                    deserialize_enum(&value)
+                        .ok()
+                        .map(|value| FacetCount { value, count })
+                })
+                .collect(),
+        })
+    }
+
     pub async fn load_matching_refs(
         db: &Database,
         query: &OpportunityQuery,
         ordering: OpportunityQueryOrdering,
         pagination: Pagination,
     ) -> Result<Vec<OpportunityReference>, Error> {
+        Ok(
+            Opportunity::load_matching_refs_page(db, query, ordering, pagination)
+                .await?
+                .0,
+        )
+    }
+
+    /// Like `load_matching_refs`, but also returns the cursors for the
+    /// neighboring pages when using `Pagination::Cursor`
+    pub async fn load_matching_refs_page(
+        db: &Database,
+        query: &OpportunityQuery,
+        ordering: OpportunityQueryOrdering,
+        pagination: Pagination,
+    ) -> Result<(Vec<OpportunityReference>, PageCursors), Error> {
         let (query_string, query_params) = build_matching_query(
             &[
                 "primary_table.uid",
@@ -1962,28 +3007,157 @@ impl Opportunity {
                 "primary_table.title",
                 "primary_table.image_url",
                 "primary_table.short_desc",
+                "primary_table.languages",
+                "primary_table.translations",
             ],
             query,
             ordering,
-            pagination,
+            &pagination,
         )?;
 
+        let preferences = query
+            .language
+            .as_deref()
+            .map(crate::language_preferences)
+            .unwrap_or_default();
+
         let query_obj = ParamValue::add_all_to_query(query_params, sqlx::query(&query_string))?;
 
-        query_obj
+        let rows = query_obj
             .map(|rec| {
-                Ok(OpportunityReference {
+                let mut reference = OpportunityReference {
                     uid: rec.get("uid"),
                     slug: rec.try_get("slug").unwrap_or_default(),
                     title: rec.get("title"),
                     image_url: rec.get("image_url"),
                     short_desc: rec.get("short_desc"),
-                })
+                };
+
+                if !preferences.is_empty() {
+                    let languages: Vec<String> = rec.try_get("languages").unwrap_or_default();
+                    let translations: BTreeMap<String, Translation> = rec
+                        .try_get("translations")
+                        .ok()
+                        .and_then(|value| serde_json::from_value(value).ok())
+                        .unwrap_or_default();
+                    reference.localize(&languages, &translations, &preferences);
+                }
+
+                (reference, rec.try_get("_cursor").ok())
             })
             .fetch_all(db)
-            .await?
-            .into_iter()
-            .collect()
+            .await?;
+
+        paginate(rows, ordering, &pagination)
+    }
+
+    /// Loads the occurrences of opportunities matching `query` which
+    /// overlap the window from `begin` to `end`. Days are counted
+    /// according to `timezone`, which defaults to UTC. Occurrences
+    /// are only materialized out to RECURRENCE_HORIZON_DAYS.
+    pub async fn load_occurrences_between(
+        db: &Database,
+        query: &OpportunityQuery,
+        begin: &DateTime<FixedOffset>,
+        end: &DateTime<FixedOffset>,
+        timezone: Option<&str>,
+    ) -> Result<OccurrenceWindow, Error> {
+        if end <= begin {
+            return Err(Error::OutOfBounds("end".to_string()));
+        }
+
+        if *end - *begin > Duration::days(MAX_OCCURRENCE_WINDOW_DAYS) {
+            return Err(Error::OutOfBounds("end".to_string()));
+        }
+
+        let zone: chrono_tz::Tz = match timezone {
+            Some(name) => name
+                .parse()
+                .map_err(|_| Error::Value("timezone".to_string()))?,
+            None => chrono_tz::UTC,
+        };
+
+        let (inner_string, mut query_params) = build_matching_query(
+            &["primary_table.id"],
+            query,
+            OpportunityQueryOrdering::Any,
+            &Pagination::All,
+        )?;
+
+        let begin_param = ParamValue::RawString(begin.to_rfc3339()).append(&mut query_params);
+        let end_param = ParamValue::RawString(end.to_rfc3339()).append(&mut query_params);
+
+        let query_string = format!(
+            r#"SELECT occ.starts, occ.ends, opp.uid, opp.slug, opp.title, opp.image_url, opp.short_desc
+               FROM c_opportunity_occurrence occ JOIN c_opportunity opp ON occ.opportunity_id = opp.id
+               WHERE occ.opportunity_id IN ({})
+               AND occ.starts < ${end_param}::timestamptz
+               AND coalesce(occ.ends, occ.starts) >= ${begin_param}::timestamptz
+               ORDER BY occ.starts ASC, opp.title ASC;"#,
+            inner_string.trim_end_matches(';')
+        );
+
+        let query_obj = ParamValue::add_all_to_query(query_params, sqlx::query(&query_string))?;
+
+        let occurrences: Vec<OpportunityOccurrence> = query_obj
+            .map(|rec| OpportunityOccurrence {
+                opportunity: OpportunityReference {
+                    uid: rec.get("uid"),
+                    slug: rec.try_get("slug").unwrap_or_default(),
+                    title: rec.get("title"),
+                    image_url: rec.get("image_url"),
+                    short_desc: rec.get("short_desc"),
+                },
+                occurrence: Occurrence {
+                    start: rec.get::<DateTime<Utc>, _>("starts").to_fixed_offset(),
+                    end: rec
+                        .get::<Option<DateTime<Utc>>, _>("ends")
+                        .map(|dt| dt.to_fixed_offset()),
+                },
+            })
+            .fetch_all(db)
+            .await?;
+
+        let mut days = BTreeMap::new();
+
+        for found in occurrences.iter() {
+            for day in found.occurrence.days(begin, end, &zone) {
+                *days.entry(day).or_insert(0) += 1;
+            }
+        }
+
+        Ok(OccurrenceWindow {
+            begin: *begin,
+            end: *end,
+            occurrences,
+            days,
+        })
+    }
+
+    /// Highlighted snippets for each of `uids`, showing where they
+    /// match the full-text search `text`
+    pub async fn load_snippets(
+        db: &Database,
+        text: &str,
+        language: Option<&str>,
+        uids: &[Uuid],
+    ) -> Result<HashMap<Uuid, OpportunitySnippet>, Error> {
+        Ok(
+            sqlx::query_file!("db/opportunity/snippets.sql", text, language, uids)
+                .map(|row| {
+                    (
+                        row.uid,
+                        OpportunitySnippet {
+                            title: row.title,
+                            snippet: row.snippet,
+                        },
+                    )
+                })
+                .fetch_all(db)
+                .await?
+                .into_iter()
+                .collect(),
+        )
     }
 
     pub async fn load_matching(
@@ -1992,11 +3166,26 @@ impl Opportunity {
         ordering: OpportunityQueryOrdering,
         pagination: Pagination,
     ) -> Result<Vec<Opportunity>, Error> {
-        let (query_string, query_params) = build_matching_query(&[], query, ordering, pagination)?;
+        Ok(
+            Opportunity::load_matching_page(db, query, ordering, pagination)
+                .await?
+                .0,
+        )
+    }
+
+    /// Like `load_matching`, but also returns the cursors for the
+    /// neighboring pages when using `Pagination::Cursor`
+    pub async fn load_matching_page(
+        db: &Database,
+        query: &OpportunityQuery,
+        ordering: OpportunityQueryOrdering,
+        pagination: Pagination,
+    ) -> Result<(Vec<Opportunity>, PageCursors), Error> {
+        let (query_string, query_params) = build_matching_query(&[], query, ordering, &pagination)?;
 
         let query_obj = ParamValue::add_all_to_query(query_params, sqlx::query(&query_string))?;
 
-        query_obj
+        let rows = query_obj
             .map(|rec| {
                 let mut opp = opportunity_from_row(
                     rec.get("id"),
@@ -2021,6 +3210,7 @@ impl Opportunity {
                     rec.get("tags"),
                     rec.get("opp_topics"),
                     rec.get("ticket_required"),
+                    rec.get("capacity"),
                     rec.get("title"),
                     rec.get("description"),
                     rec.get("short_desc"),
@@ -2031,10 +3221,14 @@ impl Opportunity {
                     rec.get("end_datetimes"),
                     rec.get("recurrence"),
                     rec.get("end_recurrence"),
+                    rec.get("recurrence_rdates"),
+                    rec.get("recurrence_exdates"),
                     rec.get("timezone"),
                     rec.get("attraction_hours"),
                     rec.get("cost"),
                     rec.get("languages"),
+                    rec.get("translations"),
+                    rec.get("accessibility"),
                     rec.get("is_online"),
                     rec.get("location_type"),
                     rec.get("location_name"),
@@ -2052,25 +3246,31 @@ impl Opportunity {
                     rec.get("withdrawn"),
                     rec.get("submitted_by"),
                     rec.get("review_status"),
+                    rec.get("publish_at"),
+                    rec.get("unpublish_at"),
                     rec.get("contact_name"),
                     rec.get("contact_email"),
                     rec.get("contact_phone"),
                     rec.get("extra_data"),
+                    rec.get("parent_uid"),
+                    rec.get("trashed_at"),
                 )?;
                 let overlay_ext: Option<serde_json::Value> = rec.get("overlay_exterior");
                 let overlay_int: Option<serde_json::Value> = rec.get("overlay_interior");
                 apply_overlay(&mut opp, overlay_ext, overlay_int);
-                Ok(opp)
+                Ok((opp, rec.try_get("_cursor").ok()))
             })
             .fetch_all(db)
             .await?
             .into_iter()
-            .collect()
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        paginate(rows, ordering, &pagination)
     }
 
     pub fn to_reference(&self) -> OpportunityReference {
         OpportunityReference {
-            uid: self.exterior.uid.clone(),
+            uid: self.exterior.uid,
             slug: self.exterior.slug.clone(),
             title: self.exterior.title.clone(),
             image_url: self.exterior.image_url.clone(),
@@ -2089,7 +3289,7 @@ impl Opportunity {
     }
 
     pub async fn load_partner(&self, db: &Database) -> Result<super::partner::Partner, Error> {
-        Ok(super::partner::Partner::load_by_uid(db, &self.exterior.partner).await?)
+        super::partner::Partner::load_by_uid(db, &self.exterior.partner).await
     }
 
     pub async fn reviews(&mut self, db: &Database) -> Result<Reviews, Error> {
@@ -2101,6 +3301,15 @@ impl Opportunity {
     }
 
     pub async fn validate(&mut self) -> Result<(), Error> {
+        self.validation_report().await.into_result()
+    }
+
+    /// Normalizes the opportunity, and reports every problem which
+    /// would prevent storing it along with warnings about anything
+    /// likely to be a mistake
+    pub async fn validation_report(&mut self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
         self.exterior.partner_name = self
             .exterior
             .partner_name
@@ -2118,27 +3327,27 @@ impl Opportunity {
         self.exterior.short_desc = ammonia::clean(&self.exterior.short_desc);
         self.exterior.description = ammonia::clean(&self.exterior.description);
 
-        if let None = &self.exterior.location_point {
-            if !self.exterior.address_street.is_empty() {
-                if let Some(found) = geo::Query::new(
-                    format!(
-                        "{} {} {} {} {}",
-                        self.exterior.address_street,
-                        self.exterior.address_city,
-                        self.exterior.address_state,
-                        self.exterior.address_zip,
-                        self.exterior.address_country
-                    ),
-                    false,
-                )
-                .lookup_one()
-                .await
-                {
-                    self.exterior.location_point = Some(serde_json::json!({
-                        "type": "Point",
-                        "coordinates": [found.geometry.longitude, found.geometry.latitude]
-                    }));
-                }
+        self.exterior.validate_translations(&mut report);
+
+        if self.exterior.location_point.is_none() && !self.exterior.address_street.is_empty() {
+            if let Some(found) = geo::Query::new(
+                format!(
+                    "{} {} {} {} {}",
+                    self.exterior.address_street,
+                    self.exterior.address_city,
+                    self.exterior.address_state,
+                    self.exterior.address_zip,
+                    self.exterior.address_country
+                ),
+                false,
+            )
+            .lookup_one()
+            .await
+            {
+                self.exterior.location_point = Some(serde_json::json!({
+                    "type": "Point",
+                    "coordinates": [found.geometry.longitude, found.geometry.latitude]
+                }));
             }
         }
 
@@ -2157,22 +3366,126 @@ impl Opportunity {
         }
 
         if let Some(poly) = &self.exterior.location_polygon {
-            if poly["type"] == Value::from("Polygon") {
+            if poly["type"] == "Polygon" {
                 let new = json!({"type": "MultiPolygon", "coordinates": [poly["coordinates"]]});
                 self.exterior.location_polygon = Some(new);
             }
         }
 
         if self.exterior.partner_name.is_empty() {
-            return Err(Error::Missing("partner_name".into()));
+            report.error(
+                "$.partner_name",
+                IssueCode::Required,
+                "partner_name must not be empty",
+            );
+        }
+
+        if self.exterior.capacity.map(|c| c < 0).unwrap_or(false) {
+            report.error(
+                "$.capacity",
+                IssueCode::OutOfRange,
+                "capacity must not be negative",
+            );
+        }
+
+        if let (ReviewStatus::Scheduled, None) =
+            (self.interior.review_status, self.interior.publish_at)
+        {
+            report.error(
+                "$.publish_at",
+                IssueCode::Required,
+                "a scheduled opportunity needs a publish_at time",
+            );
+        }
+
+        if self.exterior.parent == Some(self.exterior.uid) {
+            report.error(
+                "$.parent",
+                IssueCode::NotAllowed,
+                "an opportunity can not be the parent of its own series",
+            );
+        }
+
+        if let (Some(publish), Some(unpublish)) =
+            (self.interior.publish_at, self.interior.unpublish_at)
+        {
+            if unpublish <= publish {
+                report.error(
+                    "$.unpublish_at",
+                    IssueCode::OutOfRange,
+                    "unpublish_at must be later than publish_at",
+                );
+            }
         }
 
         if let (None, Some(dt)) = (self.exterior.partner_created, self.exterior.partner_updated) {
-            self.exterior.partner_created = Some(dt.clone());
+            self.exterior.partner_created = Some(dt);
         }
 
         if self.exterior.title.is_empty() {
-            return Err(Error::Missing("title".into()));
+            report.error("$.title", IssueCode::Required, "title must not be empty");
+        }
+
+        // A recurrence rule repeats the first listed start datetime,
+        // so it has nothing to work from without one
+        if let Recurrence::Rule(_) = self.exterior.recurrence {
+            if self.exterior.start_datetimes.is_empty() {
+                report.error(
+                    "$.start_datetimes",
+                    IssueCode::Required,
+                    "a recurrence rule needs at least one start datetime",
+                );
+            }
+        }
+
+        if let Some(hours) = &self.exterior.attraction_hours {
+            hours.validate(&mut report);
+        }
+
+        for (i, (start, end)) in self
+            .exterior
+            .start_datetimes
+            .iter()
+            .zip(self.exterior.end_datetimes.iter())
+            .enumerate()
+        {
+            if end < start {
+                report.warning(
+                    format!("$.end_datetimes[{}]", i),
+                    IssueCode::EndsBeforeStart,
+                    format!(
+                        "ends at {} but starts at {}",
+                        end.to_rfc3339(),
+                        start.to_rfc3339()
+                    ),
+                );
+            }
+        }
+
+        let now = Utc::now().to_fixed_offset();
+        if !self.exterior.end_datetimes.is_empty()
+            && self.exterior.end_datetimes.iter().all(|dt| dt < &now)
+            && self.exterior.next_occurrence(&now).is_none()
+        {
+            report.warning(
+                "$.end_datetimes",
+                IssueCode::Ended,
+                "every end datetime is in the past, so the opportunity will not be shown",
+            );
+        }
+
+        if self.exterior.image_url.trim().is_empty()
+            && !matches!(self.exterior.entity_type, EntityType::Page(_))
+        {
+            report.warning(
+                "$.image_url",
+                IssueCode::MissingImage,
+                "opportunities without an image are shown with a generic placeholder",
+            );
+        }
+
+        if !report.is_valid() {
+            return report;
         }
 
         if self.exterior.uid.is_nil() {
@@ -2189,7 +3502,7 @@ impl Opportunity {
             self.exterior.uid = Uuid::new_v5(&namespace, identifier.as_ref());
         }
 
-        Ok(())
+        report
     }
 
     pub async fn load_by_id(db: &Database, id: i32) -> Result<Opportunity, Error> {
@@ -2202,15 +3515,15 @@ impl Opportunity {
             rec.partner_created, rec.partner_updated, rec.partner_opp_url,
             rec.organization_name, rec.organization_type, rec.organization_website, rec.organization_logo_url,
             rec.entity_type, rec.opp_venue, rec.opp_descriptor, rec.min_age, rec.max_age, rec.pes_domain,
-            rec.tags, rec.opp_topics, rec.ticket_required,
+            rec.tags, rec.opp_topics, rec.ticket_required, rec.capacity,
             rec.title, rec.description, rec.short_desc, rec.image_url, rec.image_credit,
-            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.timezone,
-            rec.attraction_hours, rec.cost, rec.languages, rec.is_online,
+            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.recurrence_rdates, rec.recurrence_exdates, rec.timezone,
+            rec.attraction_hours, rec.cost, rec.languages, rec.translations, rec.accessibility, rec.is_online,
             rec.location_type, rec.location_name, rec.location_point_geojson, rec.location_polygon_geojson,
             rec.address_street, rec.address_city, rec.address_state, rec.address_country, rec.address_zip,
             rec.opp_hashtags, rec.opp_social_handles, rec.opp_partner,
-            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status,
-            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data,
+            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status, rec.publish_at, rec.unpublish_at,
+            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data, rec.parent_uid, rec.trashed_at,
         )
     }
 
@@ -2224,15 +3537,15 @@ impl Opportunity {
             rec.partner_created, rec.partner_updated, rec.partner_opp_url,
             rec.organization_name, rec.organization_type, rec.organization_website, rec.organization_logo_url,
             rec.entity_type, rec.opp_venue, rec.opp_descriptor, rec.min_age, rec.max_age, rec.pes_domain,
-            rec.tags, rec.opp_topics, rec.ticket_required,
+            rec.tags, rec.opp_topics, rec.ticket_required, rec.capacity,
             rec.title, rec.description, rec.short_desc, rec.image_url, rec.image_credit,
-            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.timezone,
-            rec.attraction_hours, rec.cost, rec.languages, rec.is_online,
+            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.recurrence_rdates, rec.recurrence_exdates, rec.timezone,
+            rec.attraction_hours, rec.cost, rec.languages, rec.translations, rec.accessibility, rec.is_online,
             rec.location_type, rec.location_name, rec.location_point_geojson, rec.location_polygon_geojson,
             rec.address_street, rec.address_city, rec.address_state, rec.address_country, rec.address_zip,
             rec.opp_hashtags, rec.opp_social_handles, rec.opp_partner,
-            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status,
-            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data,
+            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status, rec.publish_at, rec.unpublish_at,
+            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data, rec.parent_uid, rec.trashed_at,
         )?;
         apply_overlay(&mut opp, rec.overlay_exterior, rec.overlay_interior);
         Ok(opp)
@@ -2248,15 +3561,15 @@ impl Opportunity {
             rec.partner_created, rec.partner_updated, rec.partner_opp_url,
             rec.organization_name, rec.organization_type, rec.organization_website, rec.organization_logo_url,
             rec.entity_type, rec.opp_venue, rec.opp_descriptor, rec.min_age, rec.max_age, rec.pes_domain,
-            rec.tags, rec.opp_topics, rec.ticket_required,
+            rec.tags, rec.opp_topics, rec.ticket_required, rec.capacity,
             rec.title, rec.description, rec.short_desc, rec.image_url, rec.image_credit,
-            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.timezone,
-            rec.attraction_hours, rec.cost, rec.languages, rec.is_online,
+            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.recurrence_rdates, rec.recurrence_exdates, rec.timezone,
+            rec.attraction_hours, rec.cost, rec.languages, rec.translations, rec.accessibility, rec.is_online,
             rec.location_type, rec.location_name, rec.location_point_geojson, rec.location_polygon_geojson,
             rec.address_street, rec.address_city, rec.address_state, rec.address_country, rec.address_zip,
             rec.opp_hashtags, rec.opp_social_handles, rec.opp_partner,
-            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status,
-            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data,
+            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status, rec.publish_at, rec.unpublish_at,
+            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data, rec.parent_uid, rec.trashed_at,
         )
     }
 
@@ -2273,15 +3586,15 @@ impl Opportunity {
             rec.partner_created, rec.partner_updated, rec.partner_opp_url,
             rec.organization_name, rec.organization_type, rec.organization_website, rec.organization_logo_url,
             rec.entity_type, rec.opp_venue, rec.opp_descriptor, rec.min_age, rec.max_age, rec.pes_domain,
-            rec.tags, rec.opp_topics, rec.ticket_required,
+            rec.tags, rec.opp_topics, rec.ticket_required, rec.capacity,
             rec.title, rec.description, rec.short_desc, rec.image_url, rec.image_credit,
-            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.timezone,
-            rec.attraction_hours, rec.cost, rec.languages, rec.is_online,
+            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.recurrence_rdates, rec.recurrence_exdates, rec.timezone,
+            rec.attraction_hours, rec.cost, rec.languages, rec.translations, rec.accessibility, rec.is_online,
             rec.location_type, rec.location_name, rec.location_point_geojson, rec.location_polygon_geojson,
             rec.address_street, rec.address_city, rec.address_state, rec.address_country, rec.address_zip,
             rec.opp_hashtags, rec.opp_social_handles, rec.opp_partner,
-            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status,
-            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data,
+            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status, rec.publish_at, rec.unpublish_at,
+            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data, rec.parent_uid, rec.trashed_at,
         )?;
         apply_overlay(&mut opp, rec.overlay_exterior, rec.overlay_interior);
         Ok(opp)
@@ -2307,7 +3620,7 @@ impl Opportunity {
     }
 
     pub async fn set_id_if_necessary(&mut self, db: &Database) -> Result<(), Error> {
-        if let None = self.id {
+        if self.id.is_none() {
             self.id = Opportunity::id_by_uid(db, &self.exterior.uid).await?;
         }
 
@@ -2324,15 +3637,15 @@ impl Opportunity {
             rec.partner_created, rec.partner_updated, rec.partner_opp_url,
             rec.organization_name, rec.organization_type, rec.organization_website, rec.organization_logo_url,
             rec.entity_type, rec.opp_venue, rec.opp_descriptor, rec.min_age, rec.max_age, rec.pes_domain,
-            rec.tags, rec.opp_topics, rec.ticket_required,
+            rec.tags, rec.opp_topics, rec.ticket_required, rec.capacity,
             rec.title, rec.description, rec.short_desc, rec.image_url, rec.image_credit,
-            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.timezone,
-            rec.attraction_hours, rec.cost, rec.languages, rec.is_online,
+            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.recurrence_rdates, rec.recurrence_exdates, rec.timezone,
+            rec.attraction_hours, rec.cost, rec.languages, rec.translations, rec.accessibility, rec.is_online,
             rec.location_type, rec.location_name, rec.location_point_geojson, rec.location_polygon_geojson,
             rec.address_street, rec.address_city, rec.address_state, rec.address_country, rec.address_zip,
             rec.opp_hashtags, rec.opp_social_handles, rec.opp_partner,
-            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status,
-            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data,
+            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status, rec.publish_at, rec.unpublish_at,
+            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data, rec.parent_uid, rec.trashed_at,
         )
     }
 
@@ -2349,15 +3662,15 @@ impl Opportunity {
             rec.partner_created, rec.partner_updated, rec.partner_opp_url,
             rec.organization_name, rec.organization_type, rec.organization_website, rec.organization_logo_url,
             rec.entity_type, rec.opp_venue, rec.opp_descriptor, rec.min_age, rec.max_age, rec.pes_domain,
-            rec.tags, rec.opp_topics, rec.ticket_required,
+            rec.tags, rec.opp_topics, rec.ticket_required, rec.capacity,
             rec.title, rec.description, rec.short_desc, rec.image_url, rec.image_credit,
-            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.timezone,
-            rec.attraction_hours, rec.cost, rec.languages, rec.is_online,
+            rec.start_datetimes, rec.has_end, rec.end_datetimes, rec.recurrence, rec.end_recurrence, rec.recurrence_rdates, rec.recurrence_exdates, rec.timezone,
+            rec.attraction_hours, rec.cost, rec.languages, rec.translations, rec.accessibility, rec.is_online,
             rec.location_type, rec.location_name, rec.location_point_geojson, rec.location_polygon_geojson,
             rec.address_street, rec.address_city, rec.address_state, rec.address_country, rec.address_zip,
             rec.opp_hashtags, rec.opp_social_handles, rec.opp_partner,
-            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status,
-            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data,
+            rec.accepted, rec.withdrawn, rec.submitted_by, rec.review_status, rec.publish_at, rec.unpublish_at,
+            rec.contact_name, rec.contact_email, rec.contact_phone, rec.extra_data, rec.parent_uid, rec.trashed_at,
         )?;
         apply_overlay(&mut opp, rec.overlay_exterior, rec.overlay_interior);
         Ok(opp)
@@ -2404,7 +3717,21 @@ impl Opportunity {
         Ok(())
     }
 
+    /// Stores the opportunity on behalf of no one in particular. Prefer
+    /// `store_by` when the author is known.
     pub async fn store(&mut self, db: &Database) -> Result<(), Error> {
+        self.store_by(db, &RevisionAuthor::System, "system").await
+    }
+
+    /// Stores the opportunity and records the result as a revision
+    /// attributed to `author`, with `source` describing how the
+    /// change arrived
+    pub async fn store_by(
+        &mut self,
+        db: &Database,
+        author: &RevisionAuthor,
+        source: &str,
+    ) -> Result<(), Error> {
         self.validate().await?;
 
         self.set_slug_if_necessary(db).await?;
@@ -2418,6 +3745,9 @@ impl Opportunity {
         let start_datetimes: Vec<DateTime<FixedOffset>> = self.exterior.start_datetimes.clone();
         let end_datetimes: Vec<DateTime<FixedOffset>> = self.exterior.end_datetimes.clone();
         let recurrence = serialize_enum(&self.exterior.recurrence);
+        let (occurrences, recurrence_until) = self
+            .exterior
+            .materialized_occurrences(&Utc::now().to_fixed_offset());
         let attraction_hours = self.exterior.attraction_hours.as_ref()
             .map(|h| serde_json::to_value(h).unwrap_or_default());
         let cost = serialize_enum(&self.exterior.cost);
@@ -2426,6 +3756,22 @@ impl Opportunity {
         let opp_social_handles = serde_json::to_value(&self.exterior.opp_social_handles)?;
         let review_status = serialize_enum(&self.interior.review_status);
         let extra_data = &self.interior.extra_data;
+        let translations = serde_json::to_value(&self.exterior.translations)?;
+        let accessibility: Vec<String> = serialize_enum_vec(&self.exterior.accessibility);
+
+        // The series this used to belong to needs refreshing as well,
+        // if it has moved to a different one
+        let previous_parent = match self.id {
+            Some(id) => sqlx::query_file_scalar!("db/opportunity/parent_uid_by_id.sql", id)
+                .fetch_optional(db)
+                .await?
+                .flatten(),
+            None => None,
+        };
+
+        // The row and its occurrences are stored together, so that the
+        // occurrences can't be left behind if storing either fails
+        let mut tx = db.begin().await?;
 
         if let Some(id) = self.id {
             sqlx::query_file!(
@@ -2462,7 +3808,10 @@ impl Opportunity {
                 &end_datetimes as &[DateTime<FixedOffset>],
                 recurrence,
                 self.exterior.end_recurrence,
+                &self.exterior.recurrence_rdates as &[DateTime<FixedOffset>],
+                &self.exterior.recurrence_exdates as &[DateTime<FixedOffset>],
                 self.exterior.timezone,
+                recurrence_until,
                 attraction_hours as Option<serde_json::Value>,
                 cost,
                 &self.exterior.languages as &[String],
@@ -2487,8 +3836,14 @@ impl Opportunity {
                 self.interior.contact_email,
                 self.interior.contact_phone,
                 extra_data,
+                translations,
+                &accessibility as &[String],
+                self.exterior.capacity,
+                self.interior.publish_at,
+                self.interior.unpublish_at,
+                self.exterior.parent,
             )
-            .execute(db)
+            .execute(&mut *tx)
             .await?;
         } else {
             let rec = sqlx::query_file!(
@@ -2524,7 +3879,10 @@ impl Opportunity {
                 &end_datetimes as &[DateTime<FixedOffset>],
                 recurrence,
                 self.exterior.end_recurrence,
+                &self.exterior.recurrence_rdates as &[DateTime<FixedOffset>],
+                &self.exterior.recurrence_exdates as &[DateTime<FixedOffset>],
                 self.exterior.timezone,
+                recurrence_until,
                 attraction_hours as Option<serde_json::Value>,
                 cost,
                 &self.exterior.languages as &[String],
@@ -2549,13 +3907,44 @@ impl Opportunity {
                 self.interior.contact_email,
                 self.interior.contact_phone,
                 extra_data,
+                translations,
+                &accessibility as &[String],
+                self.exterior.capacity,
+                self.interior.publish_at,
+                self.interior.unpublish_at,
+                self.exterior.parent,
             )
-            .fetch_one(db)
+            .fetch_one(&mut *tx)
             .await?;
 
             self.id = Some(rec.id);
         };
 
+        if let Some(id) = self.id {
+            Opportunity::replace_occurrences(&mut tx, id, &occurrences).await?;
+        }
+
+        tx.commit().await?;
+
+        if let Some(id) = self.id {
+            Opportunity::record_revision(db, id, author, source).await?;
+            Opportunity::detect_duplicates(db, id).await?;
+        }
+
+        // A parent stored directly keeps its derived dates and
+        // location, rather than the ones it was submitted with
+        if Opportunity::has_series_children(db, &self.exterior.uid).await? {
+            Opportunity::refresh_series(db, &self.exterior.uid).await?;
+        }
+
+        if let Some(parent) = self.exterior.parent {
+            Opportunity::refresh_series(db, &parent).await?;
+        }
+
+        if let Some(previous) = previous_parent.filter(|prev| Some(*prev) != self.exterior.parent) {
+            Opportunity::refresh_series(db, &previous).await?;
+        }
+
         let overlay = sqlx::query_scalar!(
             r#"SELECT true AS "exists!" FROM c_opportunity_overlay WHERE opportunity_id = $1"#,
             self.id
@@ -2595,4 +3984,252 @@ VALUES ($1, $2, $3)
 
         Ok(())
     }
+
+    /// Replaces the stored occurrences of an opportunity, as part of a
+    /// transaction which also stores whatever they were derived from
+    async fn replace_occurrences(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        id: i32,
+        occurrences: &[Occurrence],
+    ) -> Result<(), Error> {
+        let starts: Vec<DateTime<FixedOffset>> = occurrences.iter().map(|occ| occ.start).collect();
+        let ends: Vec<Option<DateTime<FixedOffset>>> =
+            occurrences.iter().map(|occ| occ.end).collect();
+
+        sqlx::query_file!("db/opportunity/delete_occurrences.sql", id)
+            .execute(&mut **tx)
+            .await?;
+
+        sqlx::query_file!(
+            "db/opportunity/insert_occurrences.sql",
+            id,
+            &starts as &[DateTime<FixedOffset>],
+            &ends as &[Option<DateTime<FixedOffset>>],
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Rolls the materialized occurrences of recurring opportunities
+    /// forward, so that they continue to extend to the horizon. The
+    /// server runs this once a day, and it can also be run by hand
+    /// with the toolkit's refresh-occurrences command. Returns the
+    /// number of opportunities refreshed.
+    pub async fn refresh_occurrences(db: &Database) -> Result<u32, Error> {
+        let ids = sqlx::query_file_scalar!("db/opportunity/recurring_ids.sql")
+            .fetch_all(db)
+            .await?;
+
+        let now = Utc::now().to_fixed_offset();
+        let mut count = 0;
+
+        for id in ids {
+            let opp = Opportunity::load_by_id(db, id).await?;
+            let (occurrences, _) = opp.exterior.materialized_occurrences(&now);
+
+            let mut tx = db.begin().await?;
+            Opportunity::replace_occurrences(&mut tx, id, &occurrences).await?;
+            tx.commit().await?;
+
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_areas_deserialize_parsed_or_structure() {
+        let polygon = r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}"#;
+
+        let parsed: OpportunityQuery = serde_json::from_value(serde_json::json!({
+            "within_bbox": "-80.1, 40, -79.5,40.6",
+            "within_polygon": polygon,
+        }))
+        .unwrap();
+
+        assert_eq!(parsed.within_bbox, Some((-80.1, 40.0, -79.5, 40.6)));
+        assert!(matches!(parsed.within_polygon, Some(Geometry::Polygon(_))));
+
+        let again: OpportunityQuery =
+            serde_json::from_value(serde_json::to_value(&parsed).unwrap()).unwrap();
+
+        assert_eq!(again.within_bbox, parsed.within_bbox);
+        assert!(matches!(again.within_polygon, Some(Geometry::Polygon(_))));
+
+        assert!(serde_json::from_value::<OpportunityQuery>(
+            serde_json::json!({ "within_bbox": "1,2,3" })
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn search_areas_are_validated() {
+        let query = OpportunityQuery {
+            within_bbox: Some((-80.0, 41.0, -79.0, 40.0)),
+            ..Default::default()
+        };
+
+        let built = build_matching_query(&["*"], &query, Default::default(), &Pagination::All);
+        assert!(matches!(built, Err(Error::OutOfBounds(_))));
+
+        let query = OpportunityQuery {
+            within_polygon: serde_json::from_str(r#"{"type":"Point","coordinates":[0,0]}"#)
+                .unwrap(),
+            ..Default::default()
+        };
+
+        let built = build_matching_query(&["*"], &query, Default::default(), &Pagination::All);
+        assert!(matches!(built, Err(Error::Value(_))));
+
+        let polygon = |json: &str| -> Result<(), Error> {
+            check_search_polygon(&serde_json::from_str(json).unwrap())
+        };
+
+        assert!(polygon(r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}"#).is_ok());
+        assert!(matches!(
+            polygon(r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[0,0]]]}"#),
+            Err(Error::Value(_))
+        ));
+        assert!(matches!(
+            polygon(r#"{"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,1]]]}"#),
+            Err(Error::Value(_))
+        ));
+        assert!(matches!(
+            polygon(r#"{"type":"MultiPolygon","coordinates":[[]]}"#),
+            Err(Error::Value(_))
+        ));
+        assert!(matches!(
+            polygon(r#"{"type":"Polygon","coordinates":[[[0,0],[200,0],[1,91],[0,0]]]}"#),
+            Err(Error::OutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn accessibility_filter_requires_every_accommodation() {
+        let query = OpportunityQuery {
+            accessibility: Some(vec![
+                Accessibility::WheelchairAccessible,
+                Accessibility::SignLanguageInterpretation,
+            ]),
+            ..Default::default()
+        };
+
+        let (sql, params) =
+            build_matching_query(&["*"], &query, Default::default(), &Pagination::All).unwrap();
+        assert!(sql.contains("<@ primary_table.accessibility"));
+        assert!(params.iter().any(|p| matches!(
+            p,
+            ParamValue::RawVecString(v)
+                if v == &["wheelchair_accessible", "sign_language_interpretation"]
+        )));
+    }
+
+    #[test]
+    fn review_status_honours_publication_schedule() {
+        let now = DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z").unwrap();
+        let earlier = now - Duration::hours(1);
+        let later = now + Duration::hours(1);
+
+        assert!(ReviewStatus::Publish.public(None, None, &now));
+        assert!(!ReviewStatus::Publish.public(Some(&later), None, &now));
+        assert!(!ReviewStatus::NotRequired.public(None, Some(&earlier), &now));
+        assert!(ReviewStatus::NotRequired.public(Some(&earlier), Some(&later), &now));
+
+        assert!(!ReviewStatus::Scheduled.public(None, None, &now));
+        assert!(!ReviewStatus::Scheduled.public(Some(&later), None, &now));
+        assert!(ReviewStatus::Scheduled.public(Some(&earlier), None, &now));
+        assert!(!ReviewStatus::Draft.public(Some(&earlier), None, &now));
+    }
+
+    #[test]
+    fn facet_counts_match_either_representation() {
+        let counts: Vec<FacetCount<Descriptor>> = facet_counts(&[
+            ("citizen_science".to_string(), 3),
+            ("hack-a-thon".to_string(), 1),
+            ("hackathon".to_string(), 1),
+            ("nonsense".to_string(), 7),
+        ]);
+
+        assert_eq!(counts.len(), Descriptor::iter().count());
+        assert_eq!(counts[0].value, Descriptor::CitizenScience);
+        assert_eq!(counts[0].count, 3);
+        assert_eq!(counts[1].value, Descriptor::Hackathon);
+        assert_eq!(counts[1].count, 2);
+        assert!(counts[2..].iter().all(|c| c.count == 0));
+    }
+
+    #[test]
+    fn facet_unfiltered_removes_only_its_own_filter() {
+        let query = OpportunityQuery {
+            topics: Some(vec![Topic::Biology]),
+            cost: Some(Cost::Free),
+            physical: Some(OpportunityQueryPhysical::InPersonOrOnline),
+            ..Default::default()
+        };
+
+        let without = Facet::Topic.unfiltered(&query).unwrap();
+        assert!(without.topics.is_none());
+        assert_eq!(without.cost, Some(Cost::Free));
+
+        // Choosing both physical modes isn't really a filter
+        assert!(Facet::Physical.unfiltered(&query).is_none());
+        assert!(Facet::Domain.unfiltered(&query).is_none());
+    }
+
+    #[test]
+    fn paginate_links_neighboring_pages() {
+        let position = |n: u32| Some(format!(r#"[["{n}"], "2026-10-18T12:00:00+00:00"]"#));
+        let rows =
+            |range: std::ops::Range<u32>| range.map(|n| (n, position(n))).collect::<Vec<_>>();
+        let ordering = OpportunityQueryOrdering::Alphabetical;
+
+        let (page, cursors) = paginate(
+            rows(0..4),
+            ordering,
+            &Pagination::Cursor {
+                cursor: None,
+                size: 3,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(page, vec![0, 1, 2]);
+        assert!(cursors.prev.is_none());
+
+        let next = OpportunityCursor::decode(&cursors.next.clone().unwrap()).unwrap();
+        assert_eq!(next.keys, json!(["2"]));
+        assert!(!next.backward);
+
+        // Backward pages arrive in reverse order, with the extra row
+        // at the end
+        let (page, cursors) = paginate(
+            rows(0..4).into_iter().rev().collect(),
+            ordering,
+            &Pagination::Cursor {
+                cursor: Some(
+                    OpportunityCursor {
+                        backward: true,
+                        ..next
+                    }
+                    .encode(),
+                ),
+                size: 3,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(page, vec![1, 2, 3]);
+        assert!(cursors.next.is_some());
+
+        let prev = OpportunityCursor::decode(&cursors.prev.unwrap()).unwrap();
+        assert_eq!(prev.keys, json!(["1"]));
+        assert!(prev.backward);
+    }
 }
@@ -0,0 +1,356 @@
+//! The review of submitted opportunities. Each opportunity has a
+//! thread of comments between its submitter and the people reviewing
+//! it, into which review status transitions are also recorded, and
+//! pending opportunities can be assigned to a particular reviewer.
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Opportunity, ReviewStatus};
+use crate::emails::EmailMessage;
+use crate::model::serde_helpers::{deserialize_enum, serialize_enum};
+use crate::model::Error;
+use crate::{Database, ToFixedOffset};
+
+/// One entry in an opportunity's moderation thread. Entries which
+/// record a review status transition have `from_status` and
+/// `to_status`, and their body is the note that went with it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationComment {
+    pub id: i32,
+    pub author: Option<Uuid>,
+    pub author_name: Option<String>,
+    pub body: String,
+    pub from_status: Option<ReviewStatus>,
+    pub to_status: Option<ReviewStatus>,
+    pub created: DateTime<FixedOffset>,
+}
+
+/// An opportunity waiting for review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationQueueEntry {
+    pub uid: Uuid,
+    pub slug: String,
+    pub title: String,
+    pub partner: Uuid,
+    pub partner_name: Option<String>,
+    pub submitted_by: Option<Uuid>,
+    pub pending_since: DateTime<FixedOffset>,
+    /// Seconds since the opportunity entered the pending status
+    pub age: i64,
+    pub assignee: Option<Uuid>,
+    pub assignee_name: Option<String>,
+}
+
+/// Which email to send about a moderation event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModerationNotice {
+    /// To the reviewers, when the submitter asks for review
+    PendingApproval,
+    /// To the submitter, when a reviewer publishes the opportunity
+    Approved,
+    /// To the submitter, when a reviewer schedules the opportunity
+    Scheduled,
+    /// To the submitter, when a reviewer sends it back for revisions
+    ReturnedToDraft,
+    /// To the submitter, when a reviewer rejects the opportunity
+    Rejected,
+    /// To whichever party didn't make any other status change
+    StatusChanged,
+    /// To whichever party didn't write a new comment
+    Comment,
+}
+
+impl ModerationNotice {
+    /// The notice for a transition to `to`. Transitions made by the
+    /// submitter notify the reviewers, and those made by anyone else
+    /// notify the submitter.
+    pub fn for_transition(to: ReviewStatus, by_submitter: bool) -> ModerationNotice {
+        match (to, by_submitter) {
+            (ReviewStatus::Pending, true) => ModerationNotice::PendingApproval,
+            (_, true) => ModerationNotice::StatusChanged,
+            (ReviewStatus::Publish, false) => ModerationNotice::Approved,
+            (ReviewStatus::Scheduled, false) => ModerationNotice::Scheduled,
+            (ReviewStatus::Draft, false) => ModerationNotice::ReturnedToDraft,
+            (ReviewStatus::Reject, false) => ModerationNotice::Rejected,
+            (_, false) => ModerationNotice::StatusChanged,
+        }
+    }
+
+    fn template(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            ModerationNotice::PendingApproval => (
+                "opportunity-pending-approval",
+                "Pending Approval on Science Near Me: {title}",
+                r#"
+<p>The {org_name} opportunity <strong>{title}</strong> on {partner_name} / Science Near Me has been created or updated, and is pending approval for publication.</p>
+{note}
+<p>Please evaluate the opportunity and <a href="https://sciencenearme.org/exchange/{partner_uid}/{opp_slug}">approve, reject, or send it back to draft</a> it.</p>
+"#,
+            ),
+            ModerationNotice::Approved => (
+                "opportunity-approved",
+                "Published on Science Near Me: {title}",
+                r#"
+<p>The opportunity <strong>{title}</strong> has been published on {partner_name} and Science Near Me.</p>
+{note}
+<p>You can view the opportunity on {partner_name}'s web site or at <a href="https://sciencenearme.org/{opp_slug}">Science Near Me</a>.</p>
+"#,
+            ),
+            ModerationNotice::Scheduled => (
+                "opportunity-scheduled",
+                "Scheduled for publication on Science Near Me: {title}",
+                r#"
+<p>The opportunity <strong>{title}</strong> has been approved, and will be published on {partner_name} and Science Near Me on {publish_at}.</p>
+{note}
+"#,
+            ),
+            ModerationNotice::ReturnedToDraft => (
+                "opportunity-returned-to-draft",
+                "Revisions needed on Science Near Me: {title}",
+                r#"
+<p>The opportunity <strong>{title}</strong> has been returned to draft status, as it still requires work in order to be ready for publication.</p>
+{note}
+"#,
+            ),
+            ModerationNotice::Rejected => (
+                "opportunity-rejected",
+                "Not published on Science Near Me: {title}",
+                r#"
+<p>We're sorry, but the opportunity <strong>{title}</strong> has been rejected for publication.</p>
+{note}
+"#,
+            ),
+            ModerationNotice::StatusChanged => (
+                "opportunity-status-changed",
+                "Status changed on Science Near Me: {title}",
+                r#"
+<p>{author_name} changed the status of the opportunity <strong>{title}</strong> from {from_status} to {to_status}.</p>
+{note}
+<p>You can follow its review on <a href="https://sciencenearme.org/exchange/{partner_uid}/{opp_slug}">Science Near Me</a>.</p>
+"#,
+            ),
+            ModerationNotice::Comment => (
+                "moderation-comment",
+                "New comment on Science Near Me: {title}",
+                r#"
+<p>{author_name} commented on the review of the opportunity <strong>{title}</strong>.</p>
+{note}
+<p>You can reply on <a href="https://sciencenearme.org/exchange/{partner_uid}/{opp_slug}">Science Near Me</a>.</p>
+"#,
+            ),
+        }
+    }
+
+    /// Fills in the email for this notice. `note` is the comment or
+    /// transition note written by `author_name`, and may be empty.
+    pub async fn message(
+        &self,
+        db: &Database,
+        opp: &Opportunity,
+        partner_name: &str,
+        author_name: &str,
+        from: ReviewStatus,
+        note: &str,
+    ) -> EmailMessage {
+        let (slug, subject, body) = self.template();
+
+        let template = EmailMessage::load_or_default(db, slug, subject, body).await;
+
+        let note = if note.trim().is_empty() {
+            String::new()
+        } else {
+            format!("<blockquote>{}</blockquote>", ammonia::clean_text(note))
+        };
+
+        template.materialize(vec![
+            ("title", opp.exterior.title.clone()),
+            ("partner_name", partner_name.to_string()),
+            ("partner_uid", opp.exterior.partner.to_string()),
+            ("org_name", opp.exterior.organization_name.clone()),
+            ("opp_slug", opp.exterior.slug.clone()),
+            ("author_name", author_name.to_string()),
+            ("from_status", status_label(from).to_string()),
+            (
+                "to_status",
+                status_label(opp.interior.review_status).to_string(),
+            ),
+            (
+                "publish_at",
+                opp.interior
+                    .publish_at
+                    .map(|dt| dt.format("%B %-d, %Y at %-I:%M %p UTC%:z").to_string())
+                    .unwrap_or_default(),
+            ),
+            ("note", note),
+        ])
+    }
+}
+
+fn status_label(status: ReviewStatus) -> &'static str {
+    match status {
+        ReviewStatus::Draft => "draft",
+        ReviewStatus::Pending => "pending review",
+        ReviewStatus::Reject => "rejected",
+        ReviewStatus::Publish => "published",
+        ReviewStatus::Scheduled => "scheduled",
+        ReviewStatus::NotRequired => "published without review",
+    }
+}
+
+impl Opportunity {
+    /// The moderation thread, oldest first
+    pub async fn moderation_thread(&self, db: &Database) -> Result<Vec<ModerationComment>, Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        Ok(sqlx::query_file!("db/moderation/thread.sql", opp_id)
+            .map(|row| ModerationComment {
+                id: row.id,
+                author: row.author,
+                author_name: row.author_name,
+                body: row.body,
+                from_status: row.from_status.and_then(|s| deserialize_enum(&s).ok()),
+                to_status: row.to_status.and_then(|s| deserialize_enum(&s).ok()),
+                created: row.created.to_fixed_offset(),
+            })
+            .fetch_all(db)
+            .await?)
+    }
+
+    pub async fn add_moderation_comment(
+        &self,
+        db: &Database,
+        author: &Uuid,
+        body: &str,
+    ) -> Result<(), Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        if body.trim().is_empty() {
+            return Err(Error::Missing("body".to_string()));
+        }
+
+        sqlx::query_file!(
+            "db/moderation/add_comment.sql",
+            opp_id,
+            author,
+            body.trim(),
+            None::<String>,
+            None::<String>,
+        )
+        .fetch_one(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a change of review status from `from` to the current
+    /// status in the moderation thread, along with its note. A
+    /// missing author means the change was made automatically.
+    pub async fn record_review_transition(
+        &self,
+        db: &Database,
+        author: Option<&Uuid>,
+        from: ReviewStatus,
+        note: &str,
+    ) -> Result<(), Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        sqlx::query_file!(
+            "db/moderation/add_comment.sql",
+            opp_id,
+            author,
+            note.trim(),
+            Some(serialize_enum(&from)),
+            Some(serialize_enum(&self.interior.review_status)),
+        )
+        .fetch_one(db)
+        .await?;
+
+        sqlx::query_file!("db/moderation/status_changed.sql", opp_id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// The person assigned to review this opportunity, if any
+    pub async fn moderation_assignee(&self, db: &Database) -> Result<Option<Uuid>, Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        Ok(
+            sqlx::query_file_scalar!("db/moderation/assignee.sql", opp_id)
+                .fetch_optional(db)
+                .await?,
+        )
+    }
+
+    /// Assigns the review of this opportunity to a person, or clears
+    /// the assignment
+    pub async fn assign_moderator(
+        &self,
+        db: &Database,
+        assignee: Option<&Uuid>,
+    ) -> Result<(), Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        sqlx::query_file!("db/moderation/assign.sql", opp_id, assignee)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Opportunities pending review, longest waiting first. When
+    /// `manager` is given, only opportunities belonging to partners
+    /// that person manages are included.
+    pub async fn moderation_queue(
+        db: &Database,
+        manager: Option<&Uuid>,
+        now: &DateTime<FixedOffset>,
+    ) -> Result<Vec<ModerationQueueEntry>, Error> {
+        Ok(sqlx::query_file!("db/moderation/queue.sql", manager)
+            .map(|row| {
+                let pending_since = row.pending_since.to_fixed_offset();
+                ModerationQueueEntry {
+                    uid: row.uid,
+                    slug: row.slug,
+                    title: row.title,
+                    partner: row.partner,
+                    partner_name: row.partner_name,
+                    submitted_by: row.submitted_by,
+                    pending_since,
+                    age: (*now - pending_since).num_seconds(),
+                    assignee: row.assignee,
+                    assignee_name: row.assignee_name,
+                }
+            })
+            .fetch_all(db)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitions_notify_the_other_party() {
+        assert_eq!(
+            ModerationNotice::for_transition(ReviewStatus::Pending, true),
+            ModerationNotice::PendingApproval
+        );
+        assert_eq!(
+            ModerationNotice::for_transition(ReviewStatus::Draft, true),
+            ModerationNotice::StatusChanged
+        );
+        assert_eq!(
+            ModerationNotice::for_transition(ReviewStatus::Reject, false),
+            ModerationNotice::Rejected
+        );
+        assert_eq!(
+            ModerationNotice::for_transition(ReviewStatus::Pending, false),
+            ModerationNotice::StatusChanged
+        );
+    }
+}
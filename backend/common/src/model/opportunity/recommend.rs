@@ -0,0 +1,566 @@
+//! Recommendations of opportunities related to a set of interests,
+//! which partners show as "related opportunities" on their own sites.
+//! The database gathers the current opportunities which share at
+//! least one thing with the interests, keeping the best few thousand
+//! by a rough version of the same ranking, and they are ranked here,
+//! so that the ranking doesn't depend on anything but its inputs. Only
+//! what the ranking needs is loaded for the candidates, and the rest
+//! of each reference is loaded for the requested page alone.
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use super::{Opportunity, OpportunityReference, Topic};
+use crate::model::serde_helpers::{deserialize_enum_vec, serialize_enum_vec};
+use crate::model::{Error, Pagination};
+use crate::{Database, ToFixedOffset};
+
+/// Meters from the point of interest at which an opportunity counts
+/// as half as near as one right there
+pub const RECOMMEND_RADIUS: f64 = 50_000.0;
+
+/// Days until the next occurrence at which an opportunity counts as
+/// half as current as one happening now
+pub const RECOMMEND_HORIZON_DAYS: f64 = 30.0;
+
+// Opportunities further away than this are only candidates if
+// something other than their location is related
+const CANDIDATE_RADIUS: f64 = RECOMMEND_RADIUS * 4.0;
+
+// Most candidates ranked for a single request. The database keeps the
+// ones which look best, and the rest are never shown.
+const MAX_CANDIDATES: i64 = 2000;
+
+// Currency of an opportunity which isn't scheduled, or which is
+// already under way, the same as one happening RECOMMEND_HORIZON_DAYS
+// from now. These are often ongoing, so they shouldn't outrank
+// opportunities which are about to happen, but they shouldn't be
+// buried either.
+const UNSCHEDULED_CURRENCY: f32 = 0.5;
+
+const TOPIC_WEIGHT: f32 = 0.3;
+const TAG_WEIGHT: f32 = 0.2;
+const TEXT_WEIGHT: f32 = 0.25;
+const LOCATION_WEIGHT: f32 = 0.15;
+const TIME_WEIGHT: f32 = 0.1;
+
+// Words shorter than MIN_TERM are ignored, and longer ones are cut
+// down to TERM_PREFIX characters, as a rough stand-in for stemming:
+// "astronomy" and "astronomer" both become "astro", and "galaxy" and
+// "galaxies" both become "galax"
+const MIN_TERM: usize = 3;
+const TERM_PREFIX: usize = 5;
+
+const STOP_WORDS: &[&str] = &[
+    "about", "all", "also", "and", "are", "but", "can", "for", "from", "has", "have", "how",
+    "into", "its", "more", "not", "our", "than", "that", "the", "their", "them", "then", "there",
+    "these", "they", "this", "was", "were", "what", "when", "where", "which", "who", "will",
+    "with", "you", "your",
+];
+
+/// What the opportunities should be related to. Anything left empty
+/// doesn't affect the ranking.
+#[derive(Debug, Clone, Default)]
+pub struct Interests {
+    pub tags: Vec<String>,
+    pub topics: Vec<Topic>,
+    /// A passage of text, such as an article the recommendations
+    /// will be shown beside
+    pub text: Option<String>,
+    pub longitude: Option<f64>,
+    pub latitude: Option<f64>,
+}
+
+/// An opportunity which might be recommended
+#[derive(Debug, Clone, Default)]
+pub struct RecommendCandidate {
+    pub uid: Uuid,
+    /// The title and descriptions, or empty if there's no text of
+    /// interest to compare them with
+    pub words: String,
+    pub tags: Vec<String>,
+    pub topics: Vec<Topic>,
+    /// Meters from the point of interest, or None if either of them
+    /// has no location
+    pub distance: Option<f64>,
+    /// The start of the next occurrence which hasn't ended, which is
+    /// in the past if it is under way, or None if the opportunity
+    /// isn't scheduled
+    pub next_occurrence: Option<DateTime<FixedOffset>>,
+}
+
+/// How closely an opportunity matches a set of interests
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecommendScore {
+    /// Fraction of the requested topics the opportunity has
+    pub topic_overlap: f32,
+    /// Fraction of the requested tags the opportunity has, ignoring
+    /// case
+    pub tag_overlap: f32,
+    /// Cosine similarity of the words in the requested text and in
+    /// the opportunity's title and descriptions, from 0 to 1
+    pub text_similarity: f32,
+    /// Meters from the point of interest, if known
+    pub distance: Option<f64>,
+    /// Days until the opportunity next begins, or None if it is
+    /// already under way or isn't scheduled
+    pub days_until: Option<f64>,
+}
+
+impl RecommendScore {
+    /// Combines the evidence into a single score from 0 to 1
+    pub fn score(&self) -> f32 {
+        let nearness = match self.distance {
+            Some(distance) => (RECOMMEND_RADIUS / (RECOMMEND_RADIUS + distance.max(0.0))) as f32,
+            None => 0.0,
+        };
+
+        let currency = match self.days_until {
+            Some(days) => {
+                (RECOMMEND_HORIZON_DAYS / (RECOMMEND_HORIZON_DAYS + days.max(0.0))) as f32
+            }
+            None => UNSCHEDULED_CURRENCY,
+        };
+
+        TOPIC_WEIGHT * self.topic_overlap.clamp(0.0, 1.0)
+            + TAG_WEIGHT * self.tag_overlap.clamp(0.0, 1.0)
+            + TEXT_WEIGHT * self.text_similarity.clamp(0.0, 1.0)
+            + LOCATION_WEIGHT * nearness
+            + TIME_WEIGHT * currency
+    }
+}
+
+// Counts of the terms in a passage of text. A BTreeMap keeps the
+// order of the floating point sums, and so the scores, stable.
+fn terms(text: &str) -> BTreeMap<String, f32> {
+    let mut counts = BTreeMap::new();
+
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+
+        if word.chars().count() < MIN_TERM || STOP_WORDS.contains(&word.as_str()) {
+            continue;
+        }
+
+        *counts
+            .entry(word.chars().take(TERM_PREFIX).collect())
+            .or_insert(0.0) += 1.0;
+    }
+
+    counts
+}
+
+fn cosine(a: &BTreeMap<String, f32>, b: &BTreeMap<String, f32>) -> f32 {
+    let dot: f32 = a
+        .iter()
+        .filter_map(|(term, x)| b.get(term).map(|y| x * y))
+        .sum();
+
+    if dot == 0.0 {
+        return 0.0;
+    }
+
+    let norm = |v: &BTreeMap<String, f32>| v.values().map(|x| x * x).sum::<f32>().sqrt();
+
+    dot / (norm(a) * norm(b))
+}
+
+fn overlap<T: PartialEq>(wanted: &[T], has: &[T]) -> f32 {
+    if wanted.is_empty() {
+        return 0.0;
+    }
+
+    wanted.iter().filter(|w| has.contains(w)).count() as f32 / wanted.len() as f32
+}
+
+impl Interests {
+    fn wanted_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self.tags.iter().map(|tag| tag.to_lowercase()).collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    fn wanted_topics(&self) -> Vec<Topic> {
+        let mut topics = Vec::with_capacity(self.topics.len());
+
+        for topic in &self.topics {
+            if !topics.contains(topic) {
+                topics.push(*topic);
+            }
+        }
+
+        topics
+    }
+
+    fn wanted_text(&self) -> Option<&str> {
+        self.text
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Scores every candidate, and orders them from best to worst
+    /// match. Equal scores are ordered by uid, so the order doesn't
+    /// depend on the order of the candidates.
+    pub fn rank(
+        &self,
+        candidates: Vec<RecommendCandidate>,
+        now: &DateTime<FixedOffset>,
+    ) -> Vec<(RecommendCandidate, RecommendScore)> {
+        let tags = self.wanted_tags();
+        let topics = self.wanted_topics();
+        let text = self.wanted_text().map(terms).unwrap_or_default();
+
+        let mut ranked: Vec<_> = candidates
+            .into_iter()
+            .map(|candidate| {
+                let has_tags: Vec<String> = candidate
+                    .tags
+                    .iter()
+                    .map(|tag| tag.to_lowercase())
+                    .collect();
+
+                let text_similarity = if text.is_empty() {
+                    0.0
+                } else {
+                    cosine(&text, &terms(&candidate.words))
+                };
+
+                let days_until = candidate
+                    .next_occurrence
+                    .filter(|next| next >= now)
+                    .map(|next| (next - *now).num_seconds() as f64 / 86400.0);
+
+                let score = RecommendScore {
+                    topic_overlap: overlap(&topics, &candidate.topics),
+                    tag_overlap: overlap(&tags, &has_tags),
+                    text_similarity,
+                    distance: candidate.distance,
+                    days_until,
+                };
+
+                (candidate, score)
+            })
+            .collect();
+
+        ranked.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .score()
+                .total_cmp(&a_score.score())
+                .then_with(|| a.uid.cmp(&b.uid))
+        });
+
+        ranked
+    }
+}
+
+/// The part of `items` selected by `pagination`. Cursors aren't
+/// supported, since the ranking isn't keyed on anything stored.
+fn paginate<T>(items: Vec<T>, pagination: &Pagination) -> Result<Vec<T>, Error> {
+    let (skip, take) = match pagination {
+        Pagination::All => (0, items.len()),
+        Pagination::One => (0, 1),
+        Pagination::Page { index, size } => ((*index as usize) * (*size as usize), *size as usize),
+        Pagination::Cursor { .. } => return Err(Error::Value("cursor".to_string())),
+    };
+
+    Ok(items.into_iter().skip(skip).take(take).collect())
+}
+
+impl Opportunity {
+    /// The current opportunities which best match the interests, best
+    /// first, along with the number of them which can be paged
+    /// through. Interests which are all empty match nothing.
+    pub async fn recommend(
+        db: &Database,
+        interests: &Interests,
+        pagination: &Pagination,
+    ) -> Result<(Vec<OpportunityReference>, u32), Error> {
+        let candidates: Vec<RecommendCandidate> = sqlx::query_file!(
+            "db/opportunity/recommend_candidates.sql",
+            &serialize_enum_vec(&interests.wanted_topics()) as &[String],
+            &interests.wanted_tags() as &[String],
+            interests.wanted_text(),
+            interests.longitude,
+            interests.latitude,
+            CANDIDATE_RADIUS,
+            MAX_CANDIDATES,
+            TOPIC_WEIGHT as f64,
+            TAG_WEIGHT as f64,
+            TEXT_WEIGHT as f64,
+            LOCATION_WEIGHT as f64,
+            RECOMMEND_RADIUS,
+        )
+        .map(|row| RecommendCandidate {
+            uid: row.uid,
+            words: row.words,
+            tags: row.tags,
+            topics: deserialize_enum_vec(&row.topics),
+            distance: row.distance,
+            next_occurrence: row.next_occurrence.map(|dt| dt.to_fixed_offset()),
+        })
+        .fetch_all(db)
+        .await?;
+
+        // Anything past the candidates the database kept can't be
+        // reached, so it isn't counted
+        let total = candidates.len() as u32;
+
+        let now = chrono::Utc::now().to_fixed_offset();
+        let page: Vec<Uuid> = paginate(interests.rank(candidates, &now), pagination)?
+            .into_iter()
+            .map(|(candidate, _)| candidate.uid)
+            .collect();
+
+        let references = sqlx::query_file_as!(
+            OpportunityReference,
+            "db/opportunity/recommend_references.sql",
+            &page as &[Uuid]
+        )
+        .fetch_all(db)
+        .await?;
+
+        Ok((references, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2026-03-01T12:00:00-05:00").unwrap()
+    }
+
+    fn days_from_now(days: i64) -> Option<DateTime<FixedOffset>> {
+        Some(now() + chrono::Duration::days(days))
+    }
+
+    fn fixtures() -> Vec<RecommendCandidate> {
+        vec![
+            RecommendCandidate {
+                uid: Uuid::from_u128(1),
+                words: "Star Party at the Observatory \
+                        Look through telescopes with local astronomers \
+                        Our astronomy club hosts a night of stargazing."
+                    .into(),
+                tags: vec!["Stargazing".into(), "telescopes".into()],
+                topics: vec![Topic::AstronomyAndSpace],
+                distance: Some(8_000.0),
+                next_occurrence: days_from_now(3),
+                ..Default::default()
+            },
+            RecommendCandidate {
+                uid: Uuid::from_u128(2),
+                words: "Galaxy Classification Online \
+                        Sort galaxies by shape from home \
+                        Help astronomers classify galaxies in telescope images."
+                    .into(),
+                tags: vec!["citizen science".into()],
+                topics: vec![Topic::AstronomyAndSpace],
+                distance: None,
+                next_occurrence: None,
+                ..Default::default()
+            },
+            RecommendCandidate {
+                uid: Uuid::from_u128(3),
+                words: "Backyard Bird Count \
+                        Count the birds that visit your feeder \
+                        A weekend of bird watching for the whole family."
+                    .into(),
+                tags: vec!["birds".into()],
+                topics: vec![Topic::Animals, Topic::EcologyAndEnvironment],
+                distance: Some(12_000.0),
+                next_occurrence: days_from_now(10),
+                ..Default::default()
+            },
+            RecommendCandidate {
+                uid: Uuid::from_u128(4),
+                words: "Robotics Workshop \
+                        Build and program a small robot \
+                        Teams design robots to complete a maze."
+                    .into(),
+                tags: vec!["robots".into(), "stargazing".into()],
+                topics: vec![Topic::Technology, Topic::Engineering],
+                distance: Some(500_000.0),
+                next_occurrence: days_from_now(60),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn ranked_uids(interests: &Interests, candidates: Vec<RecommendCandidate>) -> Vec<u128> {
+        interests
+            .rank(candidates, &now())
+            .into_iter()
+            .map(|(candidate, _)| candidate.uid.as_u128())
+            .collect()
+    }
+
+    #[test]
+    fn topics_and_tags_rank_matches_first() {
+        let interests = Interests {
+            topics: vec![Topic::AstronomyAndSpace],
+            tags: vec!["STARGAZING".into()],
+            ..Default::default()
+        };
+
+        let ranked = interests.rank(fixtures(), &now());
+
+        assert_eq!(ranked[0].0.uid.as_u128(), 1);
+        assert_eq!(ranked[0].1.topic_overlap, 1.0);
+        assert_eq!(ranked[0].1.tag_overlap, 1.0);
+        assert_eq!(ranked[1].0.uid.as_u128(), 2);
+        assert_eq!(ranked[1].1.tag_overlap, 0.0);
+    }
+
+    #[test]
+    fn text_matches_related_words() {
+        let interests = Interests {
+            text: Some("The astronomer pointed her telescope at a distant galaxy.".into()),
+            ..Default::default()
+        };
+
+        let similarity: BTreeMap<u128, f32> = interests
+            .rank(fixtures(), &now())
+            .into_iter()
+            .map(|(candidate, score)| (candidate.uid.as_u128(), score.text_similarity))
+            .collect();
+
+        assert!(similarity[&2] > similarity[&1]);
+        assert!(similarity[&1] > 0.0);
+        assert_eq!(similarity[&3], 0.0);
+        assert_eq!(similarity[&4], 0.0);
+    }
+
+    #[test]
+    fn nearer_and_sooner_rank_higher() {
+        let near = RecommendCandidate {
+            uid: Uuid::from_u128(10),
+            distance: Some(1_000.0),
+            next_occurrence: days_from_now(30),
+            ..Default::default()
+        };
+        let far = RecommendCandidate {
+            uid: Uuid::from_u128(11),
+            distance: Some(100_000.0),
+            ..near.clone()
+        };
+        let unplaced = RecommendCandidate {
+            uid: Uuid::from_u128(12),
+            distance: None,
+            ..near.clone()
+        };
+        let sooner = RecommendCandidate {
+            uid: Uuid::from_u128(13),
+            next_occurrence: days_from_now(1),
+            ..far.clone()
+        };
+
+        let interests = Interests {
+            longitude: Some(-84.39),
+            latitude: Some(33.75),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ranked_uids(&interests, vec![unplaced, far, near, sooner]),
+            vec![10, 13, 11, 12]
+        );
+    }
+
+    #[test]
+    fn unscheduled_ranks_below_soon() {
+        let soon = RecommendCandidate {
+            uid: Uuid::from_u128(30),
+            topics: vec![Topic::Animals],
+            next_occurrence: days_from_now(2),
+            ..Default::default()
+        };
+        let unscheduled = RecommendCandidate {
+            uid: Uuid::from_u128(31),
+            next_occurrence: None,
+            ..soon.clone()
+        };
+        let under_way = RecommendCandidate {
+            uid: Uuid::from_u128(32),
+            next_occurrence: days_from_now(-90),
+            ..soon.clone()
+        };
+        let distant = RecommendCandidate {
+            uid: Uuid::from_u128(33),
+            next_occurrence: days_from_now(300),
+            ..soon.clone()
+        };
+
+        let interests = Interests {
+            topics: vec![Topic::Animals],
+            ..Default::default()
+        };
+
+        let ranked = interests.rank(vec![distant, under_way, unscheduled, soon], &now());
+
+        assert_eq!(
+            ranked
+                .iter()
+                .map(|(candidate, _)| candidate.uid.as_u128())
+                .collect::<Vec<_>>(),
+            vec![30, 31, 32, 33]
+        );
+        assert_eq!(ranked[1].1.days_until, None);
+        assert_eq!(ranked[2].1.days_until, None);
+        assert_eq!(ranked[1].1.score(), ranked[2].1.score());
+    }
+
+    #[test]
+    fn ranking_is_deterministic() {
+        let interests = Interests {
+            topics: vec![Topic::AstronomyAndSpace, Topic::Animals],
+            text: Some("family stargazing night".into()),
+            ..Default::default()
+        };
+
+        let expected = ranked_uids(&interests, fixtures());
+
+        let mut reversed = fixtures();
+        reversed.reverse();
+        assert_eq!(ranked_uids(&interests, reversed), expected);
+
+        // Identical candidates are ordered by uid
+        let twins = vec![
+            RecommendCandidate {
+                uid: Uuid::from_u128(21),
+                ..fixtures()[2].clone()
+            },
+            RecommendCandidate {
+                uid: Uuid::from_u128(20),
+                ..fixtures()[2].clone()
+            },
+        ];
+        assert_eq!(ranked_uids(&interests, twins), vec![20, 21]);
+    }
+
+    #[test]
+    fn pages_of_recommendations() {
+        let items: Vec<u32> = (0..7).collect();
+
+        let page = |pagination: Pagination| paginate(items.clone(), &pagination).unwrap();
+
+        assert_eq!(page(Pagination::Page { index: 0, size: 3 }), vec![0, 1, 2]);
+        assert_eq!(page(Pagination::Page { index: 2, size: 3 }), vec![6]);
+        assert!(page(Pagination::Page { index: 3, size: 3 }).is_empty());
+        assert_eq!(page(Pagination::One), vec![0]);
+        assert_eq!(page(Pagination::All).len(), 7);
+        assert!(paginate(
+            items.clone(),
+            &Pagination::Cursor {
+                cursor: None,
+                size: 3
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,922 @@
+//! Recurrence rules for opportunities, following RFC 5545 section
+//! 3.3.10. The rule text is stored as-is in the database (for
+//! example `FREQ=MONTHLY;BYDAY=2SA`), while the legacy `once`,
+//! `daily` and `weekly` values continue to work as shorthand.
+
+use super::super::Error;
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    Offset, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::str::FromStr;
+
+// Guards against rules which can never produce another occurrence,
+// such as BYMONTH=2;BYMONTHDAY=30. A period is one day, week, month
+// or year depending on the frequency.
+const MAX_PERIODS: u32 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// One entry of a BYDAY list, such as `TU` (every Tuesday) or `-1FR`
+/// (the last Friday of the month or year).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i16>,
+    pub weekday: Weekday,
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_weekday(code: &str) -> Result<Weekday, Error> {
+    Ok(match code {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return Err(Error::Value(format!("RRULE weekday: {code}"))),
+    })
+}
+
+impl FromStr for WeekdayNum {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.len() < 2 {
+            return Err(Error::Value(format!("RRULE BYDAY: {s}")));
+        }
+        let (ordinal, code) = s.split_at(s.len() - 2);
+        let ordinal = if ordinal.is_empty() {
+            None
+        } else {
+            let n: i16 = ordinal
+                .trim_start_matches('+')
+                .parse()
+                .map_err(|_| Error::Value(format!("RRULE BYDAY: {s}")))?;
+            if n == 0 || n.abs() > 53 {
+                return Err(Error::OutOfBounds(format!("RRULE BYDAY: {s}")));
+            }
+            Some(n)
+        };
+        Ok(WeekdayNum {
+            ordinal,
+            weekday: parse_weekday(code)?,
+        })
+    }
+}
+
+impl std::fmt::Display for WeekdayNum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(n) = self.ordinal {
+            write!(f, "{}", n)?;
+        }
+        write!(f, "{}", weekday_code(self.weekday))
+    }
+}
+
+/// The subset of RFC 5545 RRULE parts which our partners need:
+/// FREQ, INTERVAL, COUNT, UNTIL, BYDAY, BYMONTHDAY, BYMONTH and
+/// BYSETPOS. Weeks always start on Monday, so WKST is accepted but
+/// has no effect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<DateTime<FixedOffset>>,
+    pub by_day: Vec<WeekdayNum>,
+    pub by_month_day: Vec<i8>,
+    pub by_month: Vec<u8>,
+    pub by_set_pos: Vec<i16>,
+}
+
+impl RecurrenceRule {
+    pub fn new(frequency: Frequency) -> RecurrenceRule {
+        RecurrenceRule {
+            frequency,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+            by_set_pos: Vec::new(),
+        }
+    }
+
+    /// True if the rule has neither COUNT nor UNTIL, and so recurs forever
+    pub fn is_unbounded(&self) -> bool {
+        self.count.is_none() && self.until.is_none()
+    }
+
+    /// Iterate over the occurrence start times generated by this
+    /// rule, beginning with `dtstart` itself. Local times are
+    /// computed in `timezone` when it names a known zone, so that an
+    /// event at 7pm stays at 7pm across daylight saving transitions.
+    pub fn iter(&self, dtstart: DateTime<FixedOffset>, timezone: Option<&str>) -> RuleIter {
+        let zone = match timezone.and_then(|name| name.parse::<Tz>().ok()) {
+            Some(tz) => Zone::Named(tz),
+            None => Zone::Fixed(*dtstart.offset()),
+        };
+        let local = zone.local_time(&dtstart);
+
+        RuleIter {
+            rule: self.clone(),
+            zone,
+            dtstart,
+            start_date: local.date(),
+            local_time: local.time(),
+            period: 0,
+            buffer: VecDeque::new(),
+            emitted: 0,
+            started: false,
+            done: false,
+        }
+    }
+}
+
+fn parse_until(value: &str) -> Result<DateTime<FixedOffset>, Error> {
+    let err = || Error::Value(format!("RRULE UNTIL: {value}"));
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Ok(dt);
+    }
+
+    let utc = Utc.fix();
+
+    if let Some(stamp) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(stamp, "%Y%m%dT%H%M%S").map_err(|_| err())?;
+        return Ok(utc.from_utc_datetime(&naive));
+    }
+
+    if value.len() == 8 {
+        // A date-only UNTIL includes the whole of that day
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| err())?;
+        let naive = date.and_hms_opt(23, 59, 59).ok_or_else(err)?;
+        return Ok(utc.from_utc_datetime(&naive));
+    }
+
+    // Floating times have no zone of their own, so we treat them as UTC
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").map_err(|_| err())?;
+    Ok(utc.from_utc_datetime(&naive))
+}
+
+fn parse_list<T: FromStr>(name: &str, value: &str) -> Result<Vec<T>, Error> {
+    value
+        .split(',')
+        .map(|part| {
+            part.trim()
+                .trim_start_matches('+')
+                .parse()
+                .map_err(|_| Error::Value(format!("RRULE {name}: {value}")))
+        })
+        .collect()
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let text = match text.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &text[6..],
+            _ => text,
+        };
+
+        let mut frequency = None;
+        let mut rule = RecurrenceRule::new(Frequency::Daily);
+
+        for part in text.split(';').filter(|p| !p.trim().is_empty()) {
+            let Some((name, value)) = part.split_once('=') else {
+                return Err(Error::Value(format!("RRULE part: {part}")));
+            };
+            let name = name.trim().to_ascii_uppercase();
+            let value = value.trim().to_ascii_uppercase();
+
+            match name.as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(Error::Value(format!("RRULE FREQ: {value}"))),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .map_err(|_| Error::Value(format!("RRULE INTERVAL: {value}")))?;
+                    if rule.interval == 0 {
+                        return Err(Error::OutOfBounds("RRULE INTERVAL".into()));
+                    }
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse()
+                            .map_err(|_| Error::Value(format!("RRULE COUNT: {value}")))?,
+                    )
+                }
+                "UNTIL" => rule.until = Some(parse_until(&value)?),
+                "BYDAY" => rule.by_day = parse_list("BYDAY", &value)?,
+                "BYMONTHDAY" => {
+                    rule.by_month_day = parse_list("BYMONTHDAY", &value)?;
+                    if rule.by_month_day.iter().any(|d| *d == 0 || d.abs() > 31) {
+                        return Err(Error::OutOfBounds("RRULE BYMONTHDAY".into()));
+                    }
+                }
+                "BYMONTH" => {
+                    rule.by_month = parse_list("BYMONTH", &value)?;
+                    if rule.by_month.iter().any(|m| *m == 0 || *m > 12) {
+                        return Err(Error::OutOfBounds("RRULE BYMONTH".into()));
+                    }
+                }
+                "BYSETPOS" => {
+                    rule.by_set_pos = parse_list("BYSETPOS", &value)?;
+                    if rule.by_set_pos.iter().any(|p| *p == 0 || p.abs() > 366) {
+                        return Err(Error::OutOfBounds("RRULE BYSETPOS".into()));
+                    }
+                }
+                "WKST" => {
+                    parse_weekday(&value)?;
+                }
+                _ => return Err(Error::Value(format!("RRULE part not supported: {name}"))),
+            }
+        }
+
+        let Some(frequency) = frequency else {
+            return Err(Error::Missing("RRULE FREQ".into()));
+        };
+
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err(Error::Exclusive("RRULE COUNT".into(), "RRULE UNTIL".into()));
+        }
+
+        if frequency != Frequency::Monthly
+            && frequency != Frequency::Yearly
+            && rule.by_day.iter().any(|d| d.ordinal.is_some())
+        {
+            return Err(Error::Value(
+                "RRULE BYDAY ordinals require FREQ=MONTHLY or FREQ=YEARLY".into(),
+            ));
+        }
+
+        if frequency == Frequency::Weekly && !rule.by_month_day.is_empty() {
+            return Err(Error::Value(
+                "RRULE BYMONTHDAY is not allowed with FREQ=WEEKLY".into(),
+            ));
+        }
+
+        rule.frequency = frequency;
+
+        Ok(rule)
+    }
+}
+
+fn join<T: std::fmt::Display>(items: &[T]) -> String {
+    items.iter().fold(String::new(), |mut accum, item| {
+        if !accum.is_empty() {
+            accum.push(',');
+        }
+        let _ = write!(accum, "{}", item);
+        accum
+    })
+}
+
+impl std::fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "FREQ={}", self.frequency.as_str())?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(
+                f,
+                ";UNTIL={}",
+                until.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+            )?;
+        }
+        if !self.by_month.is_empty() {
+            write!(f, ";BYMONTH={}", join(&self.by_month))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(f, ";BYMONTHDAY={}", join(&self.by_month_day))?;
+        }
+        if !self.by_day.is_empty() {
+            write!(f, ";BYDAY={}", join(&self.by_day))?;
+        }
+        if !self.by_set_pos.is_empty() {
+            write!(f, ";BYSETPOS={}", join(&self.by_set_pos))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Zone {
+    Named(Tz),
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    fn local_time(&self, dt: &DateTime<FixedOffset>) -> NaiveDateTime {
+        match self {
+            Zone::Named(tz) => dt.with_timezone(tz).naive_local(),
+            Zone::Fixed(offset) => dt.with_timezone(offset).naive_local(),
+        }
+    }
+
+    fn resolve_local(&self, local: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        let resolve = |result: LocalResult<DateTime<FixedOffset>>| match result {
+            LocalResult::Single(dt) => Some(dt),
+            LocalResult::Ambiguous(earliest, _) => Some(earliest),
+            LocalResult::None => None,
+        };
+
+        let attempt = |local: &NaiveDateTime| match self {
+            Zone::Named(tz) => resolve(
+                tz.from_local_datetime(local)
+                    .map(|dt| dt.with_timezone(&dt.offset().fix())),
+            ),
+            Zone::Fixed(offset) => resolve(offset.from_local_datetime(local)),
+        };
+
+        // Local times which fall into a daylight saving gap are moved
+        // forward by an hour, the same as most calendar programs do
+        attempt(local).or_else(|| attempt(&(*local + Duration::hours(1))))
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+fn month_day(year: i32, month: u32, day: i8) -> Option<NaiveDate> {
+    let len = days_in_month(year, month) as i32;
+    let day = day as i32;
+    let day = if day < 0 { len + day + 1 } else { day };
+    if day < 1 || day > len {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year, month, day as u32)
+}
+
+/// All dates in `first..=last` which match the BYDAY entries,
+/// counting ordinals from the start (or end) of that span.
+fn weekdays_in_span(first: NaiveDate, last: NaiveDate, by_day: &[WeekdayNum]) -> Vec<NaiveDate> {
+    let mut found = Vec::new();
+
+    for entry in by_day {
+        let matching: Vec<NaiveDate> = first
+            .iter_days()
+            .take_while(|d| *d <= last)
+            .filter(|d| d.weekday() == entry.weekday)
+            .collect();
+
+        match entry.ordinal {
+            None => found.extend(matching),
+            Some(n) if n > 0 => found.extend(matching.get(n as usize - 1)),
+            Some(n) => {
+                let back = (-n) as usize;
+                if back <= matching.len() {
+                    found.push(matching[matching.len() - back]);
+                }
+            }
+        }
+    }
+
+    found
+}
+
+fn month_candidates(
+    rule: &RecurrenceRule,
+    year: i32,
+    month: u32,
+    start: NaiveDate,
+) -> Vec<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1);
+    let last = NaiveDate::from_ymd_opt(year, month, days_in_month(year, month));
+    let (Some(first), Some(last)) = (first, last) else {
+        return Vec::new();
+    };
+
+    let by_month_day: Vec<NaiveDate> = rule
+        .by_month_day
+        .iter()
+        .filter_map(|d| month_day(year, month, *d))
+        .collect();
+
+    match (rule.by_month_day.is_empty(), rule.by_day.is_empty()) {
+        (true, true) => month_day(year, month, start.day() as i8)
+            .into_iter()
+            .collect(),
+        (false, true) => by_month_day,
+        (true, false) => weekdays_in_span(first, last, &rule.by_day),
+        (false, false) => weekdays_in_span(first, last, &rule.by_day)
+            .into_iter()
+            .filter(|d| by_month_day.contains(d))
+            .collect(),
+    }
+}
+
+fn add_months(start: NaiveDate, months: u32) -> (i32, u32) {
+    let total = start.year() as i64 * 12 + start.month0() as i64 + months as i64;
+    ((total / 12) as i32, (total % 12) as u32 + 1)
+}
+
+impl RecurrenceRule {
+    /// The dates generated by the rule for one period, before BYSETPOS,
+    /// COUNT and UNTIL are applied
+    fn period_candidates(&self, start: NaiveDate, period: u32) -> Vec<NaiveDate> {
+        let step = period.saturating_mul(self.interval);
+
+        let month_ok =
+            |d: &NaiveDate| self.by_month.is_empty() || self.by_month.contains(&(d.month() as u8));
+
+        let mut dates: Vec<NaiveDate> = match self.frequency {
+            Frequency::Daily => {
+                let Some(day) = start.checked_add_signed(Duration::days(step as i64)) else {
+                    return Vec::new();
+                };
+                let day_ok = self.by_day.is_empty()
+                    || self.by_day.iter().any(|d| d.weekday == day.weekday());
+                let month_day_ok = self.by_month_day.is_empty()
+                    || self
+                        .by_month_day
+                        .iter()
+                        .any(|d| month_day(day.year(), day.month(), *d) == Some(day));
+                if day_ok && month_day_ok {
+                    vec![day]
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Weekly => {
+                let monday = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+                let Some(monday) = monday.checked_add_signed(Duration::weeks(step as i64)) else {
+                    return Vec::new();
+                };
+                if self.by_day.is_empty() {
+                    vec![monday + Duration::days(start.weekday().num_days_from_monday() as i64)]
+                } else {
+                    self.by_day
+                        .iter()
+                        .map(|d| monday + Duration::days(d.weekday.num_days_from_monday() as i64))
+                        .collect()
+                }
+            }
+            Frequency::Monthly => {
+                let (year, month) = add_months(start, step);
+                month_candidates(self, year, month, start)
+            }
+            Frequency::Yearly => {
+                let year = start.year() + step as i32;
+                if self.by_month.is_empty()
+                    && self.by_month_day.is_empty()
+                    && !self.by_day.is_empty()
+                {
+                    match (
+                        NaiveDate::from_ymd_opt(year, 1, 1),
+                        NaiveDate::from_ymd_opt(year, 12, 31),
+                    ) {
+                        (Some(first), Some(last)) => weekdays_in_span(first, last, &self.by_day),
+                        _ => Vec::new(),
+                    }
+                } else {
+                    let months: Vec<u32> = if self.by_month.is_empty() {
+                        vec![start.month()]
+                    } else {
+                        self.by_month.iter().map(|m| *m as u32).collect()
+                    };
+                    months
+                        .into_iter()
+                        .flat_map(|month| month_candidates(self, year, month, start))
+                        .collect()
+                }
+            }
+        };
+
+        dates.retain(month_ok);
+        dates.sort();
+        dates.dedup();
+
+        if self.by_set_pos.is_empty() {
+            return dates;
+        }
+
+        let len = dates.len() as i16;
+        let mut selected: Vec<NaiveDate> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|pos| {
+                let index = if *pos > 0 { pos - 1 } else { len + pos };
+                if index >= 0 && index < len {
+                    Some(dates[index as usize])
+                } else {
+                    None
+                }
+            })
+            .collect();
+        selected.sort();
+        selected.dedup();
+        selected
+    }
+
+    /// The first date of a period, used to decide when iteration
+    /// can stop
+    fn period_floor(&self, start: NaiveDate, period: u32) -> Option<NaiveDate> {
+        let step = period.saturating_mul(self.interval);
+        match self.frequency {
+            Frequency::Daily => start.checked_add_signed(Duration::days(step as i64)),
+            Frequency::Weekly => (start
+                - Duration::days(start.weekday().num_days_from_monday() as i64))
+            .checked_add_signed(Duration::weeks(step as i64)),
+            Frequency::Monthly => {
+                let (year, month) = add_months(start, step);
+                NaiveDate::from_ymd_opt(year, month, 1)
+            }
+            Frequency::Yearly => NaiveDate::from_ymd_opt(start.year() + step as i32, 1, 1),
+        }
+    }
+}
+
+/// Yields occurrence start times for a rule in chronological order.
+/// DTSTART is always the first occurrence, as RFC 5545 requires, and
+/// counts toward COUNT.
+pub struct RuleIter {
+    rule: RecurrenceRule,
+    zone: Zone,
+    dtstart: DateTime<FixedOffset>,
+    start_date: NaiveDate,
+    local_time: NaiveTime,
+    period: u32,
+    buffer: VecDeque<DateTime<FixedOffset>>,
+    emitted: u32,
+    started: bool,
+    done: bool,
+}
+
+impl RuleIter {
+    fn emit(&mut self, dt: DateTime<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+        if let Some(until) = self.rule.until {
+            if dt > until {
+                self.done = true;
+                return None;
+            }
+        }
+        if let Some(count) = self.rule.count {
+            if self.emitted >= count {
+                self.done = true;
+                return None;
+            }
+        }
+        self.emitted += 1;
+        Some(dt)
+    }
+}
+
+impl Iterator for RuleIter {
+    type Item = DateTime<FixedOffset>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        if !self.started {
+            self.started = true;
+            return self.emit(self.dtstart);
+        }
+
+        while self.buffer.is_empty() {
+            if self.period >= MAX_PERIODS {
+                self.done = true;
+                return None;
+            }
+
+            if let Some(until) = self.rule.until {
+                match self.rule.period_floor(self.start_date, self.period) {
+                    Some(floor) if floor <= until.date_naive() + Duration::days(1) => {}
+                    _ => {
+                        self.done = true;
+                        return None;
+                    }
+                }
+            }
+
+            let dates = self.rule.period_candidates(self.start_date, self.period);
+            self.period += 1;
+
+            for date in dates {
+                if let Some(dt) = self.zone.resolve_local(&date.and_time(self.local_time)) {
+                    if dt > self.dtstart {
+                        self.buffer.push_back(dt);
+                    }
+                }
+            }
+        }
+
+        let dt = self.buffer.pop_front()?;
+        self.emit(dt)
+    }
+}
+
+/// The recurrence of an opportunity. `Once`, `Daily` and `Weekly`
+/// are the original values, and remain valid shorthand for the
+/// equivalent rules bounded by `end_recurrence`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Recurrence {
+    #[default]
+    Once,
+    Daily,
+    Weekly,
+    Rule(RecurrenceRule),
+}
+
+impl Recurrence {
+    /// The full rule, if any, with `end_recurrence` applied as an
+    /// upper bound
+    pub fn rule(&self, end_recurrence: Option<DateTime<FixedOffset>>) -> Option<RecurrenceRule> {
+        let mut rule = match self {
+            Recurrence::Once => return None,
+            Recurrence::Daily => RecurrenceRule::new(Frequency::Daily),
+            Recurrence::Weekly => RecurrenceRule::new(Frequency::Weekly),
+            Recurrence::Rule(rule) => rule.clone(),
+        };
+
+        if let Some(end) = end_recurrence {
+            if rule.count.is_none() {
+                rule.until = Some(match rule.until {
+                    Some(until) if until < end => until,
+                    _ => end,
+                });
+            }
+        }
+
+        Some(rule)
+    }
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recurrence::Once => write!(f, "once"),
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly => write!(f, "weekly"),
+            Recurrence::Rule(rule) => write!(f, "{}", rule),
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "" | "once" => Recurrence::Once,
+            "daily" => Recurrence::Daily,
+            "weekly" => Recurrence::Weekly,
+            _ => Recurrence::Rule(s.parse()?),
+        })
+    }
+}
+
+impl Serialize for Recurrence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A single concrete instance of an opportunity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Occurrence {
+    pub start: DateTime<FixedOffset>,
+    pub end: Option<DateTime<FixedOffset>>,
+}
+
+impl Occurrence {
+    /// True if the occurrence is in progress at or after `begin`, and
+    /// starts before `end`
+    pub fn overlaps(&self, begin: &DateTime<FixedOffset>, end: &DateTime<FixedOffset>) -> bool {
+        &self.start < end && self.end.as_ref().unwrap_or(&self.start) >= begin
+    }
+
+    /// The calendar days in `zone` during which the occurrence is in
+    /// progress, limited to the window from `begin` to `end`. An
+    /// occurrence which ends exactly at midnight doesn't spill over
+    /// into the next day.
+    pub fn days(
+        &self,
+        begin: &DateTime<FixedOffset>,
+        end: &DateTime<FixedOffset>,
+        zone: &Tz,
+    ) -> impl Iterator<Item = NaiveDate> {
+        let first = self.start.max(*begin);
+        let last = self
+            .end
+            .map(|dt| dt - Duration::seconds(1))
+            .unwrap_or(self.start)
+            .min(*end - Duration::seconds(1))
+            .max(first);
+        let last_day = last.with_timezone(zone).date_naive();
+
+        first
+            .with_timezone(zone)
+            .date_naive()
+            .iter_days()
+            .take_while(move |day| *day <= last_day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    #[test]
+    fn shorthand_round_trip() {
+        for text in ["once", "daily", "weekly"] {
+            let parsed: Recurrence = serde_json::from_value(serde_json::json!(text)).unwrap();
+            assert_eq!(
+                serde_json::to_value(&parsed).unwrap(),
+                serde_json::json!(text)
+            );
+        }
+    }
+
+    #[test]
+    fn rule_round_trip() {
+        let parsed: Recurrence = "RRULE:freq=monthly;byday=2SA;until=20250101"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            parsed.to_string(),
+            "FREQ=MONTHLY;UNTIL=20250101T235959Z;BYDAY=2SA"
+        );
+        assert!("FREQ=HOURLY".parse::<Recurrence>().is_err());
+        assert!("BYDAY=MO".parse::<Recurrence>().is_err());
+        assert!("FREQ=WEEKLY;BYDAY=2MO".parse::<Recurrence>().is_err());
+    }
+
+    #[test]
+    fn second_saturday() {
+        let rule: RecurrenceRule = "FREQ=MONTHLY;BYDAY=2SA;COUNT=4".parse().unwrap();
+        let found: Vec<_> = rule
+            .iter(dt("2024-01-13T20:00:00-05:00"), Some("America/New_York"))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                dt("2024-01-13T20:00:00-05:00"),
+                dt("2024-02-10T20:00:00-05:00"),
+                dt("2024-03-09T20:00:00-05:00"),
+                dt("2024-04-13T20:00:00-04:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn tuesday_thursday_until() {
+        let rule: RecurrenceRule = "FREQ=WEEKLY;BYDAY=TU,TH;UNTIL=20240918T000000Z"
+            .parse()
+            .unwrap();
+        let found: Vec<_> = rule.iter(dt("2024-09-03T15:00:00+00:00"), None).collect();
+        assert_eq!(
+            found,
+            vec![
+                dt("2024-09-03T15:00:00+00:00"),
+                dt("2024-09-05T15:00:00+00:00"),
+                dt("2024-09-10T15:00:00+00:00"),
+                dt("2024-09-12T15:00:00+00:00"),
+                dt("2024-09-17T15:00:00+00:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn last_weekday_of_month() {
+        let rule: RecurrenceRule = "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=3"
+            .parse()
+            .unwrap();
+        let found: Vec<_> = rule.iter(dt("2024-05-31T12:00:00+00:00"), None).collect();
+        assert_eq!(
+            found,
+            vec![
+                dt("2024-05-31T12:00:00+00:00"),
+                dt("2024-06-28T12:00:00+00:00"),
+                dt("2024-07-31T12:00:00+00:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn impossible_rule_terminates() {
+        let rule: RecurrenceRule = "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30".parse().unwrap();
+        assert_eq!(rule.iter(dt("2024-01-01T00:00:00+00:00"), None).count(), 1);
+    }
+
+    #[test]
+    fn shorthand_uses_end_recurrence() {
+        let rule = Recurrence::Daily
+            .rule(Some(dt("2024-01-03T12:00:00+00:00")))
+            .unwrap();
+        assert_eq!(rule.iter(dt("2024-01-01T10:00:00+00:00"), None).count(), 3);
+        assert!(Recurrence::Once.rule(None).is_none());
+    }
+
+    #[test]
+    fn occurrence_days() {
+        let zone: Tz = "America/Los_Angeles".parse().unwrap();
+        let begin = dt("2026-03-01T00:00:00-08:00");
+        let end = dt("2026-04-01T00:00:00-07:00");
+        let day = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+        // Late evening UTC is still the previous day in Los Angeles
+        let evening = Occurrence {
+            start: dt("2026-03-10T02:00:00Z"),
+            end: Some(dt("2026-03-10T04:00:00Z")),
+        };
+        assert_eq!(
+            evening.days(&begin, &end, &zone).collect::<Vec<_>>(),
+            vec![day("2026-03-09")]
+        );
+
+        // Ending at midnight doesn't count the following day
+        let overnight = Occurrence {
+            start: dt("2026-03-20T18:00:00-07:00"),
+            end: Some(dt("2026-03-22T00:00:00-07:00")),
+        };
+        assert_eq!(
+            overnight.days(&begin, &end, &zone).collect::<Vec<_>>(),
+            vec![day("2026-03-20"), day("2026-03-21")]
+        );
+
+        // Clipped to the window
+        let spanning = Occurrence {
+            start: dt("2026-02-27T09:00:00-08:00"),
+            end: Some(dt("2026-03-02T17:00:00-08:00")),
+        };
+        assert_eq!(
+            spanning.days(&begin, &end, &zone).collect::<Vec<_>>(),
+            vec![day("2026-03-01"), day("2026-03-02")]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::Opportunity;
+use crate::model::Error;
+use crate::{Database, ToFixedOffset};
+
+/// Who stored a revision of an opportunity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "uid", rename_all = "snake_case")]
+pub enum RevisionAuthor {
+    /// A person editing through the site or the management pages
+    Person(Uuid),
+    /// A partner using their API token
+    Partner(Uuid),
+    /// The importer, reading the feed of the identified partner
+    Importer(Uuid),
+    /// Maintenance tasks and anything else not acting for someone
+    System,
+}
+
+impl RevisionAuthor {
+    fn kind(&self) -> &'static str {
+        match self {
+            RevisionAuthor::Person(_) => "person",
+            RevisionAuthor::Partner(_) => "partner",
+            RevisionAuthor::Importer(_) => "importer",
+            RevisionAuthor::System => "system",
+        }
+    }
+
+    pub(super) fn uid(&self) -> Option<Uuid> {
+        match self {
+            RevisionAuthor::Person(uid)
+            | RevisionAuthor::Partner(uid)
+            | RevisionAuthor::Importer(uid) => Some(*uid),
+            RevisionAuthor::System => None,
+        }
+    }
+
+    fn from_parts(kind: &str, uid: Option<Uuid>) -> RevisionAuthor {
+        match (kind, uid) {
+            ("person", Some(uid)) => RevisionAuthor::Person(uid),
+            ("partner", Some(uid)) => RevisionAuthor::Partner(uid),
+            ("importer", Some(uid)) => RevisionAuthor::Importer(uid),
+            _ => RevisionAuthor::System,
+        }
+    }
+}
+
+/// The state of an opportunity as it was stored at some point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: i32,
+    pub created: DateTime<FixedOffset>,
+    pub author: RevisionAuthor,
+    /// How the opportunity was stored, e.g. "api", "importer" or "rollback"
+    pub source: String,
+    pub snapshot: Value,
+}
+
+/// One field which differs between two revisions. A field missing
+/// from one side of the comparison is null on that side.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+impl Revision {
+    /// Lists the top level fields which differ between this revision
+    /// and `later`, in alphabetical order
+    pub fn diff(&self, later: &Revision) -> Vec<FieldChange> {
+        diff_snapshots(&self.snapshot, &later.snapshot)
+    }
+}
+
+pub fn diff_snapshots(before: &Value, after: &Value) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    before
+        .keys()
+        .chain(after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|field| {
+            let mut was = before.get(field).cloned().unwrap_or(Value::Null);
+            let mut now = after.get(field).cloned().unwrap_or(Value::Null);
+
+            // Snapshots recorded before the tags were sorted may have
+            // them in any order
+            if field == "tags" {
+                sort_tags(&mut was);
+                sort_tags(&mut now);
+            }
+
+            if was == now {
+                None
+            } else {
+                Some(FieldChange {
+                    field: field.clone(),
+                    before: was,
+                    after: now,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Puts a JSON array of tags in order. The tags are a set, which
+/// serializes in whatever order the HashSet iterates them.
+fn sort_tags(tags: &mut Value) {
+    if let Value::Array(tags) = tags {
+        tags.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+    }
+}
+
+/// The JSON recorded for a revision of `opp`. The database id is left
+/// out, since it's not part of the opportunity's content, and the tags
+/// are sorted so that storing the same opportunity twice produces the
+/// same snapshot.
+pub(super) fn snapshot(opp: &Opportunity) -> Result<Value, Error> {
+    let mut snapshot = serde_json::to_value(opp)?;
+
+    if let Some(obj) = snapshot.as_object_mut() {
+        obj.remove("id");
+
+        if let Some(tags) = obj.get_mut("tags") {
+            sort_tags(tags);
+        }
+    }
+
+    Ok(snapshot)
+}
+
+impl Opportunity {
+    /// Records the current stored state of the opportunity as a new
+    /// revision, unless it's unchanged since the latest revision
+    pub(super) async fn record_revision(
+        db: &Database,
+        id: i32,
+        author: &RevisionAuthor,
+        source: &str,
+    ) -> Result<(), Error> {
+        let stored = Opportunity::load_by_id(db, id).await?;
+
+        sqlx::query_file_scalar!(
+            "db/opportunity/insert_revision.sql",
+            id,
+            author.kind(),
+            author.uid(),
+            source,
+            snapshot(&stored)?
+        )
+        .fetch_optional(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// All revisions of the opportunity identified by `uid`, most
+    /// recent first
+    pub async fn load_revisions(db: &Database, uid: &Uuid) -> Result<Vec<Revision>, Error> {
+        Ok(sqlx::query_file!("db/opportunity/revisions.sql", uid)
+            .map(|row| Revision {
+                id: row.id,
+                created: row.created.to_fixed_offset(),
+                author: RevisionAuthor::from_parts(&row.author_kind, row.author_uid),
+                source: row.source,
+                snapshot: row.snapshot,
+            })
+            .fetch_all(db)
+            .await?)
+    }
+
+    pub async fn load_revision(db: &Database, uid: &Uuid, id: i32) -> Result<Revision, Error> {
+        sqlx::query_file!("db/opportunity/get_revision.sql", uid, id)
+            .map(|row| Revision {
+                id: row.id,
+                created: row.created.to_fixed_offset(),
+                author: RevisionAuthor::from_parts(&row.author_kind, row.author_uid),
+                source: row.source,
+                snapshot: row.snapshot,
+            })
+            .fetch_optional(db)
+            .await?
+            .ok_or(Error::NoSuch("revision"))
+    }
+
+    /// Restores the opportunity to the state recorded in `revision`.
+    /// The restored state isn't saved until the opportunity is stored,
+    /// which records the rollback as a new revision.
+    pub fn restore(&mut self, revision: &Revision) -> Result<(), Error> {
+        let mut restored: Opportunity = serde_json::from_value(revision.snapshot.clone())?;
+
+        if restored.exterior.uid != self.exterior.uid {
+            return Err(Error::Value("revision".to_string()));
+        }
+
+        restored.id = self.id;
+        *self = restored;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_lists_changed_fields_only() {
+        let changes = diff_snapshots(
+            &json!({"title": "Star party", "min_age": 0, "tags": ["sky"], "cost": "free"}),
+            &json!({"title": "Star Party", "min_age": 0, "tags": ["sky", "night"], "slug": "star-party"}),
+        );
+
+        assert_eq!(
+            changes.iter().map(|c| c.field.as_str()).collect::<Vec<_>>(),
+            vec!["cost", "slug", "tags", "title"]
+        );
+        assert_eq!(changes[0].after, Value::Null);
+        assert_eq!(changes[1].before, Value::Null);
+        assert_eq!(changes[3].before, json!("Star party"));
+    }
+
+    #[test]
+    fn snapshot_ignores_tag_order() {
+        let names: Vec<String> = (0..16).map(|i| format!("tag-{i}")).collect();
+
+        let mut forward = Opportunity::default();
+        forward.exterior.tags = names.iter().cloned().collect();
+        let mut backward = Opportunity::default();
+        backward.exterior.tags = names.iter().rev().cloned().collect();
+
+        assert_eq!(
+            snapshot(&forward).unwrap().to_string(),
+            snapshot(&backward).unwrap().to_string()
+        );
+        assert!(diff_snapshots(
+            &json!({"tags": ["night", "sky"]}),
+            &json!({"tags": ["sky", "night"]})
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn author_round_trips_through_columns() {
+        let uid = Uuid::new_v4();
+
+        for author in [
+            RevisionAuthor::Person(uid),
+            RevisionAuthor::Partner(uid),
+            RevisionAuthor::Importer(uid),
+            RevisionAuthor::System,
+        ] {
+            assert_eq!(
+                RevisionAuthor::from_parts(author.kind(), author.uid()),
+                author
+            );
+        }
+    }
+}
@@ -0,0 +1,449 @@
+//! Registrations for the occurrences of opportunities which have a
+//! capacity. Once an occurrence is full, further registrations go on
+//! a waitlist, and the earliest waitlisted people are confirmed as
+//! seats are freed by cancellations.
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+use uuid::Uuid;
+
+use super::Opportunity;
+use crate::emails::EmailMessage;
+use crate::model::involvement::{Involvement, Mode};
+use crate::model::{Error, Person};
+use crate::{Database, ToFixedOffset};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum RsvpStatus {
+    Confirmed,
+    Waitlisted,
+    Cancelled,
+}
+
+impl RsvpStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RsvpStatus::Confirmed => "confirmed",
+            RsvpStatus::Waitlisted => "waitlisted",
+            RsvpStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn from_str(status: &str) -> RsvpStatus {
+        match status {
+            "confirmed" => RsvpStatus::Confirmed,
+            "waitlisted" => RsvpStatus::Waitlisted,
+            _ => RsvpStatus::Cancelled,
+        }
+    }
+}
+
+/// A person's registration for one occurrence of an opportunity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rsvp {
+    pub id: i32,
+    pub opportunity: Uuid,
+    /// Start time of the occurrence
+    pub occurrence: DateTime<FixedOffset>,
+    pub person: Uuid,
+    pub status: RsvpStatus,
+    /// Place on the waitlist, starting from 1, when waitlisted
+    pub position: Option<i64>,
+    pub created: DateTime<FixedOffset>,
+    pub updated: DateTime<FixedOffset>,
+}
+
+macro_rules! rsvp_from_row {
+    ($row:expr) => {
+        Rsvp {
+            id: $row.id,
+            opportunity: $row.opportunity,
+            occurrence: $row.occurrence.to_fixed_offset(),
+            person: $row.person,
+            status: RsvpStatus::from_str(&$row.status),
+            position: $row.position,
+            created: $row.created.to_fixed_offset(),
+            updated: $row.updated.to_fixed_offset(),
+        }
+    };
+}
+
+/// The outcome of cancelling a registration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RsvpCancellation {
+    pub cancelled: Rsvp,
+    /// Waitlisted registrations which were confirmed into the freed seat
+    pub promoted: Vec<Rsvp>,
+}
+
+/// The outcome of registering for an occurrence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RsvpRegistration {
+    pub rsvp: Rsvp,
+    /// False when the person was already registered or waitlisted, in
+    /// which case nothing was changed and there's nothing to notify
+    /// them about
+    pub changed: bool,
+}
+
+/// How many seats are taken at an upcoming occurrence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RsvpAvailability {
+    pub starts: DateTime<FixedOffset>,
+    pub ends: Option<DateTime<FixedOffset>>,
+    pub capacity: i32,
+    pub confirmed: i64,
+    pub waitlisted: i64,
+    pub available: i64,
+}
+
+/// A registered person, as listed for the partner running the
+/// opportunity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attendee {
+    pub occurrence: DateTime<FixedOffset>,
+    pub status: RsvpStatus,
+    pub position: Option<i64>,
+    pub registered: DateTime<FixedOffset>,
+    pub person: Uuid,
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub email: String,
+    pub phone: Option<String>,
+}
+
+/// Which email to send about a registration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsvpNotice {
+    Confirmed,
+    Waitlisted,
+    Promoted,
+    Cancelled,
+}
+
+impl RsvpNotice {
+    /// The notice for a registration which was just made
+    pub fn for_status(status: RsvpStatus) -> RsvpNotice {
+        match status {
+            RsvpStatus::Confirmed => RsvpNotice::Confirmed,
+            RsvpStatus::Waitlisted => RsvpNotice::Waitlisted,
+            RsvpStatus::Cancelled => RsvpNotice::Cancelled,
+        }
+    }
+
+    fn template(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            RsvpNotice::Confirmed => (
+                "rsvp-confirmed",
+                "You're registered: {title}",
+                r#"
+<p>You're registered for <strong>{title}</strong> on {when}.</p>
+<p>If you can't make it, please <a href="https://sciencenearme.org/{opp_slug}">cancel your registration</a> so that someone else can take your seat.</p>
+"#,
+            ),
+            RsvpNotice::Waitlisted => (
+                "rsvp-waitlisted",
+                "You're on the waitlist: {title}",
+                r#"
+<p><strong>{title}</strong> on {when} is full, so you're number {position} on the waitlist.</p>
+<p>We'll email you if a seat opens up.</p>
+"#,
+            ),
+            RsvpNotice::Promoted => (
+                "rsvp-promoted",
+                "A seat opened up: {title}",
+                r#"
+<p>A seat opened up for <strong>{title}</strong> on {when}, and you're now registered.</p>
+<p>If you can't make it, please <a href="https://sciencenearme.org/{opp_slug}">cancel your registration</a> so that someone else can take your seat.</p>
+"#,
+            ),
+            RsvpNotice::Cancelled => (
+                "rsvp-cancelled",
+                "Registration cancelled: {title}",
+                r#"
+<p>Your registration for <strong>{title}</strong> on {when} has been cancelled.</p>
+"#,
+            ),
+        }
+    }
+}
+
+impl Rsvp {
+    pub async fn load_by_id(db: &Database, id: i32) -> Result<Rsvp, Error> {
+        let row = sqlx::query_file!("db/rsvp/get_by_id.sql", id)
+            .fetch_one(db)
+            .await?;
+
+        Ok(rsvp_from_row!(row))
+    }
+
+    /// Emails the registered person about their registration
+    pub async fn notify(
+        &self,
+        db: &Database,
+        opp: &Opportunity,
+        notice: RsvpNotice,
+    ) -> Result<(), Error> {
+        let person = Person::load_by_uid(db, &self.person).await?;
+
+        let when = match opp
+            .exterior
+            .timezone
+            .as_ref()
+            .and_then(|name| name.parse::<chrono_tz::Tz>().ok())
+        {
+            Some(zone) => self
+                .occurrence
+                .with_timezone(&zone)
+                .format("%A, %B %-d, %Y at %-I:%M %p %Z")
+                .to_string(),
+            None => self
+                .occurrence
+                .format("%A, %B %-d, %Y at %-I:%M %p UTC%:z")
+                .to_string(),
+        };
+
+        let (slug, subject, body) = notice.template();
+
+        let template = EmailMessage::load_or_default(db, slug, subject, body).await;
+
+        let msg = template.materialize(vec![
+            ("title", opp.exterior.title.clone()),
+            ("opp_slug", opp.exterior.slug.clone()),
+            ("when", when),
+            (
+                "position",
+                self.position.map(|p| p.to_string()).unwrap_or_default(),
+            ),
+        ]);
+
+        crate::emails::send_message(person.interior.email, &msg).await;
+
+        Ok(())
+    }
+}
+
+impl Opportunity {
+    /// Registers the person for the upcoming occurrence starting at
+    /// `occurrence`, or puts them on the waitlist if it's full.
+    /// Registering again while already registered or waitlisted
+    /// changes nothing. Opportunities which aren't publicly visible
+    /// are treated as not existing.
+    pub async fn rsvp(
+        &self,
+        db: &Database,
+        person: &Person,
+        occurrence: &DateTime<FixedOffset>,
+    ) -> Result<RsvpRegistration, Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+        let person_id = person.id.ok_or(Error::NoSuch("person"))?;
+
+        if !self.current() || self.interior.trashed_at.is_some() {
+            return Err(Error::NoSuch("opportunity"));
+        }
+
+        if self.exterior.capacity.is_none() {
+            return Err(Error::Value("capacity".to_string()));
+        }
+
+        if !sqlx::query_file_scalar!("db/rsvp/is_upcoming.sql", opp_id, occurrence)
+            .fetch_one(db)
+            .await?
+        {
+            return Err(Error::Value("occurrence".to_string()));
+        }
+
+        if let Some(existing) = sqlx::query_file!("db/rsvp/by_person.sql", opp_id, person_id)
+            .map(|row| rsvp_from_row!(row))
+            .fetch_all(db)
+            .await?
+            .into_iter()
+            .find(|r| r.occurrence == *occurrence && r.status != RsvpStatus::Cancelled)
+        {
+            return Ok(RsvpRegistration {
+                rsvp: existing,
+                changed: false,
+            });
+        }
+
+        Involvement::upgrade(
+            db,
+            &person.exterior.uid,
+            &self.exterior.uid,
+            Mode::Saved,
+            &None,
+        )
+        .await?;
+
+        let mut tx = db.begin().await?;
+
+        // Locking the opportunity serializes registrations, so that
+        // two people can't both take the last seat
+        let capacity = sqlx::query_file_scalar!("db/rsvp/lock_capacity.sql", opp_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .ok_or_else(|| Error::Value("capacity".to_string()))?;
+
+        let confirmed = sqlx::query_file_scalar!("db/rsvp/count_confirmed.sql", opp_id, occurrence)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let status = if confirmed < capacity.into() {
+            RsvpStatus::Confirmed
+        } else {
+            RsvpStatus::Waitlisted
+        };
+
+        let id = sqlx::query_file_scalar!(
+            "db/rsvp/upsert.sql",
+            opp_id,
+            occurrence,
+            person_id,
+            person.exterior.uid,
+            self.exterior.uid,
+            status.as_str(),
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(RsvpRegistration {
+            rsvp: Rsvp::load_by_id(db, id).await?,
+            changed: true,
+        })
+    }
+
+    /// Cancels the person's registration for the occurrence, and
+    /// confirms people from the waitlist into any seats that frees
+    pub async fn cancel_rsvp(
+        &self,
+        db: &Database,
+        person: &Person,
+        occurrence: &DateTime<FixedOffset>,
+    ) -> Result<RsvpCancellation, Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+        let person_id = person.id.ok_or(Error::NoSuch("person"))?;
+
+        let mut tx = db.begin().await?;
+
+        let capacity = sqlx::query_file_scalar!("db/rsvp/lock_capacity.sql", opp_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .unwrap_or(0);
+
+        let cancelled =
+            sqlx::query_file_scalar!("db/rsvp/cancel.sql", opp_id, occurrence, person_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(Error::NoSuch("rsvp"))?;
+
+        let promoted =
+            sqlx::query_file_scalar!("db/rsvp/promote.sql", opp_id, occurrence, capacity)
+                .fetch_all(&mut *tx)
+                .await?;
+
+        tx.commit().await?;
+
+        let mut cancellation = RsvpCancellation {
+            cancelled: Rsvp::load_by_id(db, cancelled).await?,
+            promoted: Vec::with_capacity(promoted.len()),
+        };
+
+        for id in promoted {
+            cancellation.promoted.push(Rsvp::load_by_id(db, id).await?);
+        }
+
+        Ok(cancellation)
+    }
+
+    /// All of the person's registrations for this opportunity,
+    /// including cancelled ones
+    pub async fn rsvps_for_person(
+        &self,
+        db: &Database,
+        person: &Person,
+    ) -> Result<Vec<Rsvp>, Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+        let person_id = person.id.ok_or(Error::NoSuch("person"))?;
+
+        Ok(
+            sqlx::query_file!("db/rsvp/by_person.sql", opp_id, person_id)
+                .map(|row| rsvp_from_row!(row))
+                .fetch_all(db)
+                .await?,
+        )
+    }
+
+    /// Seats taken at each upcoming occurrence. Empty if the
+    /// opportunity doesn't take registrations.
+    pub async fn rsvp_availability(&self, db: &Database) -> Result<Vec<RsvpAvailability>, Error> {
+        let (Some(opp_id), Some(capacity)) = (self.id, self.exterior.capacity) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(sqlx::query_file!("db/rsvp/availability.sql", opp_id)
+            .map(|row| RsvpAvailability {
+                starts: row.starts.to_fixed_offset(),
+                ends: row.ends.map(|dt| dt.to_fixed_offset()),
+                capacity,
+                confirmed: row.confirmed,
+                waitlisted: row.waitlisted,
+                available: (i64::from(capacity) - row.confirmed).max(0),
+            })
+            .fetch_all(db)
+            .await?)
+    }
+
+    /// Everyone registered or waitlisted, for all occurrences or just
+    /// the one starting at `occurrence`
+    pub async fn attendees(
+        &self,
+        db: &Database,
+        occurrence: Option<DateTime<FixedOffset>>,
+    ) -> Result<Vec<Attendee>, Error> {
+        let opp_id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        Ok(
+            sqlx::query_file!("db/rsvp/attendees.sql", opp_id, occurrence)
+                .map(|row| Attendee {
+                    occurrence: row.occurrence.to_fixed_offset(),
+                    status: RsvpStatus::from_str(&row.status),
+                    position: row.position,
+                    registered: row.created.to_fixed_offset(),
+                    person: row.person,
+                    username: row.username,
+                    first_name: row.first_name,
+                    last_name: row.last_name,
+                    email: row.email,
+                    phone: row.phone,
+                })
+                .fetch_all(db)
+                .await?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_round_trip() {
+        for status in [
+            RsvpStatus::Confirmed,
+            RsvpStatus::Waitlisted,
+            RsvpStatus::Cancelled,
+        ] {
+            assert_eq!(RsvpStatus::from_str(status.as_str()), status);
+        }
+
+        assert_eq!(
+            RsvpNotice::for_status(RsvpStatus::Waitlisted),
+            RsvpNotice::Waitlisted
+        );
+    }
+}
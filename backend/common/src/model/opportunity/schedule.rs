@@ -0,0 +1,80 @@
+//! Timed publication. An opportunity with the `Scheduled` review
+//! status becomes `Publish` once its `publish_at` time arrives, and
+//! any public opportunity returns to `Draft` once its `unpublish_at`
+//! time arrives. Visibility already honours both times directly, so
+//! this only brings the stored status into line with them.
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{Opportunity, ReviewStatus, RevisionAuthor};
+use crate::model::Error;
+use crate::Database;
+
+/// A review status change made because a scheduled time passed
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledTransition {
+    pub uid: Uuid,
+    pub slug: String,
+    pub from: ReviewStatus,
+    pub to: ReviewStatus,
+    pub at: DateTime<FixedOffset>,
+}
+
+impl Opportunity {
+    /// Moves every opportunity whose publish or unpublish time is at
+    /// or before `now` into its new review status, returning the
+    /// transitions which were made
+    pub async fn apply_publication_schedule(
+        db: &Database,
+        now: &DateTime<FixedOffset>,
+    ) -> Result<Vec<ScheduledTransition>, Error> {
+        let mut transitions = Vec::new();
+
+        let publish = sqlx::query_file_scalar!("db/opportunity/due_to_publish.sql", now)
+            .fetch_all(db)
+            .await?;
+
+        for id in publish {
+            let mut opp = Opportunity::load_by_id(db, id).await?;
+            if let Some(at) = opp.interior.publish_at {
+                transitions.push(opp.transition(db, ReviewStatus::Publish, at).await?);
+            }
+        }
+
+        let unpublish = sqlx::query_file_scalar!("db/opportunity/due_to_unpublish.sql", now)
+            .fetch_all(db)
+            .await?;
+
+        for id in unpublish {
+            let mut opp = Opportunity::load_by_id(db, id).await?;
+            if let Some(at) = opp.interior.unpublish_at.take() {
+                transitions.push(opp.transition(db, ReviewStatus::Draft, at).await?);
+            }
+        }
+
+        Ok(transitions)
+    }
+
+    async fn transition(
+        &mut self,
+        db: &Database,
+        to: ReviewStatus,
+        at: DateTime<FixedOffset>,
+    ) -> Result<ScheduledTransition, Error> {
+        let from = self.interior.review_status;
+        self.interior.review_status = to;
+        self.store_by(db, &RevisionAuthor::System, "schedule")
+            .await?;
+        self.record_review_transition(db, None, from, "").await?;
+
+        Ok(ScheduledTransition {
+            uid: self.exterior.uid,
+            slug: self.exterior.slug.clone(),
+            from,
+            to,
+            at,
+        })
+    }
+}
@@ -0,0 +1,273 @@
+//! Series of opportunities, such as the events of a science festival,
+//! which name an umbrella opportunity as their parent. The parent's
+//! dates and location are derived from its published children
+//! whenever it or one of them is stored, so that a search which hides
+//! the children still finds the series at the right times and places.
+//! Whatever dates and location were submitted for the parent itself
+//! are replaced. Series are a single level deep: the parent of a
+//! parent is not refreshed.
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde_json::json;
+use std::cmp::Ordering;
+use uuid::Uuid;
+
+use super::{LocationType, Opportunity, OpportunityExterior, Recurrence};
+use crate::model::serde_helpers::serialize_enum;
+use crate::model::Error;
+use crate::{Database, ToFixedOffset};
+
+// Degrees added around the children's locations, so that the area of
+// a series whose events are in a line is not empty
+const ENVELOPE_MARGIN: f64 = 0.01;
+
+fn point(opp: &OpportunityExterior) -> Option<(f64, f64)> {
+    let coords = opp.location_point.as_ref()?.get("coordinates")?;
+    Some((coords.get(0)?.as_f64()?, coords.get(1)?.as_f64()?))
+}
+
+impl OpportunityExterior {
+    /// Spans the series from the first occurrence of any child to the
+    /// end of the last one, and places it at the children's shared
+    /// location, or near all of them when they are in different
+    /// places. When none of the children have dates, or none of them
+    /// have a location, the series has none either.
+    pub fn derive_from_children(
+        &mut self,
+        children: &[OpportunityExterior],
+        now: &DateTime<FixedOffset>,
+    ) {
+        let occurrences: Vec<_> = children
+            .iter()
+            .flat_map(|child| child.materialized_occurrences(now).0)
+            .collect();
+
+        let first = occurrences.iter().map(|occ| occ.start).min();
+        let last = occurrences
+            .iter()
+            .map(|occ| occ.end.unwrap_or(occ.start))
+            .max();
+
+        if let (Some(first), Some(last)) = (first, last) {
+            self.start_datetimes = vec![first];
+            self.has_end = last > first;
+            self.end_datetimes = if self.has_end { vec![last] } else { vec![] };
+        } else {
+            self.start_datetimes.clear();
+            self.has_end = false;
+            self.end_datetimes.clear();
+        }
+
+        self.recurrence = Recurrence::Once;
+        self.end_recurrence = None;
+        self.recurrence_rdates.clear();
+        self.recurrence_exdates.clear();
+
+        self.is_online = children.iter().any(|child| child.is_online);
+
+        let mut points: Vec<(f64, f64)> = children.iter().filter_map(point).collect();
+        points.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        points.dedup();
+
+        match points.as_slice() {
+            [] => {
+                self.location_type = LocationType::Any;
+                self.location_point = None;
+                self.location_polygon = None;
+            }
+            [(lon, lat)] => {
+                self.location_type = LocationType::At;
+                self.location_point = Some(json!({"type": "Point", "coordinates": [lon, lat]}));
+                self.location_polygon = None;
+            }
+            _ => {
+                let west = points.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+                let east = points.iter().map(|p| p.0).fold(f64::NEG_INFINITY, f64::max);
+                let south = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+                let north = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+
+                let (west, east) = (west - ENVELOPE_MARGIN, east + ENVELOPE_MARGIN);
+                let (south, north) = (south - ENVELOPE_MARGIN, north + ENVELOPE_MARGIN);
+
+                self.location_type = LocationType::Near;
+                self.location_point = Some(json!({
+                    "type": "Point",
+                    "coordinates": [(west + east) / 2.0, (south + north) / 2.0]
+                }));
+                self.location_polygon = Some(json!({
+                    "type": "MultiPolygon",
+                    "coordinates": [[[
+                        [west, south],
+                        [east, south],
+                        [east, north],
+                        [west, north],
+                        [west, south]
+                    ]]]
+                }));
+            }
+        }
+    }
+}
+
+impl Opportunity {
+    /// True if any opportunity, published or not, names `uid` as its
+    /// parent
+    pub async fn has_series_children(db: &Database, uid: &Uuid) -> Result<bool, Error> {
+        let rec = sqlx::query_file!("db/opportunity/has_children.sql", uid)
+            .fetch_one(db)
+            .await?;
+
+        Ok(rec.exists.unwrap_or(false))
+    }
+
+    /// Derives the dates and location of the series parent from its
+    /// published children, clearing them if there are none left. Does
+    /// nothing if the parent hasn't been stored yet, since importers
+    /// often bring in the children first.
+    pub async fn refresh_series(db: &Database, parent_uid: &Uuid) -> Result<(), Error> {
+        let ids = sqlx::query_file_scalar!("db/opportunity/series_children.sql", parent_uid)
+            .fetch_all(db)
+            .await?;
+
+        let mut parent = match Opportunity::load_by_uid(db, parent_uid).await {
+            Ok(parent) => parent,
+            Err(Error::SQLx(sqlx::Error::RowNotFound)) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        let Some(id) = parent.id else {
+            return Ok(());
+        };
+
+        let mut children = Vec::with_capacity(ids.len());
+
+        for child_id in ids {
+            children.push(Opportunity::load_by_id(db, child_id).await?.exterior);
+        }
+
+        let now = Utc::now().to_fixed_offset();
+
+        parent.exterior.derive_from_children(&children, &now);
+
+        let (occurrences, recurrence_until) = parent.exterior.materialized_occurrences(&now);
+
+        let mut tx = db.begin().await?;
+
+        sqlx::query_file!(
+            "db/opportunity/update_series.sql",
+            id,
+            &parent.exterior.start_datetimes as &[DateTime<FixedOffset>],
+            parent.exterior.has_end,
+            &parent.exterior.end_datetimes as &[DateTime<FixedOffset>],
+            serialize_enum(&parent.exterior.recurrence),
+            recurrence_until,
+            parent.exterior.is_online,
+            serialize_enum(&parent.exterior.location_type),
+            parent.exterior.location_point,
+            parent.exterior.location_polygon,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Opportunity::replace_occurrences(&mut tx, id, &occurrences).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn child(start: &str, end: &str, lon: f64, lat: f64) -> OpportunityExterior {
+        OpportunityExterior {
+            start_datetimes: vec![DateTime::parse_from_rfc3339(start).unwrap()],
+            end_datetimes: vec![DateTime::parse_from_rfc3339(end).unwrap()],
+            has_end: true,
+            location_type: LocationType::At,
+            location_point: Some(json!({"type": "Point", "coordinates": [lon, lat]})),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parent_spans_its_children() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap();
+
+        let children = [
+            child(
+                "2026-03-14T10:00:00-04:00",
+                "2026-03-14T12:00:00-04:00",
+                -84.39,
+                33.75,
+            ),
+            child(
+                "2026-03-07T18:00:00-05:00",
+                "2026-03-07T21:00:00-05:00",
+                -84.32,
+                33.79,
+            ),
+            child(
+                "2026-03-21T09:00:00-04:00",
+                "2026-03-21T17:00:00-04:00",
+                -84.39,
+                33.75,
+            ),
+        ];
+
+        let mut parent = OpportunityExterior::default();
+        parent.derive_from_children(&children, &now);
+
+        assert_eq!(parent.start_datetimes, vec![children[1].start_datetimes[0]]);
+        assert_eq!(parent.end_datetimes, vec![children[2].end_datetimes[0]]);
+        assert!(parent.has_end);
+
+        assert!(matches!(parent.location_type, LocationType::Near));
+        let ring = &parent.location_polygon.unwrap()["coordinates"][0][0];
+        assert_eq!(
+            ring[0],
+            json!([-84.39 - ENVELOPE_MARGIN, 33.75 - ENVELOPE_MARGIN])
+        );
+        assert_eq!(
+            ring[2],
+            json!([-84.32 + ENVELOPE_MARGIN, 33.79 + ENVELOPE_MARGIN])
+        );
+
+        let mut parent = OpportunityExterior::default();
+        parent.derive_from_children(&[children[0].clone(), children[2].clone()], &now);
+
+        assert!(matches!(parent.location_type, LocationType::At));
+        assert_eq!(parent.location_point, children[0].location_point);
+        assert!(parent.location_polygon.is_none());
+    }
+
+    #[test]
+    fn parent_without_children_is_cleared() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap();
+
+        let mut parent = OpportunityExterior::default();
+        parent.derive_from_children(
+            &[child(
+                "2026-03-14T10:00:00-04:00",
+                "2026-03-14T12:00:00-04:00",
+                -84.39,
+                33.75,
+            )],
+            &now,
+        );
+        assert_eq!(parent.start_datetimes.len(), 1);
+        assert!(parent.location_point.is_some());
+
+        parent.derive_from_children(&[], &now);
+
+        assert!(parent.start_datetimes.is_empty());
+        assert!(parent.end_datetimes.is_empty());
+        assert!(!parent.has_end);
+        assert!(matches!(parent.location_type, LocationType::Any));
+        assert!(parent.location_point.is_none());
+        assert!(parent.location_polygon.is_none());
+        assert!(!parent.is_online);
+    }
+}
@@ -0,0 +1,190 @@
+//! Map tiles of the opportunities matching a query, in the usual
+//! z/x/y web mercator tiling scheme. Opportunities which are close
+//! together at the tile's zoom level are gathered into clusters, each
+//! carrying a count of its members.
+
+use serde_json::Value;
+use sqlx::prelude::*;
+
+use super::{
+    build_matching_query, Opportunity, OpportunityQuery, OpportunityQueryOrdering, ParamValue,
+};
+use crate::model::{Error, Pagination};
+use crate::Database;
+
+/// Deepest zoom level we're willing to render
+pub const MAX_TILE_ZOOM: u8 = 22;
+
+/// Size of a vector tile's coordinate space
+pub const TILE_EXTENT: i32 = 4096;
+
+/// Distance beyond the edges of the tile, in tile coordinates, that
+/// geometry is kept so that markers aren't clipped at the edges
+pub const TILE_BUFFER: i32 = 64;
+
+/// Each tile is divided into a grid of this many cells on each side,
+/// and the opportunities within a cell are gathered into one cluster.
+/// Since the cells line up with tile edges, a cluster never straddles
+/// two tiles.
+pub const CLUSTER_GRID: i32 = 16;
+
+/// Name of the layer holding clusters in vector tiles
+pub const TILE_LAYER: &str = "opportunities";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileCoordinates {
+    pub z: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileCoordinates {
+    /// Checks that the coordinates identify an actual tile
+    pub fn new(z: u8, x: u32, y: u32) -> Result<TileCoordinates, Error> {
+        if z > MAX_TILE_ZOOM {
+            return Err(Error::OutOfBounds("z".to_string()));
+        }
+
+        let span = 1u32 << z;
+
+        if x >= span {
+            return Err(Error::OutOfBounds("x".to_string()));
+        }
+
+        if y >= span {
+            return Err(Error::OutOfBounds("y".to_string()));
+        }
+
+        Ok(TileCoordinates { z, x, y })
+    }
+}
+
+// Builds the query for the clusters within the tile, as a series of
+// common table expressions ending with `clusters`, which has one row
+// per cluster with columns geom (in EPSG:3857), count, and the uid,
+// slug and title of the opportunity if it's alone in its cluster
+fn build_cluster_query(
+    query: &OpportunityQuery,
+    tile: &TileCoordinates,
+) -> Result<(String, Vec<ParamValue>), Error> {
+    let (inner_string, mut params) = build_matching_query(
+        &["primary_table.id"],
+        query,
+        OpportunityQueryOrdering::Any,
+        &Pagination::All,
+    )?;
+
+    let z_param = ParamValue::RawInt(tile.z.into()).append(&mut params);
+    let x_param = ParamValue::RawInt(tile.x as i32).append(&mut params);
+    let y_param = ParamValue::RawInt(tile.y as i32).append(&mut params);
+
+    let query_string = format!(
+        r#"WITH bounds AS (
+             SELECT ST_TileEnvelope(${z_param}, ${x_param}, ${y_param}) AS geom
+           ),
+           located AS (
+             SELECT o.uid, o.slug, o.title,
+                    ST_Transform(coalesce(o.location_point::geometry, ST_PointOnSurface(o.location_polygon::geometry)), 3857) AS geom
+             FROM c_opportunity o
+             WHERE o.id IN ({inner})
+             AND (o.location_point IS NOT NULL OR o.location_polygon IS NOT NULL)
+           ),
+           visible AS (
+             SELECT located.*,
+                    floor(ST_X(located.geom) / ((ST_XMax(bounds.geom) - ST_XMin(bounds.geom)) / {CLUSTER_GRID})) AS cell_x,
+                    floor(ST_Y(located.geom) / ((ST_YMax(bounds.geom) - ST_YMin(bounds.geom)) / {CLUSTER_GRID})) AS cell_y
+             FROM located, bounds
+             WHERE located.geom && bounds.geom
+           ),
+           clusters AS (
+             SELECT ST_Centroid(ST_Collect(geom)) AS geom,
+                    count(*)::integer AS count,
+                    CASE WHEN count(*) = 1 THEN (array_agg(uid))[1]::text END AS uid,
+                    CASE WHEN count(*) = 1 THEN (array_agg(slug))[1] END AS slug,
+                    CASE WHEN count(*) = 1 THEN (array_agg(title))[1] END AS title
+             FROM visible
+             GROUP BY cell_x, cell_y
+           )"#,
+        inner = inner_string.trim_end_matches(';'),
+    );
+
+    Ok((query_string, params))
+}
+
+impl Opportunity {
+    /// Renders the clustered opportunities matching the query within
+    /// the tile as a Mapbox Vector Tile
+    pub async fn load_tile_mvt(
+        db: &Database,
+        query: &OpportunityQuery,
+        tile: &TileCoordinates,
+    ) -> Result<Vec<u8>, Error> {
+        let (clusters, params) = build_cluster_query(query, tile)?;
+
+        let query_string = format!(
+            r#"{clusters}
+               SELECT ST_AsMVT(mvt, '{TILE_LAYER}', {TILE_EXTENT}, 'geom') AS tile
+               FROM (
+                 SELECT ST_AsMVTGeom(clusters.geom, bounds.geom, {TILE_EXTENT}, {TILE_BUFFER}, true) AS geom,
+                        clusters.count, clusters.uid, clusters.slug, clusters.title
+                 FROM clusters, bounds
+               ) mvt"#
+        );
+
+        let query_obj = ParamValue::add_all_to_query(params, sqlx::query(&query_string))?;
+
+        let tile: Option<Vec<u8>> = query_obj.map(|rec| rec.get("tile")).fetch_one(db).await?;
+
+        Ok(tile.unwrap_or_default())
+    }
+
+    /// Returns the clustered opportunities matching the query within
+    /// the tile as a GeoJSON FeatureCollection, with the count of
+    /// each cluster among the properties of its feature
+    pub async fn load_tile_geojson(
+        db: &Database,
+        query: &OpportunityQuery,
+        tile: &TileCoordinates,
+    ) -> Result<Value, Error> {
+        let (clusters, params) = build_cluster_query(query, tile)?;
+
+        let query_string = format!(
+            r#"{clusters}
+               SELECT json_build_object(
+                 'type', 'FeatureCollection',
+                 'features', coalesce(json_agg(json_build_object(
+                   'type', 'Feature',
+                   'geometry', ST_AsGeoJSON(ST_Transform(clusters.geom, 4326))::json,
+                   'properties', json_build_object(
+                     'count', clusters.count,
+                     'uid', clusters.uid,
+                     'slug', clusters.slug,
+                     'title', clusters.title
+                   )
+                 )), '[]'::json)
+               ) AS collection
+               FROM clusters"#
+        );
+
+        let query_obj = ParamValue::add_all_to_query(params, sqlx::query(&query_string))?;
+
+        Ok(query_obj
+            .map(|rec| rec.get("collection"))
+            .fetch_one(db)
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tile_coordinates_must_be_within_zoom_level() {
+        assert!(TileCoordinates::new(0, 0, 0).is_ok());
+        assert!(TileCoordinates::new(3, 7, 7).is_ok());
+        assert!(TileCoordinates::new(3, 8, 0).is_err());
+        assert!(TileCoordinates::new(3, 0, 8).is_err());
+        assert!(TileCoordinates::new(MAX_TILE_ZOOM + 1, 0, 0).is_err());
+    }
+}
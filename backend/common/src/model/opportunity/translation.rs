@@ -0,0 +1,196 @@
+//! Translations of an opportunity's text into other languages. The
+//! opportunity's own title, short_desc and description are in the
+//! languages it lists in `languages`, and each translation is keyed by
+//! one of the codes in `common::LANGUAGES`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::validation::{IssueCode, ValidationReport};
+use super::{OpportunityExterior, OpportunityReference};
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Translation {
+    pub title: String,
+    pub short_desc: String,
+    pub description: String,
+}
+
+impl Translation {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_empty() && self.short_desc.is_empty() && self.description.is_empty()
+    }
+}
+
+// True if the language tags are the same, or share a primary subtag,
+// so that a request for 'es-MX' is satisfied by 'es' and vice versa
+fn same_language(a: &str, b: &str) -> bool {
+    let primary = |tag: &str| tag.split('-').next().unwrap_or_default().to_lowercase();
+    a.eq_ignore_ascii_case(b) || primary(a) == primary(b)
+}
+
+/// Chooses which translation to show someone whose `preferences` are
+/// listed most preferred first. Returns None when the original text
+/// is the best choice, either because it's in a preferred language
+/// or because none of the translations are.
+pub fn choose_translation<'t>(
+    original: &[String],
+    translations: &'t BTreeMap<String, Translation>,
+    preferences: &[String],
+) -> Option<(&'t String, &'t Translation)> {
+    for preferred in preferences {
+        if original.iter().any(|lang| same_language(lang, preferred)) {
+            return None;
+        }
+
+        let exact = translations
+            .iter()
+            .find(|(lang, _)| lang.eq_ignore_ascii_case(preferred));
+
+        if let Some(found) = exact.or_else(|| {
+            translations
+                .iter()
+                .find(|(lang, _)| same_language(lang, preferred))
+        }) {
+            return Some(found);
+        }
+    }
+
+    None
+}
+
+// Translated fields which were left empty keep the original text
+fn apply(translation: &Translation, title: &mut String, short_desc: &mut String) {
+    if !translation.title.is_empty() {
+        *title = translation.title.clone();
+    }
+
+    if !translation.short_desc.is_empty() {
+        *short_desc = translation.short_desc.clone();
+    }
+}
+
+impl OpportunityExterior {
+    /// Replaces the text fields with the translation best matching
+    /// `preferences`, if there is one, and returns its language
+    pub fn localize(&mut self, preferences: &[String]) -> Option<String> {
+        let (language, translation) =
+            choose_translation(&self.languages, &self.translations, preferences)?;
+        let (language, translation) = (language.clone(), translation.clone());
+
+        apply(&translation, &mut self.title, &mut self.short_desc);
+
+        if !translation.description.is_empty() {
+            self.description = translation.description;
+        }
+
+        Some(language)
+    }
+
+    /// Checks that translations are keyed by known language codes,
+    /// normalizing the case of the codes, and sanitizes their text.
+    /// Empty translations are dropped.
+    pub(super) fn validate_translations(&mut self, report: &mut ValidationReport) {
+        let mut validated = BTreeMap::new();
+
+        for (language, mut translation) in std::mem::take(&mut self.translations) {
+            let Some(code) = crate::LANGUAGES
+                .keys()
+                .find(|code| code.eq_ignore_ascii_case(&language))
+            else {
+                report.error(
+                    format!("$.translations.{}", language),
+                    IssueCode::UnknownLanguage,
+                    format!("{} is not a supported language code", language),
+                );
+                continue;
+            };
+
+            translation.title = translation.title.trim().to_string();
+            translation.short_desc = ammonia::clean(&translation.short_desc);
+            translation.description = ammonia::clean(&translation.description);
+
+            if !translation.is_empty() {
+                validated.insert(code.clone(), translation);
+            }
+        }
+
+        self.translations = validated;
+    }
+}
+
+impl OpportunityReference {
+    /// Replaces the title and short description with the translation
+    /// best matching `preferences`, if there is one
+    pub fn localize(
+        &mut self,
+        original: &[String],
+        translations: &BTreeMap<String, Translation>,
+        preferences: &[String],
+    ) {
+        if let Some((_, translation)) = choose_translation(original, translations, preferences) {
+            apply(translation, &mut self.title, &mut self.short_desc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translations() -> BTreeMap<String, Translation> {
+        [
+            ("es", "Noche de estrellas"),
+            ("zh-CN", "星空之夜"),
+            ("zh-TW", "星空之夜 (TW)"),
+        ]
+        .iter()
+        .map(|(lang, title)| {
+            (
+                lang.to_string(),
+                Translation {
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect()
+    }
+
+    #[test]
+    fn choose_translation_falls_back_by_preference() {
+        let original = vec!["en-US".to_string()];
+        let translations = translations();
+        let choose = |prefs: &[&str]| {
+            let prefs: Vec<String> = prefs.iter().map(|p| p.to_string()).collect();
+            choose_translation(&original, &translations, &prefs).map(|(lang, _)| lang.as_str())
+        };
+
+        assert_eq!(choose(&["es-MX"]), Some("es"));
+        assert_eq!(choose(&["zh-TW", "es"]), Some("zh-TW"));
+        assert_eq!(choose(&["fr", "es"]), Some("es"));
+        assert_eq!(choose(&["en-GB", "es"]), None);
+        assert_eq!(choose(&["fr"]), None);
+        assert_eq!(choose(&[]), None);
+    }
+
+    #[test]
+    fn localize_keeps_untranslated_fields() {
+        let mut exterior = OpportunityExterior {
+            title: "Star Night".to_string(),
+            short_desc: "Look up".to_string(),
+            languages: vec!["en-US".to_string()],
+            translations: translations(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            exterior.localize(&["es".to_string()]),
+            Some("es".to_string())
+        );
+        assert_eq!(exterior.title, "Noche de estrellas");
+        assert_eq!(exterior.short_desc, "Look up");
+    }
+}
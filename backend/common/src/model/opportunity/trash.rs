@@ -0,0 +1,124 @@
+//! Deleting an opportunity moves it to the trash rather than removing
+//! it, so that a mistake can be undone. Trashed opportunities are left
+//! out of every search and are not current, and once they have been
+//! in the trash for longer than the retention period they are purged
+//! along with everything that refers to them.
+
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use once_cell::sync::Lazy;
+
+use super::{Opportunity, RevisionAuthor};
+use crate::model::Error;
+use crate::{Database, ToFixedOffset};
+
+/// Days an opportunity stays in the trash before it is purged, unless
+/// the TRASH_RETENTION_DAYS environment variable says otherwise
+pub const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+
+pub static TRASH_RETENTION: Lazy<Duration> = Lazy::new(|| {
+    Duration::days(
+        std::env::var("TRASH_RETENTION_DAYS")
+            .ok()
+            .and_then(|days| days.parse().ok())
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS),
+    )
+});
+
+impl Opportunity {
+    /// Moves the opportunity to the trash. Trashing an opportunity
+    /// which is already in the trash does nothing.
+    pub async fn trash(&mut self, db: &Database, author: &RevisionAuthor) -> Result<(), Error> {
+        let id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        if self.interior.trashed_at.is_some() {
+            return Ok(());
+        }
+
+        let trashed_at = sqlx::query_file_scalar!("db/opportunity/trash.sql", id)
+            .fetch_one(db)
+            .await?;
+
+        self.interior.trashed_at = trashed_at.map(|dt| dt.to_fixed_offset());
+
+        self.after_trash_change(db, id, author, "trash").await
+    }
+
+    /// Takes the opportunity back out of the trash
+    pub async fn restore_from_trash(
+        &mut self,
+        db: &Database,
+        author: &RevisionAuthor,
+    ) -> Result<(), Error> {
+        let id = self.id.ok_or(Error::NoSuch("opportunity"))?;
+
+        if self.interior.trashed_at.is_none() {
+            return Ok(());
+        }
+
+        sqlx::query_file!("db/opportunity/untrash.sql", id)
+            .execute(db)
+            .await?;
+
+        self.interior.trashed_at = None;
+
+        self.after_trash_change(db, id, author, "restore").await
+    }
+
+    async fn after_trash_change(
+        &self,
+        db: &Database,
+        id: i32,
+        author: &RevisionAuthor,
+        source: &str,
+    ) -> Result<(), Error> {
+        Opportunity::record_revision(db, id, author, source).await?;
+
+        // A trashed child no longer counts towards its series
+        if let Some(parent) = self.exterior.parent {
+            Opportunity::refresh_series(db, &parent).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the opportunities which were trashed before `cutoff`,
+    /// along with their involvements, likes, reviews, search entries
+    /// and overlays. An opportunity which can't be deleted is logged
+    /// and skipped, so that it doesn't hold up the rest. Returns the
+    /// number of opportunities purged, which is less than the number
+    /// trashed before `cutoff` if any were skipped.
+    pub async fn purge_trashed_before(
+        db: &Database,
+        cutoff: &DateTime<FixedOffset>,
+    ) -> Result<u32, Error> {
+        let expired = sqlx::query_file!("db/opportunity/trashed_before.sql", cutoff)
+            .fetch_all(db)
+            .await?;
+
+        let mut count = 0;
+
+        for opp in expired {
+            match sqlx::query_file!("db/opportunity/purge.sql", opp.id, opp.uid)
+                .execute(db)
+                .await
+            {
+                Ok(_) => count += 1,
+                Err(err) => crate::log(
+                    None,
+                    "opportunity-purge-error",
+                    &serde_json::json!({ "opportunity": opp.uid, "error": err.to_string() }),
+                ),
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Purges the opportunities which have been in the trash for
+    /// longer than the retention period. This should be run
+    /// periodically.
+    pub async fn purge_trash(db: &Database) -> Result<u32, Error> {
+        let cutoff = (Utc::now() - *TRASH_RETENTION).to_fixed_offset();
+        Opportunity::purge_trashed_before(db, &cutoff).await
+    }
+}
@@ -0,0 +1,157 @@
+//! Everything wrong with an opportunity, gathered in one pass so that
+//! partners can fix all of it at once. Errors prevent the opportunity
+//! from being stored, while warnings point out things which make it
+//! less useful to the people looking for it.
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use crate::model::partner::{LoggedError, LoggedErrorLevel};
+use crate::model::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// What kind of problem was found, for programs reading the report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueCode {
+    /// The document could not be read as an opportunity at all
+    Malformed,
+    /// A required value is missing or empty
+    Required,
+    /// A value is outside the range it is allowed to take
+    OutOfRange,
+    /// A translation is keyed by a language we don't know
+    UnknownLanguage,
+    /// The value is valid in general, but not accepted here
+    NotAllowed,
+    /// There is no image to show with the opportunity
+    MissingImage,
+    /// Every listed end time has already passed
+    Ended,
+    /// An end time comes before the start time it's paired with
+    EndsBeforeStart,
+}
+
+/// One problem found during validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// JSON path of the offending value in the opportunity document,
+    /// e.g. `$.title` or `$.end_datetimes[1]`
+    pub path: String,
+    pub code: IssueCode,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    /// The field named by the path, without the leading `$.`, any
+    /// index, or any nested key
+    fn field(&self) -> &str {
+        self.path
+            .trim_start_matches("$.")
+            .split(['.', '['])
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// An entry for the partner's error log describing this issue
+    pub fn logged_error(&self, title: &str) -> LoggedError {
+        LoggedError::new(
+            match self.severity {
+                Severity::Warning => LoggedErrorLevel::Warning,
+                Severity::Error => LoggedErrorLevel::Error,
+            },
+            format!("{} ({:?}): {}", self.path, self.code, self.message),
+        )
+        .set_title(title)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    fn push(&mut self, severity: Severity, path: String, code: IssueCode, message: String) {
+        self.issues.push(ValidationIssue {
+            path,
+            code,
+            severity,
+            message,
+        });
+    }
+
+    pub fn error(&mut self, path: impl Into<String>, code: IssueCode, message: impl Into<String>) {
+        self.push(Severity::Error, path.into(), code, message.into());
+    }
+
+    pub fn warning(
+        &mut self,
+        path: impl Into<String>,
+        code: IssueCode,
+        message: impl Into<String>,
+    ) {
+        self.push(Severity::Warning, path.into(), code, message.into());
+    }
+
+    /// True if nothing prevents the opportunity from being stored
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    /// Fails with the first error in the report, in the form
+    /// `Opportunity::validate` has always reported it
+    pub fn into_result(self) -> Result<(), Error> {
+        match self.errors().next() {
+            None => Ok(()),
+            Some(issue) => Err(match issue.code {
+                IssueCode::Required => Error::Missing(issue.field().to_string()),
+                IssueCode::OutOfRange => Error::OutOfBounds(issue.field().to_string()),
+                IssueCode::UnknownLanguage => Error::Value(format!(
+                    "translations: {}",
+                    issue.path.trim_start_matches("$.translations.")
+                )),
+                _ => Error::Value(issue.message.clone()),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_error_becomes_the_legacy_error() {
+        let mut report = ValidationReport::default();
+        report.warning("$.image_url", IssueCode::MissingImage, "no image");
+        assert!(report.is_valid());
+
+        report.error("$.title", IssueCode::Required, "title is required");
+        report.error("$.capacity", IssueCode::OutOfRange, "negative capacity");
+        assert!(!report.is_valid());
+        assert_eq!(report.errors().count(), 2);
+        assert_eq!(report.warnings().count(), 1);
+
+        assert!(matches!(report.into_result(), Err(Error::Missing(field)) if field == "title"));
+    }
+}
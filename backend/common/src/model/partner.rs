@@ -565,15 +565,15 @@ INSERT
                 row.partner_created, row.partner_updated, row.partner_opp_url,
                 row.organization_name, row.organization_type, row.organization_website, row.organization_logo_url,
                 row.entity_type, row.opp_venue, row.opp_descriptor, row.min_age, row.max_age, row.pes_domain,
-                row.tags, row.opp_topics, row.ticket_required,
+                row.tags, row.opp_topics, row.ticket_required, row.capacity,
                 row.title, row.description, row.short_desc, row.image_url, row.image_credit,
-                row.start_datetimes, row.has_end, row.end_datetimes, row.recurrence, row.end_recurrence, row.timezone,
-                row.attraction_hours, row.cost, row.languages, row.is_online,
+                row.start_datetimes, row.has_end, row.end_datetimes, row.recurrence, row.end_recurrence, row.recurrence_rdates, row.recurrence_exdates, row.timezone,
+                row.attraction_hours, row.cost, row.languages, row.translations, row.accessibility, row.is_online,
                 row.location_type, row.location_name, row.location_point_geojson, row.location_polygon_geojson,
                 row.address_street, row.address_city, row.address_state, row.address_country, row.address_zip,
                 row.opp_hashtags, row.opp_social_handles, row.opp_partner,
-                row.accepted, row.withdrawn, row.submitted_by, row.review_status,
-                row.contact_name, row.contact_email, row.contact_phone, row.extra_data,
+                row.accepted, row.withdrawn, row.submitted_by, row.review_status, row.publish_at, row.unpublish_at,
+                row.contact_name, row.contact_email, row.contact_phone, row.extra_data, row.parent_uid, row.trashed_at,
             )
         })
         .fetch_all(db)
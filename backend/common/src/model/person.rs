@@ -806,6 +806,7 @@ WHERE id = $1
             Pagination::Page { index, size } => {
                 (Some(size as i64), Some(index as i64 * size as i64))
             }
+            Pagination::Cursor { size, .. } => (Some(size as i64), None),
         };
 
         sqlx::query_file!("db/person/catalog.sql", limit, offset)
@@ -862,6 +863,7 @@ WHERE id = $1
             Pagination::Page { index, size } => {
                 (Some(size as i64), Some(index as i64 * size as i64))
             }
+            Pagination::Cursor { size, .. } => (Some(size as i64), None),
         };
 
         let pattern = format!("%{}%", pattern.as_ref());
@@ -0,0 +1,253 @@
+//! Named geographic areas, such as states, counties and school
+//! districts, which searches and the regional overview can be
+//! narrowed to. Regions may be nested within a parent region.
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::geojson::{FeatureCollection, Geometry};
+use super::Error;
+use crate::{Database, ToFixedOffset};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Region {
+    pub id: Option<i32>,
+    pub name: String,
+    pub parent: Option<i32>,
+    pub geometry: Geometry,
+}
+
+/// A region without its geometry, which may be large
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionReference {
+    pub id: i32,
+    pub name: String,
+    pub parent: Option<i32>,
+    pub parent_name: Option<String>,
+    pub updated: DateTime<FixedOffset>,
+}
+
+/// What happened to the features of an imported collection
+#[derive(Debug, Default, Serialize)]
+pub struct RegionImport {
+    pub created: usize,
+    pub updated: usize,
+    /// Features which had no name or weren't polygons
+    pub skipped: usize,
+}
+
+fn region_geometry(value: Value) -> Result<Geometry, Error> {
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Fills in `{property}` placeholders in the template from the
+/// feature's properties. Returns None if a property is missing or
+/// the result is blank.
+pub fn feature_name(template: &str, properties: &Value) -> Option<String> {
+    let mut name = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = start + rest[start..].find('}')?;
+        name.push_str(&rest[..start]);
+
+        match properties.get(&rest[start + 1..end])? {
+            Value::String(s) => name.push_str(s.trim()),
+            Value::Null => return None,
+            other => name.push_str(&other.to_string()),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    name.push_str(rest);
+
+    let name = name.trim();
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+impl Region {
+    pub async fn list(db: &Database) -> Result<Vec<RegionReference>, Error> {
+        Ok(sqlx::query_file!("db/region/list.sql")
+            .map(|rec| RegionReference {
+                id: rec.id,
+                name: rec.name,
+                parent: rec.parent_id,
+                parent_name: rec.parent_name,
+                updated: rec.updated.to_fixed_offset(),
+            })
+            .fetch_all(db)
+            .await?)
+    }
+
+    pub async fn load_by_id(db: &Database, id: i32) -> Result<Region, Error> {
+        let rec = sqlx::query_file!("db/region/get_by_id.sql", id)
+            .fetch_one(db)
+            .await?;
+
+        Ok(Region {
+            id: Some(rec.id),
+            name: rec.name,
+            parent: rec.parent_id,
+            geometry: region_geometry(rec.geometry)?,
+        })
+    }
+
+    pub async fn load_by_name(db: &Database, name: &str) -> Result<Option<Region>, Error> {
+        let Some(rec) = sqlx::query_file!("db/region/get_by_name.sql", name)
+            .fetch_optional(db)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(Region {
+            id: Some(rec.id),
+            name: rec.name,
+            parent: rec.parent_id,
+            geometry: region_geometry(rec.geometry)?,
+        }))
+    }
+
+    pub async fn validate(&mut self, db: &Database) -> Result<(), Error> {
+        self.name = self.name.trim().to_string();
+
+        if self.name.is_empty() {
+            return Err(Error::Missing("name".to_string()));
+        }
+
+        if !matches!(
+            self.geometry,
+            Geometry::Polygon(_) | Geometry::MultiPolygon(_)
+        ) {
+            return Err(Error::Value("geometry".to_string()));
+        }
+
+        // A region can't be nested within itself, however indirectly
+        if let (Some(id), Some(parent)) = (self.id, self.parent) {
+            let ancestors = sqlx::query_file_scalar!("db/region/ancestors.sql", parent)
+                .fetch_all(db)
+                .await?;
+
+            if ancestors.contains(&id) {
+                return Err(Error::Value("parent".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn store(&mut self, db: &Database) -> Result<(), Error> {
+        self.validate(db).await?;
+
+        let geometry = serde_json::to_string(&self.geometry)?;
+
+        if let Some(id) = self.id {
+            sqlx::query_file!("db/region/update.sql", id, self.name, self.parent, geometry)
+                .execute(db)
+                .await?;
+        } else {
+            let rec = sqlx::query_file!("db/region/insert.sql", self.name, self.parent, geometry)
+                .fetch_one(db)
+                .await?;
+
+            self.id = Some(rec.id);
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete(db: &Database, id: i32) -> Result<(), Error> {
+        sqlx::query_file!("db/region/delete.sql", id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stores each feature of the collection as a region within
+    /// `parent`, named by filling in `name_template` from the
+    /// feature's properties. Features matching the name of an
+    /// existing region replace it.
+    pub async fn import(
+        db: &Database,
+        features: FeatureCollection,
+        name_template: &str,
+        parent: Option<i32>,
+    ) -> Result<RegionImport, Error> {
+        let mut import = RegionImport::default();
+
+        for feature in features.features {
+            let properties = feature.properties.unwrap_or_default();
+
+            let (Some(name), Some(geometry)) =
+                (feature_name(name_template, &properties), feature.geometry)
+            else {
+                import.skipped += 1;
+                continue;
+            };
+
+            if !matches!(geometry, Geometry::Polygon(_) | Geometry::MultiPolygon(_)) {
+                import.skipped += 1;
+                continue;
+            }
+
+            let mut region = match Region::load_by_name(db, &name).await? {
+                Some(existing) => {
+                    import.updated += 1;
+                    Region {
+                        parent,
+                        geometry,
+                        ..existing
+                    }
+                }
+                None => {
+                    import.created += 1;
+                    Region {
+                        id: None,
+                        name,
+                        parent,
+                        geometry,
+                    }
+                }
+            };
+
+            region.store(db).await?;
+        }
+
+        Ok(import)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_name_fills_placeholders() {
+        let properties = serde_json::json!({
+            "NAME": " Allegheny ",
+            "STATE": "PA",
+            "GEOID": 42003,
+            "EMPTY": null,
+        });
+
+        assert_eq!(
+            feature_name("{NAME} County, {STATE}", &properties),
+            Some("Allegheny County, PA".to_string())
+        );
+        assert_eq!(
+            feature_name("{GEOID}", &properties),
+            Some("42003".to_string())
+        );
+        assert_eq!(feature_name("{EMPTY}", &properties), None);
+        assert_eq!(feature_name("{MISSING}", &properties), None);
+        assert_eq!(feature_name("{NAME", &properties), None);
+    }
+}
@@ -0,0 +1,657 @@
+//! JSON Schemas, in the dialect used by OpenAPI 3.0, for the types
+//! exchanged through the public API. The schemas are assembled from
+//! the Rust types themselves, with enumerated values listed by
+//! serializing each variant, so that the API documentation can't
+//! drift away from what the server actually sends and accepts.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use strum::IntoEnumIterator;
+use uuid::Uuid;
+
+use super::opportunity::{
+    Accessibility, Attendee, Cost, Descriptor, Domain, EntityType, FieldChange, IssueCode,
+    LocationType, Occurrence, OccurrenceWindow, OpenDays, OpenException, OpenHours, Opportunity,
+    OpportunityExterior, OpportunityInterior, OpportunityOccurrence, OpportunityReference,
+    OrganizationType, Recurrence, ReviewStatus, Revision, RevisionAuthor, RsvpStatus, Severity,
+    Topic, Translation, ValidationIssue, VenueType,
+};
+use super::participation::{Mode, Participation, ParticipationExterior, ParticipationInterior};
+
+pub trait JsonSchema {
+    fn schema() -> Value;
+}
+
+/// A string schema listing the serialized form of every variant
+pub fn string_enum<T: IntoEnumIterator + Serialize>() -> Value {
+    let mut values: Vec<Value> = Vec::new();
+
+    for variant in T::iter() {
+        let value = serde_json::to_value(variant)
+            .expect("Serializing simple enum variants should never fail");
+
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+
+    json!({ "type": "string", "enum": values })
+}
+
+/// Adds a description to a schema
+pub fn describe(mut schema: Value, description: &str) -> Value {
+    if !description.is_empty() {
+        if let Some(obj) = schema.as_object_mut() {
+            obj.insert("description".to_string(), description.into());
+        }
+    }
+
+    schema
+}
+
+/// Builds the schema of a JSON object, one property at a time
+#[derive(Default)]
+pub struct ObjectSchema {
+    properties: Map<String, Value>,
+    required: Vec<String>,
+}
+
+impl ObjectSchema {
+    pub fn new() -> ObjectSchema {
+        ObjectSchema::default()
+    }
+
+    /// Adds a property holding a value of type `T`
+    pub fn field<T: JsonSchema>(self, name: &str, description: &str) -> ObjectSchema {
+        self.field_with(name, T::schema(), description)
+    }
+
+    /// Adds a property with an explicitly given schema, for values
+    /// whose Rust type says less than their documentation should
+    pub fn field_with(mut self, name: &str, schema: Value, description: &str) -> ObjectSchema {
+        self.properties
+            .insert(name.to_string(), describe(schema, description));
+        self
+    }
+
+    /// Sets a keyword such as `readOnly` or `default` on a property
+    /// which has already been added
+    pub fn with(mut self, name: &str, keyword: &str, value: Value) -> ObjectSchema {
+        if let Some(Value::Object(property)) = self.properties.get_mut(name) {
+            property.insert(keyword.to_string(), value);
+        }
+        self
+    }
+
+    pub fn read_only(self, name: &str) -> ObjectSchema {
+        self.with(name, "readOnly", true.into())
+    }
+
+    pub fn required(mut self, names: &[&str]) -> ObjectSchema {
+        self.required
+            .extend(names.iter().map(|name| name.to_string()));
+        self
+    }
+
+    /// Adds the properties of `T`, as `#[serde(flatten)]` does
+    pub fn flatten<T: JsonSchema>(mut self) -> ObjectSchema {
+        let schema = T::schema();
+
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            self.properties.extend(properties.clone());
+        }
+
+        if let Some(Value::Array(required)) = schema.get("required") {
+            self.required.extend(
+                required
+                    .iter()
+                    .filter_map(|name| name.as_str().map(str::to_string)),
+            );
+        }
+
+        self
+    }
+
+    pub fn build(self) -> Value {
+        let mut schema = json!({ "type": "object", "properties": self.properties });
+
+        if !self.required.is_empty() {
+            schema["required"] = self.required.into();
+        }
+
+        schema
+    }
+}
+
+macro_rules! primitive_schema {
+    ($($t:ty => $schema:tt),* $(,)?) => {
+        $(impl JsonSchema for $t {
+            fn schema() -> Value {
+                json!($schema)
+            }
+        })*
+    };
+}
+
+primitive_schema! {
+    String => { "type": "string" },
+    bool => { "type": "boolean" },
+    i16 => { "type": "integer" },
+    i32 => { "type": "integer", "format": "int32" },
+    i64 => { "type": "integer", "format": "int64" },
+    u32 => { "type": "integer", "minimum": 0 },
+    f32 => { "type": "number", "format": "float" },
+    f64 => { "type": "number", "format": "double" },
+    Uuid => { "type": "string", "format": "uuid" },
+    DateTime<FixedOffset> => { "type": "string", "format": "date-time" },
+    NaiveDate => { "type": "string", "format": "date" },
+    Value => {},
+}
+
+macro_rules! string_enum_schema {
+    ($($t:ty),* $(,)?) => {
+        $(impl JsonSchema for $t {
+            fn schema() -> Value {
+                string_enum::<$t>()
+            }
+        })*
+    };
+}
+
+string_enum_schema!(
+    Accessibility,
+    Cost,
+    Descriptor,
+    Domain,
+    IssueCode,
+    LocationType,
+    Mode,
+    OrganizationType,
+    ReviewStatus,
+    RsvpStatus,
+    Severity,
+    Topic,
+    VenueType,
+);
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn schema() -> Value {
+        let mut schema = T::schema();
+        schema["nullable"] = true.into();
+        schema
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema() })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for HashSet<T> {
+    fn schema() -> Value {
+        json!({ "type": "array", "items": T::schema(), "uniqueItems": true })
+    }
+}
+
+impl<K, V: JsonSchema> JsonSchema for HashMap<K, V> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": V::schema() })
+    }
+}
+
+impl<K, V: JsonSchema> JsonSchema for BTreeMap<K, V> {
+    fn schema() -> Value {
+        json!({ "type": "object", "additionalProperties": V::schema() })
+    }
+}
+
+fn geometry(kind: &str, description: &str) -> Value {
+    json!({
+        "type": "object",
+        "nullable": true,
+        "description": format!("{} represented as a [GeoJSON {} geometry](https://tools.ietf.org/html/rfc7946#section-3.1) object", description, kind),
+    })
+}
+
+impl JsonSchema for EntityType {
+    fn schema() -> Value {
+        // Page entities exist too, but are not accepted or returned
+        // by the API
+        json!({ "type": "string", "enum": ["opportunity", "attraction", "unspecified"] })
+    }
+}
+
+impl JsonSchema for Recurrence {
+    fn schema() -> Value {
+        json!({
+            "type": "string",
+            "example": "FREQ=WEEKLY;BYDAY=TU,TH",
+            "description": "Either 'once', 'daily', 'weekly', or an RFC 5545 recurrence rule such as 'FREQ=MONTHLY;BYDAY=1SA'",
+        })
+    }
+}
+
+impl JsonSchema for OpenHours {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field_with(
+                "opens",
+                json!({ "type": "string", "format": "time", "example": "10:30:00-05:00" }),
+                "",
+            )
+            .field_with(
+                "closes",
+                json!({ "type": "string", "format": "time", "example": "15:30:00-05:00" }),
+                "",
+            )
+            .required(&["opens", "closes"])
+            .build()
+    }
+}
+
+impl JsonSchema for OpenDays {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Option<OpenHours>>("monday", "")
+            .field::<Option<OpenHours>>("tuesday", "")
+            .field::<Option<OpenHours>>("wednesday", "")
+            .field::<Option<OpenHours>>("thursday", "")
+            .field::<Option<OpenHours>>("friday", "")
+            .field::<Option<OpenHours>>("saturday", "")
+            .field::<Option<OpenHours>>("sunday", "")
+            .field::<Vec<OpenException>>(
+                "exceptions",
+                "Dates on which the weekly hours do not apply",
+            )
+            .build()
+    }
+}
+
+impl JsonSchema for OpenException {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<NaiveDate>("date", "The date, in the attraction's timezone")
+            .field::<Option<OpenHours>>(
+                "hours",
+                "The hours kept on this date instead of the usual ones, or null if closed all day",
+            )
+            .field::<String>("label", "Why the hours are different, such as a holiday")
+            .required(&["date"])
+            .build()
+    }
+}
+
+impl JsonSchema for Translation {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<String>("title", "The title in the translation's language")
+            .field::<String>(
+                "short_desc",
+                "The short description in the translation's language",
+            )
+            .field::<String>(
+                "description",
+                "The description in the translation's language",
+            )
+            .build()
+    }
+}
+
+impl JsonSchema for OpportunityExterior {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Uuid>("uid", "The UID which uniquely identifies this opportunity. If omitted during opportunity creation, a UUID will be generated based on the partner_name, partner_opp_url, and title fields.")
+            .field::<String>("slug", "The unique name of this opportunity in Science Near Me URLs")
+            .read_only("slug")
+            .field::<String>("partner_name", "The name to use to identify the originating partner for this opportunity. This is not necessarily the name of the partner making the API call; for example, partner A may be serving as a proxy for partner B")
+            .field::<Option<String>>("partner_website", "The URL for the partner's main web site")
+            .field::<Option<String>>("partner_logo_url", "The URL for an image file to use to represent the partner to users.")
+            .field::<Option<DateTime<FixedOffset>>>("partner_created", "The date and time when the partner's record of this opportunity was created")
+            .field::<Option<DateTime<FixedOffset>>>("partner_updated", "The date and time when the partner's record of this opportunity was last updated")
+            .field::<Option<String>>("partner_opp_url", "The URL where the partner wishes participants to be directed for this opportunity")
+            .field::<String>("organization_name", "The name of the organization which is hosting this opportunity")
+            .field::<OrganizationType>("organization_type", "The type of organization that is hosting this opportunity")
+            .field::<Option<String>>("organization_website", "The URL of the hosting organization's web site")
+            .field::<Option<String>>("organization_logo_url", "The URL of an image to use for representing the organization")
+            .field::<EntityType>("entity_type", "Opportunities are scheduled, while attractions are ongoing")
+            .with("entity_type", "default", "opportunity".into())
+            .field::<Vec<VenueType>>("opp_venue", "Description of the facility or facilities physically hosting this opportunity")
+            .field::<Vec<Descriptor>>("opp_descriptor", "Description of the opportunity based on established typology")
+            .field::<i16>("min_age", "The youngest age, in years, that the opportunity is suitable for. 0 means no lower age limit")
+            .with("min_age", "default", 0.into())
+            .field::<i16>("max_age", "The oldest age, in years, that the opportunity is suitable for. 999 means no upper age limit")
+            .with("max_age", "default", 999.into())
+            .field::<Domain>("pes_domain", "The high-level domain of Public Engagement in Science that this opportunity best fits into")
+            .field::<HashSet<String>>("tags", "Arbitrary set of tags which describe this opportunity")
+            .field::<Vec<Topic>>("opp_topics", "Topics applicable to this opportunity")
+            .field::<bool>("ticket_required", "Participation requires a ticket")
+            .field::<Option<i32>>("capacity", "Seats available at each occurrence when Science Near Me takes the registrations, or null when it doesn't")
+            .field::<String>("title", "The visible name which will identify this opportunity to participants")
+            .field::<String>("description", "A textual description of the opportunity. One or two paragraphs is an ideal length.")
+            .field::<String>("short_desc", "A short (120 character or less) \"elevator pitch\" for this opportunity.")
+            .field::<String>("image_url", "The URL where an image associated with this opportunity may be found")
+            .field::<String>("image_credit", "Accreditation and/or copyright acknowledgment for the image")
+            .field::<Vec<DateTime<FixedOffset>>>("start_datetimes", "Dates and times at which the opportunity begins")
+            .field::<bool>("has_end", "The start dates and times are paired with end dates and times in the end_datetimes field")
+            .field::<Vec<DateTime<FixedOffset>>>("end_datetimes", "Dates and times at which the opportunity ends. Each entry in end_datetimes should be paired with the corresponding entry in start_datetimes, so an opportunity might begin at start_datetimes[2] and end at end_datetimes[2]")
+            .field::<Recurrence>("recurrence", "If set to a value other than 'once', the opportunity start_datetimes and end_datetimes in conjunction with the recurrence will be used to create additional instances of the opportunity as time passes.")
+            .with("recurrence", "default", "once".into())
+            .field::<Option<DateTime<FixedOffset>>>("end_recurrence", "The recurrence will cease once it hits a date and time beyond this value, if not null")
+            .field::<Vec<DateTime<FixedOffset>>>("recurrence_rdates", "Additional dates and times at which the opportunity occurs, beyond those produced by the recurrence")
+            .field::<Vec<DateTime<FixedOffset>>>("recurrence_exdates", "Dates and times produced by the recurrence at which the opportunity does not occur")
+            .field::<Option<String>>("timezone", "The timezone for the opportunity, as an 'America/Los_Angeles' style timezonedb name; used to adjust times for daylight savings time in recurring opportunities")
+            .field::<Option<OpenDays>>("attraction_hours", "Attractions don't have start dates and end dates, but they do have open and close times on different days of the week. The attraction_hours field should only be used if the opportunity is not tied to specific dates, but instead is always available, subject to the schedule described here.")
+            .field::<Cost>("cost", "Whether participants have a cost associated with participating in this opportunity")
+            .field::<Vec<String>>("languages", "The languages the opportunity will be presented in, as RFC 5646 language tags")
+            .with("languages", "default", json!(["en-US"]))
+            .field::<BTreeMap<String, Translation>>("translations", "The title, short_desc and description in other languages, keyed by language code")
+            .field::<Vec<Accessibility>>("accessibility", "Accommodations available to participants")
+            .field::<bool>("is_online", "The opportunity is presented entirely online, with no in-person component")
+            .field::<LocationType>("location_type", "Display the opportunity as being available anywhere (any), at an address (at), within a region (near), or unknown")
+            .field::<String>("location_name", "If the location_type is 'at' or 'near' the value of this field will be used to help participants identify the opportunity location")
+            .field_with("location_point", geometry("Point", "The point at which this opportunity is centered,"), "")
+            .field_with("location_polygon", geometry("MultiPolygon", "The region within which this opportunity is available,"), "")
+            .field::<String>("address_street", "The street part of the address for this opportunity")
+            .field::<String>("address_city", "The city part of the address for this opportunity")
+            .field::<String>("address_state", "The state or province part of the address for this opportunity")
+            .field::<String>("address_country", "The country part of the address for this opportunity")
+            .field::<String>("address_zip", "The zip or postal code part of the address for this opportunity")
+            .field::<Vec<String>>("opp_hashtags", "Hashtags to use when advertising this opportunity on social media")
+            .field::<HashMap<String, String>>("opp_social_handles", "Handles to use when advertising this opportunity on social media. e.g. {\"twitter\": \"@SciStarter\"}")
+            .field::<Uuid>("partner", "Automatically set to the UID used for authorization when the opportunity was added.")
+            .read_only("partner")
+            .field::<Option<Uuid>>("parent", "The UID of the umbrella opportunity, such as a festival, when this is one of the events in a series. The dates, recurrence, location_type, location_point, location_polygon and is_online of a parent are derived from its published children, so they are read-only: any values submitted for a parent with children are replaced when it is stored.")
+            .required(&["partner_name", "title"])
+            .build()
+    }
+}
+
+impl JsonSchema for OpportunityInterior {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Option<bool>>("accepted", "Whether this opportunity has been accepted for publication")
+            .read_only("accepted")
+            .field::<bool>("withdrawn", "If set to true, this opportunity is not visible to the public")
+            .field::<Option<Uuid>>("submitted_by", "The person who submitted this opportunity through the Science Near Me web site, if any")
+            .read_only("submitted_by")
+            .field::<ReviewStatus>("review_status", "Where the opportunity is in the review process. Scheduled opportunities are published at publish_at.")
+            .field::<Option<DateTime<FixedOffset>>>("publish_at", "When a scheduled opportunity will be published")
+            .field::<Option<DateTime<FixedOffset>>>("unpublish_at", "When the opportunity will be returned to draft")
+            .field::<String>("contact_name", "The name of the person who should be contacted for administrative reasons regarding this opportunity. NOT PUBLISHED.")
+            .field_with("contact_email", json!({ "type": "string", "format": "email" }), "The email of the person who should be contacted for administrative reasons regarding this opportunity. NOT PUBLISHED.")
+            .field::<String>("contact_phone", "The phone number of the person who should be contacted for administrative reasons regarding this opportunity. NOT PUBLISHED.")
+            .field_with("extra_data", json!({ "type": "object" }), "Any additional information which should be stored as part of this opportunity record")
+            .field::<Option<DateTime<FixedOffset>>>("trashed_at", "When the opportunity was deleted. Deleted opportunities are left out of searches, and are purged for good after a retention period unless restored.")
+            .read_only("trashed_at")
+            .build()
+    }
+}
+
+impl JsonSchema for Opportunity {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Option<i32>>("id", "")
+            .read_only("id")
+            .flatten::<OpportunityExterior>()
+            .flatten::<OpportunityInterior>()
+            .build()
+    }
+}
+
+impl JsonSchema for OpportunityReference {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Uuid>("uid", "The UID which uniquely identifies this opportunity.")
+            .field::<String>(
+                "slug",
+                "The unique name of this opportunity in Science Near Me URLs",
+            )
+            .field::<String>(
+                "title",
+                "The visible name which will identify this opportunity to participants",
+            )
+            .field::<String>(
+                "image_url",
+                "The URL where an image associated with this opportunity may be found",
+            )
+            .field::<String>(
+                "short_desc",
+                "A short (120 character or less) \"elevator pitch\" for this opportunity.",
+            )
+            .required(&["uid"])
+            .build()
+    }
+}
+
+impl JsonSchema for Occurrence {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<DateTime<FixedOffset>>("start", "")
+            .field::<Option<DateTime<FixedOffset>>>("end", "")
+            .required(&["start"])
+            .build()
+    }
+}
+
+impl JsonSchema for OpportunityOccurrence {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<OpportunityReference>("opportunity", "")
+            .flatten::<Occurrence>()
+            .build()
+    }
+}
+
+impl JsonSchema for OccurrenceWindow {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<DateTime<FixedOffset>>("begin", "")
+            .field::<DateTime<FixedOffset>>("end", "")
+            .field::<Vec<OpportunityOccurrence>>(
+                "occurrences",
+                "Every occurrence within the window, in order of their start",
+            )
+            .field::<BTreeMap<NaiveDate, u32>>(
+                "days",
+                "The number of occurrences in progress on each day of the window, keyed by date",
+            )
+            .build()
+    }
+}
+
+impl JsonSchema for RevisionAuthor {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field_with(
+                "kind",
+                json!({ "type": "string", "enum": ["person", "partner", "importer", "system"] }),
+                "",
+            )
+            .field::<Uuid>(
+                "uid",
+                "Identifies the person or partner, absent for the system",
+            )
+            .required(&["kind"])
+            .build()
+    }
+}
+
+impl JsonSchema for Revision {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<i32>("id", "")
+            .field::<DateTime<FixedOffset>>("created", "")
+            .field::<RevisionAuthor>("author", "Who stored this revision")
+            .field::<String>(
+                "source",
+                "How the opportunity was stored, e.g. \"api\", \"importer\" or \"rollback\"",
+            )
+            .field_with(
+                "snapshot",
+                json!({ "type": "object" }),
+                "The opportunity as it was stored",
+            )
+            .build()
+    }
+}
+
+impl JsonSchema for FieldChange {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<String>("field", "")
+            .field::<Value>("before", "")
+            .field::<Value>("after", "")
+            .build()
+    }
+}
+
+impl JsonSchema for Attendee {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<DateTime<FixedOffset>>(
+                "occurrence",
+                "The start of the occurrence the person registered for",
+            )
+            .field::<RsvpStatus>("status", "")
+            .field::<Option<i64>>(
+                "position",
+                "Position on the waitlist, for waitlisted registrations",
+            )
+            .field::<DateTime<FixedOffset>>("registered", "")
+            .field::<Uuid>("person", "")
+            .field::<Option<String>>("username", "")
+            .field::<Option<String>>("first_name", "")
+            .field::<Option<String>>("last_name", "")
+            .field::<String>("email", "")
+            .field::<Option<String>>("phone", "")
+            .build()
+    }
+}
+
+impl JsonSchema for ValidationIssue {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<String>("path", "JSON path of the offending value in the opportunity document, e.g. $.title or $.end_datetimes[1]")
+            .field::<IssueCode>("code", "")
+            .field::<Severity>("severity", "Errors prevent the opportunity from being stored, while warnings do not")
+            .field::<String>("message", "")
+            .required(&["path", "code", "severity", "message"])
+            .build()
+    }
+}
+
+impl JsonSchema for ParticipationExterior {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Uuid>("opportunity", "The UID which uniquely identifies the opportunity being participated in. This is the same value which was sent to and/or returned from the opportunity API endpoints.")
+            .field::<Uuid>("partner", "The UID which uniquely identifies the partner which reported this participation.")
+            .read_only("partner")
+            .field::<DateTime<FixedOffset>>("when", "The date and time when this participation action was completed.")
+            .field::<Mode>("mode", "Which general category of participation the person performed.")
+            .field::<Vec<String>>("keywords", "Any additional keywords which may further refine the understanding of this participation.")
+            .required(&["opportunity", "when", "mode"])
+            .build()
+    }
+}
+
+impl JsonSchema for ParticipationInterior {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Option<Uuid>>("participant", "")
+            .read_only("participant")
+            .field::<Option<String>>("snml", "")
+            .read_only("snml")
+            .field_with(
+                "location",
+                geometry("Point", "The point at which this participation occurred,"),
+                "",
+            )
+            .build()
+    }
+}
+
+impl JsonSchema for Participation {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<Option<i32>>("id", "")
+            .read_only("id")
+            .flatten::<ParticipationExterior>()
+            .flatten::<ParticipationInterior>()
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn keys(value: &Value) -> BTreeSet<String> {
+        value
+            .as_object()
+            .map(|obj| obj.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Fails if the schema of `T` doesn't document exactly the fields
+    /// which `value` serializes to
+    fn assert_documents<T: JsonSchema + Serialize>(value: &T) {
+        assert_eq!(
+            keys(&T::schema()["properties"]),
+            keys(&serde_json::to_value(value).unwrap())
+        );
+    }
+
+    #[test]
+    fn schemas_document_every_serialized_field() {
+        assert_documents(&Opportunity::default());
+        assert_documents(&OpportunityReference::default());
+        assert_documents(&Translation::default());
+        assert_documents(&OpenDays::default());
+        assert_documents(&OpenHours {
+            opens: "10:00:00Z".to_string(),
+            closes: "16:00:00Z".to_string(),
+        });
+        assert_documents(&ValidationIssue {
+            path: "$.title".to_string(),
+            code: IssueCode::Required,
+            severity: Severity::Error,
+            message: "title must not be empty".to_string(),
+        });
+        assert_documents(&Participation {
+            id: None,
+            exterior: ParticipationExterior {
+                opportunity: Uuid::nil(),
+                partner: Uuid::nil(),
+                when: chrono::Utc::now().into(),
+                mode: Mode::Signup,
+                keywords: vec![],
+            },
+            interior: ParticipationInterior {
+                participant: None,
+                snml: None,
+                location: None,
+            },
+        });
+        assert_documents(&Revision {
+            id: 1,
+            created: chrono::Utc::now().into(),
+            author: RevisionAuthor::Partner(Uuid::nil()),
+            source: "api".to_string(),
+            snapshot: json!({}),
+        });
+    }
+
+    #[test]
+    fn enums_list_their_serialized_values() {
+        assert_eq!(
+            Mode::schema()["enum"],
+            json!(["signup", "collect-data", "analyze-data", "organize"])
+        );
+        assert_eq!(Option::<Cost>::schema()["nullable"], json!(true));
+    }
+}
@@ -1,6 +1,7 @@
+use super::geojson::{BoundingBox, Geometry};
 use super::Error;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize};
 
 pub fn serialize_enum<T: Serialize>(val: &T) -> String {
     // Serialize the enum to a JSON string like "\"kebab-value\"", then strip the quotes
@@ -22,3 +23,59 @@ pub fn deserialize_enum_vec<T: DeserializeOwned>(vals: &[String]) -> Vec<T> {
         .filter_map(|s| deserialize_enum(s).ok())
         .collect()
 }
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BoundingBoxInput {
+    Text(String),
+    Tuple(BoundingBox),
+}
+
+/// Deserializes a bounding box either from a tuple or, as it arrives
+/// in query strings, from "west,south,east,north"
+pub fn deserialize_bbox<'de, D>(deserializer: D) -> Result<Option<BoundingBox>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<BoundingBoxInput>::deserialize(deserializer)? {
+        Some(BoundingBoxInput::Tuple(bbox)) => Ok(Some(bbox)),
+        Some(BoundingBoxInput::Text(text)) => {
+            let parts = text
+                .split(',')
+                .map(|part| part.trim().parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(serde::de::Error::custom)?;
+
+            match parts[..] {
+                [west, south, east, north] => Ok(Some((west, south, east, north))),
+                _ => Err(serde::de::Error::invalid_length(
+                    parts.len(),
+                    &"west,south,east,north",
+                )),
+            }
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GeometryInput {
+    Text(String),
+    Geometry(Geometry),
+}
+
+/// Deserializes a GeoJSON geometry either from an object or, as it
+/// arrives in query strings, from the text of one
+pub fn deserialize_geometry<'de, D>(deserializer: D) -> Result<Option<Geometry>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<GeometryInput>::deserialize(deserializer)? {
+        Some(GeometryInput::Geometry(geometry)) => Ok(Some(geometry)),
+        Some(GeometryInput::Text(text)) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
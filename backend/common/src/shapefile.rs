@@ -0,0 +1,349 @@
+//! Just enough of the ESRI shapefile format to read polygons and
+//! their attributes out of a zipped shapefile, as published for
+//! counties, school districts and such. Coordinates must already be
+//! longitude and latitude; we don't reproject.
+
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::model::geojson::{Feature, FeatureCollection, Geometry, MultiPolygon};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("I/O error")]
+    IO(#[from] std::io::Error),
+    #[error("zip archive error")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("archive does not contain a {0} file")]
+    MissingFile(&'static str),
+    #[error("shapefile coordinates are projected, not longitude and latitude")]
+    Projected,
+    #[error("unsupported shape type {0}")]
+    ShapeType(i32),
+    #[error("malformed {0} file")]
+    Malformed(&'static str),
+}
+
+const SHAPE_NULL: i32 = 0;
+const SHAPE_POLYGON: i32 = 5;
+const SHAPE_POLYGON_Z: i32 = 15;
+const SHAPE_POLYGON_M: i32 = 25;
+
+struct Cursor<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+    file: &'static str,
+}
+
+impl<'b> Cursor<'b> {
+    fn new(bytes: &'b [u8], file: &'static str) -> Self {
+        Cursor {
+            bytes,
+            pos: 0,
+            file,
+        }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8], Error> {
+        let end = self.pos + len;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(Error::Malformed(self.file))?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn i32_be(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i32_le(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f64_le(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn count(&mut self) -> Result<usize, Error> {
+        usize::try_from(self.i32_le()?).map_err(|_| Error::Malformed(self.file))
+    }
+}
+
+// Twice the signed area of the ring, which is negative for the
+// clockwise outer rings of shapefile polygons
+fn ring_area(ring: &[Vec<f32>]) -> f64 {
+    ring.windows(2)
+        .map(|pair| {
+            (pair[0][0] as f64) * (pair[1][1] as f64) - (pair[1][0] as f64) * (pair[0][1] as f64)
+        })
+        .sum()
+}
+
+// Shapefile polygons are a flat list of rings, where clockwise rings
+// are outer boundaries and counterclockwise rings are holes. Each hole
+// is assigned to the outer ring preceding it, which is how shapefiles
+// are written in practice.
+fn polygon_rings(rings: Vec<Vec<Vec<f32>>>) -> Vec<Vec<Vec<Vec<f32>>>> {
+    let mut polygons: Vec<Vec<Vec<Vec<f32>>>> = Vec::new();
+
+    for ring in rings {
+        match polygons.last_mut() {
+            Some(polygon) if ring_area(&ring) > 0.0 => polygon.push(ring),
+            _ => polygons.push(vec![ring]),
+        }
+    }
+
+    polygons
+}
+
+/// Reads the geometry of each record in a .shp file. Null shapes are
+/// returned as None, so that records still line up with the .dbf
+pub fn read_shapes(bytes: &[u8]) -> Result<Vec<Option<Geometry>>, Error> {
+    let mut cursor = Cursor::new(bytes, ".shp");
+
+    if cursor.i32_be()? != 9994 {
+        return Err(Error::Malformed(".shp"));
+    }
+
+    cursor.take(96)?;
+
+    let mut shapes = Vec::new();
+
+    while cursor.pos < bytes.len() {
+        let _number = cursor.i32_be()?;
+        let length = usize::try_from(cursor.i32_be()?).map_err(|_| Error::Malformed(".shp"))?;
+        let mut record = Cursor::new(cursor.take(length * 2)?, ".shp");
+
+        match record.i32_le()? {
+            SHAPE_NULL => shapes.push(None),
+            SHAPE_POLYGON | SHAPE_POLYGON_Z | SHAPE_POLYGON_M => {
+                record.take(32)?;
+
+                let num_parts = record.count()?;
+                let num_points = record.count()?;
+
+                let mut starts = Vec::with_capacity(num_parts);
+                for _ in 0..num_parts {
+                    starts.push(record.count()?);
+                }
+
+                let mut points = Vec::with_capacity(num_points);
+                for _ in 0..num_points {
+                    let x = record.f64_le()?;
+                    let y = record.f64_le()?;
+                    points.push(vec![x as f32, y as f32]);
+                }
+
+                let mut rings = Vec::with_capacity(num_parts);
+                for (i, start) in starts.iter().enumerate() {
+                    let end = starts.get(i + 1).copied().unwrap_or(num_points);
+                    rings.push(
+                        points
+                            .get(*start..end)
+                            .ok_or(Error::Malformed(".shp"))?
+                            .to_vec(),
+                    );
+                }
+
+                shapes.push(Some(Geometry::MultiPolygon(MultiPolygon {
+                    coordinates: polygon_rings(rings),
+                })));
+            }
+            other => return Err(Error::ShapeType(other)),
+        }
+    }
+
+    Ok(shapes)
+}
+
+/// Reads the attributes of each record in a .dbf file. Every value is
+/// read as a string, and deleted records are returned as None.
+pub fn read_attributes(bytes: &[u8]) -> Result<Vec<Option<Map<String, Value>>>, Error> {
+    let mut cursor = Cursor::new(bytes, ".dbf");
+
+    let header = cursor.take(32)?;
+    let num_records = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+    let header_len = u16::from_le_bytes(header[8..10].try_into().unwrap()) as usize;
+    let record_len = u16::from_le_bytes(header[10..12].try_into().unwrap()) as usize;
+
+    let mut fields = Vec::new();
+
+    while cursor.bytes.get(cursor.pos) != Some(&0x0d) {
+        let descriptor = cursor.take(32)?;
+        let name_len = descriptor[..11].iter().position(|b| *b == 0).unwrap_or(11);
+        let name = String::from_utf8_lossy(&descriptor[..name_len]).to_string();
+        fields.push((name, descriptor[16] as usize));
+    }
+
+    cursor.pos = header_len;
+
+    let mut records = Vec::with_capacity(num_records);
+
+    for _ in 0..num_records {
+        let mut record = Cursor::new(cursor.take(record_len)?, ".dbf");
+
+        let deleted = record.take(1)? == b"*";
+
+        let mut attributes = Map::new();
+        for (name, len) in fields.iter() {
+            let value = String::from_utf8_lossy(record.take(*len)?)
+                .trim()
+                .to_string();
+            attributes.insert(name.clone(), Value::String(value));
+        }
+
+        records.push(if deleted { None } else { Some(attributes) });
+    }
+
+    Ok(records)
+}
+
+fn read_entry<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    archive.by_name(name)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Reads the first shapefile in a zip archive as a GeoJSON feature
+/// collection, with the attributes of each record as the properties
+/// of its feature
+pub fn read_zip<P: AsRef<Path>>(path: P) -> Result<FeatureCollection, Error> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+
+    let shp = archive
+        .file_names()
+        .find(|name| name.to_lowercase().ends_with(".shp"))
+        .map(str::to_string)
+        .ok_or(Error::MissingFile(".shp"))?;
+    let stem = &shp[..shp.len() - 4];
+
+    let sibling = |archive: &zip::ZipArchive<std::fs::File>, ext: &str| {
+        archive
+            .file_names()
+            .find(|name| {
+                name.len() == shp.len()
+                    && name.to_lowercase() == format!("{}.{}", stem.to_lowercase(), ext)
+            })
+            .map(str::to_string)
+    };
+
+    if let Some(prj) = sibling(&archive, "prj") {
+        let projection = read_entry(&mut archive, &prj)?;
+        if projection.starts_with(b"PROJCS") {
+            return Err(Error::Projected);
+        }
+    }
+
+    let dbf = sibling(&archive, "dbf").ok_or(Error::MissingFile(".dbf"))?;
+
+    let shapes = read_shapes(&read_entry(&mut archive, &shp)?)?;
+    let attributes = read_attributes(&read_entry(&mut archive, &dbf)?)?;
+
+    Ok(FeatureCollection {
+        features: shapes
+            .into_iter()
+            .zip(attributes)
+            .filter_map(|(geometry, attributes)| {
+                attributes.map(|attributes| Feature {
+                    geometry,
+                    properties: Some(Value::Object(attributes)),
+                })
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon_record(rings: &[&[(f64, f64)]]) -> Vec<u8> {
+        let num_points: usize = rings.iter().map(|r| r.len()).sum();
+
+        let mut content = Vec::new();
+        content.extend(SHAPE_POLYGON.to_le_bytes());
+        content.extend([0u8; 32]);
+        content.extend((rings.len() as i32).to_le_bytes());
+        content.extend((num_points as i32).to_le_bytes());
+
+        let mut start = 0;
+        for ring in rings {
+            content.extend((start as i32).to_le_bytes());
+            start += ring.len();
+        }
+
+        for (x, y) in rings.iter().flat_map(|r| r.iter()) {
+            content.extend(x.to_le_bytes());
+            content.extend(y.to_le_bytes());
+        }
+
+        let mut record = Vec::new();
+        record.extend(1i32.to_be_bytes());
+        record.extend(((content.len() / 2) as i32).to_be_bytes());
+        record.extend(content);
+        record
+    }
+
+    #[test]
+    fn shapes_separate_polygons_and_holes() {
+        let mut shp = Vec::new();
+        shp.extend(9994i32.to_be_bytes());
+        shp.extend([0u8; 96]);
+
+        // Clockwise outer ring with a counterclockwise hole, then a
+        // second clockwise outer ring
+        shp.extend(polygon_record(&[
+            &[(0.0, 0.0), (0.0, 4.0), (4.0, 4.0), (4.0, 0.0), (0.0, 0.0)],
+            &[(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0), (1.0, 1.0)],
+            &[(8.0, 8.0), (8.0, 9.0), (9.0, 9.0), (9.0, 8.0), (8.0, 8.0)],
+        ]));
+
+        let shapes = read_shapes(&shp).unwrap();
+
+        assert_eq!(shapes.len(), 1);
+
+        let Some(Geometry::MultiPolygon(MultiPolygon { coordinates })) = &shapes[0] else {
+            panic!("expected a multipolygon");
+        };
+
+        assert_eq!(coordinates.len(), 2);
+        assert_eq!(coordinates[0].len(), 2);
+        assert_eq!(coordinates[1].len(), 1);
+        assert_eq!(coordinates[1][0][2], vec![9.0, 9.0]);
+    }
+
+    #[test]
+    fn attributes_are_read_by_field() {
+        let mut dbf = vec![0u8; 32];
+        dbf[0] = 3;
+        dbf[4..8].copy_from_slice(&2u32.to_le_bytes());
+        dbf[8..10].copy_from_slice(&(32u16 * 3 + 1).to_le_bytes());
+        dbf[10..12].copy_from_slice(&(1u16 + 10 + 2).to_le_bytes());
+
+        for (name, len) in [("NAME", 10u8), ("ST", 2u8)] {
+            let mut descriptor = [0u8; 32];
+            descriptor[..name.len()].copy_from_slice(name.as_bytes());
+            descriptor[11] = b'C';
+            descriptor[16] = len;
+            dbf.extend(descriptor);
+        }
+
+        dbf.push(0x0d);
+        dbf.extend(b" Allegheny PA");
+        dbf.extend(b"*Gone      XX");
+
+        let records = read_attributes(&dbf).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].as_ref().unwrap()["NAME"], "Allegheny");
+        assert_eq!(records[0].as_ref().unwrap()["ST"], "PA");
+        assert!(records[1].is_none());
+    }
+}
@@ -2,9 +2,11 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 
-use common::model::opportunity::{Descriptor, Domain, OpportunityImportRecord, Topic};
+use common::model::opportunity::{
+    Descriptor, Domain, OpportunityImportRecord, RevisionAuthor, Topic,
+};
 use common::model::partner::LoggedErrorLevel;
-use common::model::Partner;
+use common::model::{Opportunity, Partner};
 use importer::format::{self, CommaSeparated, Format};
 use importer::source::embedded::Embedded;
 use importer::source::{self, Source};
@@ -51,9 +53,11 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
             domain: Domain::OutOfSchoolTimeProgram,
             descriptor: vec![Descriptor::Community, Descriptor::LiveScience],
             topics: vec![Topic::GeneralScience],
+            accessibility: vec![],
             flags: vec![],
             address: None,
             timezone: Some(chrono_tz::US::Eastern),
+            parent: None,
         }),
         period: 24 * hours,
     }));
@@ -70,6 +74,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                 domain: Domain::MuseumOrScienceCenter,
                 descriptor: vec![Descriptor::Maker],
                 topics: vec![],
+                accessibility: vec![],
                 flags: vec![PartnerFlag::Ticketed],
                 address: Some(PartnerAddress {
                     name: "Rockville Science Center".into(),
@@ -80,6 +85,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                     country: "United States of America".into()
                 }),
                 timezone: Some(chrono_tz::US::Eastern),
+                parent: None,
             },
             24 * hours,
         )
@@ -112,6 +118,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                 domain: Domain::MuseumOrScienceCenter,
                 descriptor: vec![Descriptor::Community],
                 topics: vec![Topic::GeneralScience],
+                accessibility: vec![],
                 flags: Vec::new(),
                 address: Some(PartnerAddress {
                     name: "SciTech Institute".to_string(),
@@ -122,6 +129,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                     country: "United States of America".to_string(),
                 }),
                 timezone: Some(chrono_tz::US::Arizona),
+                parent: None,
             },
         ),
         period: 24 * hours,
@@ -140,6 +148,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                 domain: Domain::MuseumOrScienceCenter,
                 descriptor: vec![Descriptor::ExpoStyle],
                 topics: vec![Topic::Engineering, Topic::Technology],
+                accessibility: vec![],
                 flags: Vec::new(),
                 address: Some(PartnerAddress {
                     name: "Explora".to_string(),
@@ -150,6 +159,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                     country: "United States of America".to_string(),
                 }),
                 timezone: Some(chrono_tz::US::Mountain),
+                parent: None,
             },
         ),
         period: 24 * hours,
@@ -179,6 +189,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
             domain: Domain::MuseumOrScienceCenter,
             descriptor: vec![Descriptor::Community],
             topics: vec![Topic::GeneralScience],
+            accessibility: vec![],
             flags: vec![PartnerFlag::Cost],
             address: Some(PartnerAddress {
                 name: "The Discovery".to_string(),
@@ -189,6 +200,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                 country: "United States of America".to_string(),
             }),
             timezone: Some(chrono_tz::US::Pacific),
+            parent: None,
         }),
         period: 24 * hours,
     }));
@@ -208,9 +220,11 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
             domain: Domain::LiveScience,
             descriptor: vec![Descriptor::Festival],
             topics: vec![Topic::GeneralScience],
+            accessibility: vec![],
             flags: vec![],
             address: None,
             timezone: Some(chrono_tz::US::Central),
+            parent: None,
         }),
         period: 24 * hours,
     }));
@@ -227,10 +241,12 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
             partner_logo_url: Some("".to_string()),
             domain: Domain::LiveScience,
             topics: vec![Topic::AstronomyAndSpace],
+            accessibility: vec![],
             descriptor: vec![Descriptor::Community],
             flags: vec![],
             address: None,
             timezone: Some(chrono::Utc),
+            parent: None,
         }),
         period: 24 * hours,
     }));
@@ -250,6 +266,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
             domain: Domain::MuseumOrScienceCenter,
             descriptor: vec![Descriptor::LiveScience],
             topics: vec![Topic::GeneralScience],
+            accessibility: vec![],
             flags: vec![PartnerFlag::Cost],
             address: Some(PartnerAddress {
                 name: "Museum of Discovery and Science".to_string(),
@@ -260,6 +277,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                 country: "USA".to_string(),
             }),
             timezone: Some(chrono_tz::US::Eastern),
+            parent: None,
         }),
         period: 24 * hours,
     }));
@@ -278,9 +296,11 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
             domain: Domain::Policy,
             descriptor: vec![Descriptor::Forum],
             topics: vec![],
+            accessibility: vec![],
             flags: vec![],
             address: None,
             timezone: Some(chrono_tz::US::Eastern),
+            parent: None,
         }),
         period: 24 * hours,
     }));
@@ -299,6 +319,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
             domain: Domain::MuseumOrScienceCenter,
             descriptor: vec![Descriptor::LiveScience],
             topics: vec![Topic::GeneralScience],
+            accessibility: vec![],
             flags: vec![PartnerFlag::Cost],
             address: Some(PartnerAddress {
                 name: "Michigan Science Center".into(),
@@ -309,6 +330,7 @@ pub fn configure(importers: &mut Vec<Box<dyn Importer>>) {
                 country: "USA".into()
             }),
             timezone: Some(chrono_tz::US::Eastern),
+            parent: None,
         }),
         period: 24 * hours,
     }));
@@ -397,13 +419,34 @@ where
             .await;
         }
 
+        /// Logs every validation issue found in the item, returning
+        /// false if any of them prevent it from being stored
+        async fn check_valid(
+            item: &mut Opportunity,
+            partner_id: i32,
+            db: &sqlx::Pool<sqlx::Postgres>,
+        ) -> bool {
+            let report = item.validation_report().await;
+
+            for issue in &report.issues {
+                let mut le = issue.logged_error(&item.exterior.title);
+                le.partner_id = partner_id;
+                let _ = le.store(db).await;
+                println!("[Validate] Logged {:?}: {}", le.level, &le.message);
+            }
+
+            report.is_valid()
+        }
+
+        let partner_id = partner
+            .id
+            .expect("The id should be set after loading from the database");
+
         println!("Loading...");
         let source = match self.source.load() {
             Ok(s) => s,
             Err(mut le) => {
-                le.partner_id = partner
-                    .id
-                    .expect("The id should be set after loading from the database");
+                le.partner_id = partner_id;
                 let _ = le.store(&db).await;
                 println!("[Load] Logged error: {}", &le.message);
                 store_outcome(outcome, &db).await;
@@ -415,9 +458,7 @@ where
         let format = match self.format.decode(source) {
             Ok(f) => f,
             Err(mut le) => {
-                le.partner_id = partner
-                    .id
-                    .expect("The id should be set after loading from the database");
+                le.partner_id = partner_id;
                 let _ = le.store(&db).await;
                 println!("[Parse] Logged error: {}", &le.message);
                 store_outcome(outcome, &db).await;
@@ -430,10 +471,20 @@ where
             OneOrMany::One(result) => {
                 match result {
                     Ok(mut item) => {
+                        if !check_valid(&mut item, partner_id, &db).await {
+                            outcome.failed += 1;
+                            store_outcome(outcome, &db).await;
+                            return Ok(Some(self.period));
+                        }
                         item.set_id_if_necessary(&db).await?;
                         let created = item.id.is_none();
                         item.interior.accepted = if created { Some(true) } else { None };
-                        item.store(&db).await?;
+                        item.store_by(
+                            &db,
+                            &RevisionAuthor::Importer(item.exterior.partner),
+                            "importer",
+                        )
+                        .await?;
                         OpportunityImportRecord::store(
                             &db,
                             &item.exterior.partner,
@@ -456,9 +507,7 @@ where
                     }
                     Err(mut le) => {
                         outcome.failed += 1;
-                        le.partner_id = partner
-                            .id
-                            .expect("The id should be set after loading from the database");
+                        le.partner_id = partner_id;
                         let _ = le.store(&db).await;
                         if le.level != LoggedErrorLevel::Info {
                             println!("[Interpret] Logged error: {}", &le.message);
@@ -470,10 +519,19 @@ where
                 for result in vec {
                     match result {
                         Ok(mut item) => {
+                            if !check_valid(&mut item, partner_id, &db).await {
+                                outcome.failed += 1;
+                                continue;
+                            }
                             item.set_id_if_necessary(&db).await?;
                             let created = item.id.is_none();
                             item.interior.accepted = if created { Some(true) } else { None };
-                            item.store(&db).await?;
+                            item.store_by(
+                                &db,
+                                &RevisionAuthor::Importer(item.exterior.partner),
+                                "importer",
+                            )
+                            .await?;
                             OpportunityImportRecord::store(
                                 &db,
                                 &item.exterior.partner,
@@ -496,9 +554,7 @@ where
                         }
                         Err(mut le) => {
                             outcome.failed += 1;
-                            le.partner_id = partner
-                                .id
-                                .expect("The id should be set after loading from the database");
+                            le.partner_id = partner_id;
                             let _ = le.store(&db).await;
                             if le.level != LoggedErrorLevel::Info {
                                 println!("[Interpret] Logged error: {}", &le.message);
@@ -278,6 +278,8 @@ fn interpret_one<Tz: TimeZone>(
 
     opp.exterior.partner = partner.partner.clone();
 
+    opp.exterior.parent = partner.parent;
+
     opp.exterior.partner_name = partner.partner_name.clone();
 
     opp.exterior.partner_website = partner.partner_website.clone();
@@ -379,6 +381,8 @@ fn interpret_one<Tz: TimeZone>(
 
     opp.exterior.opp_descriptor = partner.descriptor.clone();
 
+    opp.exterior.accessibility = partner.accessibility.clone();
+
     opp.exterior.min_age = 0;
 
     opp.exterior.max_age = 999;
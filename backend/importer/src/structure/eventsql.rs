@@ -144,6 +144,8 @@ fn interpret_one<Tz: TimeZone>(
 
         opp.exterior.partner = partner.partner.clone();
 
+        opp.exterior.parent = partner.parent;
+
         opp.exterior.partner_name = partner.partner_name.clone();
 
         opp.exterior.partner_website = partner.partner_website.clone();
@@ -370,6 +372,9 @@ fn interpret_one<Tz: TimeZone>(
             .opp_descriptor
             .append(&mut partner.descriptor.clone());
         opp.exterior.opp_topics.append(&mut partner.topics.clone());
+        opp.exterior
+            .accessibility
+            .append(&mut partner.accessibility.clone());
     } else {
         return Err(
             Error::Data("Warning: Opportunity data is not present in the record".into()).into(),
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use chrono::TimeZone;
+use chrono::{DateTime, FixedOffset, TimeZone};
 use common::{
     geo::Match,
     model::{
@@ -35,11 +35,13 @@ enum SchemaOrgType {
 #[derive(serde::Deserialize, Debug)]
 enum SchemaOrgLocationType {
     Place,
+    VirtualLocation,
 }
 
 #[derive(serde::Deserialize, Debug)]
 enum SchemaOrgOrganizerType {
     Person,
+    Organization,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -90,15 +92,89 @@ enum SchemaOrgEventAttendanceMode {
     Mixed,
 }
 
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+struct SchemaOrgPostalAddress {
+    #[serde(default)]
+    street_address: String,
+    #[serde(default)]
+    address_locality: String,
+    #[serde(default)]
+    address_region: String,
+    #[serde(default)]
+    postal_code: String,
+    #[serde(default)]
+    address_country: String,
+}
+
+#[derive(serde::Deserialize, Debug)]
+#[serde(untagged)]
+enum SchemaOrgAddress {
+    Text(String),
+    Postal(SchemaOrgPostalAddress),
+}
+
+impl Default for SchemaOrgAddress {
+    fn default() -> Self {
+        SchemaOrgAddress::Text(String::new())
+    }
+}
+
+impl SchemaOrgAddress {
+    fn text(&self) -> String {
+        match self {
+            SchemaOrgAddress::Text(text) => text.clone(),
+            SchemaOrgAddress::Postal(postal) => [
+                &postal.street_address,
+                &postal.address_locality,
+                &postal.address_region,
+                &postal.postal_code,
+                &postal.address_country,
+            ]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .map(|part| part.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct SchemaOrgGeoCoordinates {
+    latitude: f64,
+    longitude: f64,
+}
+
 #[derive(serde::Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct SchemaOrgLocation {
     #[serde(rename = "@type")]
     schema_type: SchemaOrgLocationType,
+    #[serde(default)]
     name: String,
     #[serde(default, rename = "image")]
     _image: String,
-    address: String,
+    #[serde(default)]
+    address: SchemaOrgAddress,
+    #[serde(default)]
+    geo: Option<SchemaOrgGeoCoordinates>,
+}
+
+#[derive(serde::Deserialize, Debug)]
+#[serde(untagged)]
+enum SchemaOrgLocations {
+    One(SchemaOrgLocation),
+    Many(Vec<SchemaOrgLocation>),
+}
+
+impl SchemaOrgLocations {
+    fn into_vec(self) -> Vec<SchemaOrgLocation> {
+        match self {
+            SchemaOrgLocations::One(location) => vec![location],
+            SchemaOrgLocations::Many(locations) => locations,
+        }
+    }
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -107,14 +183,16 @@ struct SchemaOrgOrganizer {
     #[serde(rename = "@type")]
     _schema_type: SchemaOrgOrganizerType,
     name: String,
+    #[serde(default)]
     url: String,
 }
 
-#[derive(serde::Deserialize, Debug)]
+#[derive(serde::Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 struct Offer {
     #[serde(default, rename = "url")]
     _url: String,
+    #[serde(default)]
     price: String,
     #[serde(default, rename = "price_currency")]
     _price_currency: String,
@@ -135,12 +213,17 @@ struct SchemaOrgEvent {
     start_date: String,
     end_date: String,
     event_attendance_mode: SchemaOrgEventAttendanceMode,
-    location: SchemaOrgLocation,
+    location: SchemaOrgLocations,
     organizer: SchemaOrgOrganizer,
-    offers: Offer,
+    #[serde(default)]
+    offers: Option<Offer>,
+    #[serde(default)]
+    is_accessible_for_free: Option<bool>,
     #[serde(default, rename = "performer")]
     _performer: String,
+    #[serde(default)]
     description: String,
+    #[serde(default)]
     image: String,
     name: String,
     url: String,
@@ -150,6 +233,83 @@ impl<Tz> LdJson<Tz>
 where
     Tz: TimeZone + std::fmt::Debug + Sync + Send,
 {
+    /// Geocodes `address`, filling in the opportunity's location
+    fn locate(
+        &self,
+        opp: &mut Opportunity,
+        address: &str,
+        cache: &mut HashMap<String, Option<Match>>,
+    ) {
+        println!("Looking up: {}", address);
+        if let Some(loc) = match cache.get(address) {
+            Some(val) => val.as_deref(),
+            None => {
+                let query = common::geo::Query::new(address.to_string(), true);
+                cache.insert(
+                    address.to_string(),
+                    async_std::task::block_on(query.lookup_one()),
+                );
+                cache.get(address).and_then(|v| v.as_deref())
+            }
+        } {
+            println!(
+                "Location: {}, {}",
+                loc.geometry.longitude, loc.geometry.latitude
+            );
+            opp.exterior.location_point = Some(
+                json!({"type": "Point", "coordinates": [loc.geometry.longitude, loc.geometry.latitude]}),
+            );
+            opp.exterior.address_street = loc
+                .formatted
+                .split_once(',')
+                .unwrap_or(("", ""))
+                .0
+                .to_owned();
+            opp.exterior.address_city = loc
+                .components
+                .city
+                .clone()
+                .or_else(|| loc.components.town.clone())
+                .unwrap_or_default();
+            opp.exterior.address_state = loc.components.state.clone().unwrap_or_default();
+            opp.exterior.address_country = loc.components.country_code.clone();
+            opp.exterior.address_zip = loc.components.postcode.clone().unwrap_or_default();
+        }
+    }
+
+    /// Parses a schema.org `Date` or `DateTime`. A date without a
+    /// time is placed at the given time of day in the partner's time
+    /// zone.
+    fn parse_date(
+        &self,
+        value: &str,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Result<DateTime<FixedOffset>, LoggedError> {
+        if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+            return Ok(dt);
+        }
+
+        let Some(tz) = &self.1.timezone else {
+            return Err(LoggedError::new(
+                LoggedErrorLevel::Debug,
+                "Partner time zone is not set, so the start and end datetimes would be ambiguous",
+            ));
+        };
+
+        Ok(tz
+            .from_local_datetime(
+                &value
+                    .parse::<chrono::NaiveDate>()?
+                    .and_hms_opt(hour, minute, second)
+                    .expect("The time of day should always be valid"),
+            )
+            .earliest()
+            .ok_or_else(|| LoggedError::new(LoggedErrorLevel::Error, "Unable to determine date"))?
+            .to_fixed_offset())
+    }
+
     fn interpret_one(
         &self,
         json: Value,
@@ -177,10 +337,15 @@ where
 
         opp.exterior.image_url = data.image;
 
-        opp.exterior.cost = match data.offers.price.as_ref() {
-            "Free" | "free" => common::model::opportunity::Cost::Free,
-            "$0" | "$0.00" => common::model::opportunity::Cost::Free,
-            _ => common::model::opportunity::Cost::Cost,
+        opp.exterior.cost = match (&data.offers, data.is_accessible_for_free) {
+            (_, Some(true)) => common::model::opportunity::Cost::Free,
+            (Some(offers), _) => match offers.price.as_ref() {
+                "Free" | "free" => common::model::opportunity::Cost::Free,
+                "0" | "0.00" | "$0" | "$0.00" => common::model::opportunity::Cost::Free,
+                _ => common::model::opportunity::Cost::Cost,
+            },
+            (None, Some(false)) => common::model::opportunity::Cost::Cost,
+            (None, None) => common::model::opportunity::Cost::Unknown,
         };
 
         opp.exterior.organization_name = htmlentity::entity::decode(data.organizer.name.as_bytes())
@@ -198,51 +363,32 @@ where
 
         opp.exterior.opp_venue = vec![common::model::opportunity::VenueType::Indoors];
 
-        match data.location.schema_type {
-            SchemaOrgLocationType::Place => {
-                opp.exterior.location_type = common::model::opportunity::LocationType::At;
-                opp.exterior.location_name = data.location.name;
-
-                println!("Looking up: {}", &data.location.address);
-                if let Some(loc) = match cache.get(&data.location.address) {
-                    Some(val) => val.as_deref(),
-                    None => {
-                        let query = common::geo::Query::new(data.location.address.clone(), true);
-                        cache.insert(
-                            data.location.address.clone(),
-                            async_std::task::block_on(query.lookup_one()),
-                        );
-                        cache
-                            .get(&data.location.address)
-                            .map(|v| v.as_deref())
-                            .flatten()
+        for location in data.location.into_vec() {
+            match location.schema_type {
+                SchemaOrgLocationType::Place => {
+                    opp.exterior.location_type = common::model::opportunity::LocationType::At;
+                    opp.exterior.location_name = location.name;
+
+                    match (location.geo, location.address) {
+                        (Some(geo), SchemaOrgAddress::Postal(postal)) => {
+                            // Already geocoded, so there's no need to look it up
+                            opp.exterior.location_point = Some(
+                                json!({"type": "Point", "coordinates": [geo.longitude, geo.latitude]}),
+                            );
+                            opp.exterior.address_street = postal.street_address;
+                            opp.exterior.address_city = postal.address_locality;
+                            opp.exterior.address_state = postal.address_region;
+                            opp.exterior.address_zip = postal.postal_code;
+                            opp.exterior.address_country = postal.address_country;
+                        }
+                        (_, address) => self.locate(&mut opp, &address.text(), cache),
                     }
-                } {
-                    println!(
-                        "Location: {}, {}",
-                        loc.geometry.longitude, loc.geometry.latitude
-                    );
-                    opp.exterior.location_point = Some(
-                        json!({"type": "Point", "coordinates": [loc.geometry.longitude, loc.geometry.latitude]}),
-                    );
-                    opp.exterior.address_street = loc
-                        .formatted
-                        .split_once(',')
-                        .unwrap_or(("", ""))
-                        .0
-                        .to_owned();
-                    opp.exterior.address_city = loc
-                        .components
-                        .city
-                        .clone()
-                        .or_else(|| loc.components.town.clone())
-                        .unwrap_or_default();
-                    opp.exterior.address_state = loc.components.state.clone().unwrap_or_default();
-                    opp.exterior.address_country = loc.components.country_code.clone();
-                    opp.exterior.address_zip = loc.components.postcode.clone().unwrap_or_default();
+                }
+                SchemaOrgLocationType::VirtualLocation => {
+                    opp.exterior.is_online = true;
                 }
             }
-        };
+        }
 
         match data.event_attendance_mode {
             SchemaOrgEventAttendanceMode::Online => {
@@ -255,42 +401,13 @@ where
             }
         }
 
-        if let Some(tz) = &self.1.timezone {
-            opp.exterior.start_datetimes = vec![tz
-                .from_local_datetime(
-                    &data
-                        .start_date
-                        .parse::<chrono::NaiveDate>()?
-                        .and_hms_opt(0, 0, 0)
-                        .expect("00:00:00 should always be valid"),
-                )
-                .earliest()
-                .ok_or_else(|| {
-                    LoggedError::new(LoggedErrorLevel::Error, "Unable to determine start date")
-                })?
-                .to_fixed_offset()];
-            opp.exterior.end_datetimes = vec![tz
-                .from_local_datetime(
-                    &data
-                        .end_date
-                        .parse::<chrono::NaiveDate>()?
-                        .and_hms_opt(23, 59, 59)
-                        .expect("11:59:59 should always be valid"),
-                )
-                .earliest()
-                .ok_or_else(|| {
-                    LoggedError::new(LoggedErrorLevel::Error, "Unable to determine end date")
-                })?
-                .to_fixed_offset()];
-        } else {
-            return Err(LoggedError::new(
-                LoggedErrorLevel::Debug,
-                "Partner time zone is not set, so the start and end datetimes would be ambiguous",
-            ));
-        }
+        opp.exterior.start_datetimes = vec![self.parse_date(&data.start_date, 0, 0, 0)?];
+        opp.exterior.end_datetimes = vec![self.parse_date(&data.end_date, 23, 59, 59)?];
 
         opp.exterior.partner = self.1.partner;
 
+        opp.exterior.parent = self.1.parent;
+
         opp.exterior.partner_name = self.1.partner_name.clone();
 
         opp.exterior.partner_website = self.1.partner_website.clone();
@@ -303,6 +420,8 @@ where
 
         opp.exterior.opp_topics = self.1.topics.clone();
 
+        opp.exterior.accessibility = self.1.accessibility.clone();
+
         opp.interior.withdrawn = match data.event_status {
             SchemaOrgEventStatus::Cancelled => true,
             SchemaOrgEventStatus::MovedOnline => false,
@@ -352,3 +471,122 @@ where
         self.1.load_partner(db).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::model::opportunity::{export, Cost, LocationType, OpportunityExterior, Topic};
+
+    fn partner_info() -> PartnerInfo<chrono_tz::Tz> {
+        PartnerInfo {
+            partner: Uuid::nil(),
+            partner_name: "Aggregator".to_string(),
+            partner_website: None,
+            partner_logo_url: None,
+            domain: Default::default(),
+            descriptor: Vec::new(),
+            topics: vec![Topic::AstronomyAndSpace],
+            accessibility: Vec::new(),
+            flags: Vec::new(),
+            address: None,
+            timezone: Some(chrono_tz::America::New_York),
+            parent: None,
+        }
+    }
+
+    fn reimport(exported: &[OpportunityExterior]) -> Vec<Opportunity> {
+        let structure = LdJson("@graph".to_string(), partner_info());
+
+        match structure.interpret(export::json_ld_graph(exported)) {
+            OneOrMany::Many(results) => results.into_iter().map(|r| r.unwrap()).collect(),
+            OneOrMany::One(result) => vec![result.unwrap()],
+        }
+    }
+
+    fn star_party() -> OpportunityExterior {
+        OpportunityExterior {
+            uid: Uuid::new_v4(),
+            slug: "star-party".to_string(),
+            title: "Star Party & Telescope Night".to_string(),
+            description: "Bring a blanket".to_string(),
+            image_url: "https://example.org/stars.jpg".to_string(),
+            organization_name: "Springfield Astronomers".to_string(),
+            organization_website: Some("https://example.org/".to_string()),
+            start_datetimes: vec![
+                DateTime::parse_from_rfc3339("2026-10-20T19:00:00-04:00").unwrap()
+            ],
+            end_datetimes: vec![DateTime::parse_from_rfc3339("2026-10-20T22:00:00-04:00").unwrap()],
+            cost: Cost::Free,
+            location_type: LocationType::At,
+            location_name: "Riverside Park".to_string(),
+            location_point: Some(json!({"type": "Point", "coordinates": [-72.5, 42.1]})),
+            address_street: "1 River Rd".to_string(),
+            address_city: "Springfield".to_string(),
+            address_state: "MA".to_string(),
+            address_zip: "01101".to_string(),
+            address_country: "us".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reimports_exported_event() {
+        let original = star_party();
+        let imported = reimport(std::slice::from_ref(&original)).remove(0).exterior;
+
+        assert_eq!(imported.title, original.title);
+        assert_eq!(imported.description, original.description);
+        assert_eq!(imported.image_url, original.image_url);
+        assert_eq!(
+            imported.partner_opp_url.as_deref(),
+            Some("https://sciencenearme.org/star-party")
+        );
+        assert_eq!(imported.start_datetimes, original.start_datetimes);
+        assert_eq!(imported.end_datetimes, original.end_datetimes);
+        assert_eq!(imported.cost, Cost::Free);
+        assert_eq!(imported.organization_name, original.organization_name);
+        assert_eq!(imported.organization_website, original.organization_website);
+        assert_eq!(imported.location_type, LocationType::At);
+        assert_eq!(imported.location_name, original.location_name);
+        assert_eq!(imported.location_point, original.location_point);
+        assert_eq!(imported.address_street, original.address_street);
+        assert_eq!(imported.address_city, original.address_city);
+        assert_eq!(imported.address_state, original.address_state);
+        assert_eq!(imported.address_zip, original.address_zip);
+        assert_eq!(imported.address_country, original.address_country);
+        assert!(!imported.is_online);
+    }
+
+    #[test]
+    fn reimports_online_and_hybrid_events() {
+        let online = OpportunityExterior {
+            slug: "webinar".to_string(),
+            is_online: true,
+            location_type: LocationType::Any,
+            location_point: None,
+            cost: Cost::Cost,
+            ..star_party()
+        };
+
+        let hybrid = OpportunityExterior {
+            slug: "hybrid".to_string(),
+            is_online: true,
+            ..star_party()
+        };
+
+        let imported = reimport(&[online, hybrid]);
+
+        assert_eq!(imported.len(), 2);
+
+        assert!(imported[0].exterior.is_online);
+        assert_eq!(imported[0].exterior.location_type, LocationType::Any);
+        assert_eq!(imported[0].exterior.cost, Cost::Cost);
+
+        assert!(imported[1].exterior.is_online);
+        assert_eq!(imported[1].exterior.location_type, LocationType::At);
+        assert_eq!(
+            imported[1].exterior.location_point,
+            star_party().location_point
+        );
+    }
+}
@@ -110,6 +110,8 @@ where
 
                             opp.exterior.partner = self.0.partner.clone();
 
+                            opp.exterior.parent = self.0.parent;
+
                             opp.exterior.partner_name = self.0.partner_name.clone();
 
                             opp.exterior.partner_website = self.0.partner_website.clone();
@@ -213,6 +215,8 @@ where
 
                             opp.exterior.opp_descriptor = self.0.descriptor.clone();
 
+                            opp.exterior.accessibility = self.0.accessibility.clone();
+
                             opp.exterior.min_age = 0;
 
                             opp.exterior.max_age = 999;
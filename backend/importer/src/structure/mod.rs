@@ -57,9 +57,13 @@ pub struct PartnerInfo<Tz: TimeZone> {
     pub domain: common::model::opportunity::Domain,
     pub descriptor: Vec<common::model::opportunity::Descriptor>,
     pub topics: Vec<common::model::opportunity::Topic>,
+    pub accessibility: Vec<common::model::opportunity::Accessibility>,
     pub flags: Vec<PartnerFlag>,
     pub address: Option<PartnerAddress>,
     pub timezone: Option<Tz>,
+    /// The umbrella opportunity, such as a festival, which every
+    /// imported opportunity is one of the events of
+    pub parent: Option<Uuid>,
 }
 
 impl<Tz: TimeZone> PartnerInfo<Tz> {
@@ -129,6 +129,8 @@ fn interpret_one<Tz: chrono::TimeZone + std::fmt::Debug>(
 
     opp.exterior.partner = partner.partner.clone();
 
+    opp.exterior.parent = partner.parent;
+
     opp.exterior.partner_name = partner.partner_name.clone();
 
     opp.exterior.partner_website = partner.partner_website.clone();
@@ -3,7 +3,7 @@ use std::{collections::BTreeMap, fmt::Debug, time::Duration};
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use common::model::{
-    opportunity::{Cost, EntityType, LocationType, OpportunityImportRecord},
+    opportunity::{Cost, EntityType, LocationType, OpportunityImportRecord, RevisionAuthor},
     Opportunity, Partner,
 };
 use scraper::{ElementRef, Html, Selector};
@@ -398,6 +398,7 @@ impl<Tz: TimeZone + Debug + Sync> Importer for Page<Tz> {
                 opp.exterior.entity_type = EntityType::Opportunity;
                 opp.exterior.pes_domain = self.info.domain.clone();
                 opp.exterior.opp_descriptor = self.info.descriptor.clone();
+                opp.exterior.accessibility = self.info.accessibility.clone();
                 opp.exterior.cost = Cost::Unknown;
                 opp.exterior.organization_type = Default::default();
                 opp.exterior.languages = vec!["en".to_string()];
@@ -470,7 +471,12 @@ impl<Tz: TimeZone + Debug + Sync> Importer for Page<Tz> {
 
             opp.set_id_if_necessary(&db).await?;
             opp.interior.accepted = if created { Some(true) } else { None };
-            opp.store(&db).await?;
+            opp.store_by(
+                &db,
+                &RevisionAuthor::Importer(opp.exterior.partner),
+                "importer",
+            )
+            .await?;
             OpportunityImportRecord::store(
                 &db,
                 &opp.exterior.partner,
@@ -18,8 +18,9 @@ use uuid::Uuid;
 
 use common::{
     model::{
-        opportunity::{OpportunityQuery, OpportunityQueryOrdering},
-        Opportunity, Pagination, Person,
+        geojson::{FeatureCollection, GeoJSON},
+        opportunity::{OpportunityQuery, OpportunityQueryOrdering, RevisionAuthor},
+        Opportunity, Pagination, Person, Region,
     },
     Database,
 };
@@ -355,7 +356,7 @@ async fn update_opportunities<F: Fn(&mut Opportunity) -> Result<(), DynError>>(
             &state.db,
             &state.opportunity_query,
             OpportunityQueryOrdering::Native,
-            pagination,
+            pagination.clone(),
         )
         .await?;
 
@@ -365,7 +366,8 @@ async fn update_opportunities<F: Fn(&mut Opportunity) -> Result<(), DynError>>(
 
         for mut opp in opps {
             update(&mut opp)?;
-            opp.store(&state.db).await?;
+            opp.store_by(&state.db, &RevisionAuthor::System, "toolkit")
+                .await?;
         }
 
         if let Pagination::Page { index, size } = pagination {
@@ -436,7 +438,7 @@ async fn refresh_geo_opportunities(state: &mut State, _args: Vec<String>) -> Res
             &state.db,
             &state.opportunity_query,
             OpportunityQueryOrdering::Native,
-            pagination,
+            pagination.clone(),
         )
         .await?;
 
@@ -475,7 +477,8 @@ async fn refresh_geo_opportunities(state: &mut State, _args: Vec<String>) -> Res
                         "coordinates": [lon, lat]
                     }));
                     opp.exterior.location_polygon = geo.geojson;
-                    opp.store(&state.db).await?;
+                    opp.store_by(&state.db, &RevisionAuthor::System, "toolkit")
+                        .await?;
                     success = true;
                 }
             }
@@ -493,7 +496,8 @@ async fn refresh_geo_opportunities(state: &mut State, _args: Vec<String>) -> Res
                     "type": "Point",
                     "coordinates": [geo.geometry.longitude, geo.geometry.latitude]
                 }));
-                opp.store(&state.db).await?;
+                opp.store_by(&state.db, &RevisionAuthor::System, "toolkit")
+                    .await?;
             }
         }
 
@@ -1115,6 +1119,61 @@ enum Action {
     Joins,
     Opportunities,
     GenerateOppsRegionalOverview,
+    RefreshOccurrences,
+    /// Load regions from a GeoJSON file or a zipped shapefile
+    ImportRegions {
+        path: std::path::PathBuf,
+        /// Region names, with {property} replaced by that property of
+        /// each feature
+        #[clap(long, default_value = "{name}")]
+        name: String,
+        /// Name of the region the imported regions are within
+        #[clap(long)]
+        parent: Option<String>,
+    },
+}
+
+async fn import_regions(
+    state: &mut State,
+    path: &std::path::Path,
+    name: &str,
+    parent: Option<&str>,
+) -> Result<(), DynError> {
+    let parent = match parent {
+        Some(parent_name) => Some(
+            Region::load_by_name(&state.db, parent_name)
+                .await?
+                .and_then(|region| region.id)
+                .ok_or_else(|| format!("No region named {}", parent_name))?,
+        ),
+        None => None,
+    };
+
+    let is_zip = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("zip"))
+        .unwrap_or(false);
+
+    let features = if is_zip {
+        common::shapefile::read_zip(path)?
+    } else {
+        match serde_json::from_slice(&std::fs::read(path)?)? {
+            GeoJSON::FeatureCollection(collection) => collection,
+            GeoJSON::Feature(feature) => FeatureCollection {
+                features: vec![feature],
+            },
+            _ => return Err("Expected a GeoJSON Feature or FeatureCollection".into()),
+        }
+    };
+
+    let import = Region::import(&state.db, features, name, parent).await?;
+
+    println!(
+        "Created {} regions, updated {}, skipped {}",
+        import.created, import.updated, import.skipped
+    );
+
+    Ok(())
 }
 
 #[derive(Parser, Debug)]
@@ -1142,6 +1201,13 @@ async fn main() -> Result<(), DynError> {
         Action::GenerateOppsRegionalOverview => {
             opps_regional_overview_calc(state.db.clone()).await?;
         }
+        Action::RefreshOccurrences => {
+            let count = Opportunity::refresh_occurrences(&state.db).await?;
+            println!("Refreshed occurrences for {} opportunities", count);
+        }
+        Action::ImportRegions { path, name, parent } => {
+            import_regions(&mut state, &path, &name, parent.as_deref()).await?;
+        }
         Action::Shell => run_shell(state).await?,
     }
 
@@ -1,4 +1,4 @@
-use common::{model, Database, INTERNAL_UID};
+use common::{model, Database, ToFixedOffset, INTERNAL_UID};
 #[cfg(not(debug_assertions))]
 use http_types::headers::HeaderValue;
 use sqlx::postgres::PgPoolOptions;
@@ -94,6 +94,76 @@ async fn initialize(db: &Database) -> tide::Result {
     Ok("initialized".into())
 }
 
+/// How often to check for opportunities whose publish or unpublish
+/// time has arrived
+const SCHEDULE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+async fn notify_scheduled_transition(
+    db: &Database,
+    transition: &model::opportunity::ScheduledTransition,
+) -> tide::Result<()> {
+    let opp = model::opportunity::Opportunity::load_by_uid(db, &transition.uid).await?;
+    ui::moderation::notify_transition(db, &opp, None, transition.from, "").await
+}
+
+async fn run_publication_schedule(db: Database) {
+    loop {
+        let now = chrono::Utc::now().to_fixed_offset();
+
+        match model::opportunity::Opportunity::apply_publication_schedule(&db, &now).await {
+            Ok(transitions) => {
+                for transition in transitions {
+                    log::info!(
+                        "Scheduled transition of {} from {:?} to {:?}",
+                        transition.slug,
+                        transition.from,
+                        transition.to
+                    );
+                    common::log(None, "opportunity-schedule", &transition);
+
+                    if let Err(err) = notify_scheduled_transition(&db, &transition).await {
+                        log::error!("Unable to send notice of scheduled transition: {}", err);
+                    }
+                }
+            }
+            Err(err) => log::error!("Unable to apply publication schedule: {}", err),
+        }
+
+        async_std::task::sleep(SCHEDULE_INTERVAL).await;
+    }
+}
+
+/// How often to purge opportunities which have been in the trash for
+/// longer than the retention period
+const PURGE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+async fn run_trash_purge(db: Database) {
+    loop {
+        match model::opportunity::Opportunity::purge_trash(&db).await {
+            Ok(0) => {}
+            Ok(count) => log::info!("Purged {} opportunities from the trash", count),
+            Err(err) => log::error!("Unable to purge the trash: {}", err),
+        }
+
+        async_std::task::sleep(PURGE_INTERVAL).await;
+    }
+}
+
+/// How often to roll the occurrences of recurring opportunities
+/// forward to the materialization horizon
+const OCCURRENCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+async fn run_occurrence_refresh(db: Database) {
+    loop {
+        match model::opportunity::Opportunity::refresh_occurrences(&db).await {
+            Ok(count) => log::info!("Refreshed occurrences for {} opportunities", count),
+            Err(err) => log::error!("Unable to refresh occurrences: {}", err),
+        }
+
+        async_std::task::sleep(OCCURRENCE_INTERVAL).await;
+    }
+}
+
 #[async_std::main]
 async fn main() -> tide::Result<()> {
     match sodiumoxide::init() {
@@ -120,21 +190,26 @@ async fn main() -> tide::Result<()> {
 
     initialize(&pool).await?;
 
+    async_std::task::spawn(run_publication_schedule(pool.clone()));
+    async_std::task::spawn(run_trash_purge(pool.clone()));
+    async_std::task::spawn(run_occurrence_refresh(pool.clone()));
+
     let mut app = tide::with_state(pool);
 
     #[cfg(not(debug_assertions))]
     app.with(
         CorsMiddleware::new()
             .allow_methods(
-                "GET, POST, PUT, DELETE, OPTIONS"
+                "GET, POST, PUT, PATCH, DELETE, OPTIONS"
                     .parse::<HeaderValue>()
                     .unwrap(),
             )
             .allow_headers(
-                "Authorization, Content-Type"
+                "Authorization, Content-Type, If-Match"
                     .parse::<HeaderValue>()
                     .unwrap(),
             )
+            .expose_headers("ETag".parse::<HeaderValue>().unwrap())
             // This doesn't quite work as desired with credentialed javascript requestes
             //.allow_origin(Origin::Any)
             .allow_origin(Origin::List(vec![
@@ -172,6 +247,7 @@ async fn main() -> tide::Result<()> {
             .at("api/ui/", ui::routes)
             .at("api/docs/", |routes| {
                 routes
+                    .at("openapi.json", |r| r.get(v1::openapi::openapi_json))
                     .serve_dir("static/")
                     .expect("Unable to serve static docs dir")
             }),
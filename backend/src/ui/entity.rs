@@ -1,10 +1,13 @@
-use chrono::Utc;
+use chrono::{DateTime, FixedOffset, Utc};
 use common::{
     model::{
         involvement::{Involvement, Mode},
-        opportunity::{Opportunity, OpportunityQuery, OpportunityQueryOrdering, ReviewStatus},
+        opportunity::{
+            Opportunity, OpportunityQuery, OpportunityQueryOrdering, ReviewStatus, RevisionAuthor,
+            RsvpNotice,
+        },
         person::{LogEvent, LogIdentifier, Permission, PermitAction},
-        Pagination, Partner, Person,
+        Pagination,
     },
     Database, ToFixedOffset,
 };
@@ -17,8 +20,9 @@ use tide_fluent_routes::{
 };
 
 use crate::ui::okay_empty;
+use crate::v1::requested_language;
 
-use super::{okay, opportunity, request_person};
+use super::{moderation, okay, opportunity, request_person};
 
 pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
     routes.at(":slug", |r| {
@@ -28,6 +32,9 @@ pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
             .at("shared", |r| r.post(shared))
             .at("calendar", |r| r.post(calendar))
             .at("interest", |r| r.post(register_interest))
+            .at("rsvp", |r| {
+                r.get(get_rsvp).post(add_rsvp).delete(cancel_rsvp)
+            })
             .at("likes", |r| {
                 r.get(get_likes).post(add_like).delete(remove_like)
             })
@@ -42,6 +49,11 @@ pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
             .at("recommended", |r| r.get(recommended))
             .at("managers", |r| r.post(request_page_management))
             .at("status", |r| r.put(set_review_status))
+            .at("moderation", |r| {
+                r.get(moderation::thread)
+                    .post(moderation::add_comment)
+                    .at("assignee", |r| r.put(moderation::assign))
+            })
     })
 }
 
@@ -51,7 +63,15 @@ pub async fn entity(mut req: tide::Request<Database>) -> tide::Result {
     let slug = req.param("slug")?;
     let db = req.state();
 
-    let opp = Opportunity::load_by_slug_with_overlay(db, slug)
+    if let Some(canonical) = Opportunity::redirect_for_slug(db, slug).await? {
+        return Ok(tide::Redirect::permanent(format!(
+            "/api/ui/entity/{}",
+            urlencoding::encode(&canonical)
+        ))
+        .into());
+    }
+
+    let mut opp = Opportunity::load_by_slug_with_overlay(db, slug)
         .await
         .with_status(|| StatusCode::NotFound)?;
 
@@ -71,7 +91,9 @@ pub async fn entity(mut req: tide::Request<Database>) -> tide::Result {
     };
 
     if authorized != PermitAction::Nothing
-        || (opp.interior.accepted.unwrap_or(false) && !opp.interior.withdrawn)
+        || (opp.interior.accepted.unwrap_or(false)
+            && !opp.interior.withdrawn
+            && opp.interior.trashed_at.is_none())
     {
         common::log(
             person.as_ref().map(|p| &p.exterior.uid),
@@ -89,6 +111,14 @@ pub async fn entity(mut req: tide::Request<Database>) -> tide::Result {
                 .await?;
         }
 
+        // Editors see the original text, so that it's what they save
+        if authorized == PermitAction::Nothing {
+            if let Some(language) = requested_language(&req) {
+                opp.exterior
+                    .localize(&common::language_preferences(&language));
+            }
+        }
+
         okay(&opp.into_annotated_exterior(authorized))
     } else {
         Err(tide::Error::from_str(StatusCode::NotFound, "not found"))
@@ -499,9 +529,116 @@ pub async fn register_interest(mut req: tide::Request<Database>) -> tide::Result
     okay_empty()
 }
 
+pub async fn get_rsvp(mut req: tide::Request<Database>) -> tide::Result {
+    let person = request_person(&mut req).await?;
+    let db = req.state();
+
+    let opp = Opportunity::load_by_slug(db, req.param("slug")?).await?;
+
+    let mine = if let Some(person) = person {
+        opp.rsvps_for_person(db, &person).await?
+    } else {
+        Vec::new()
+    };
+
+    okay(&json!({
+        "capacity": opp.exterior.capacity,
+        "occurrences": opp.rsvp_availability(db).await?,
+        "mine": mine,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct RsvpForm {
+    occurrence: DateTime<FixedOffset>,
+}
+
+fn rsvp_error(err: common::model::Error) -> tide::Error {
+    match err {
+        common::model::Error::Value(field) if field == "capacity" => tide::Error::from_str(
+            StatusCode::BadRequest,
+            "this opportunity does not take registrations",
+        ),
+        common::model::Error::Value(field) if field == "occurrence" => tide::Error::from_str(
+            StatusCode::BadRequest,
+            "no upcoming occurrence starts at that time",
+        ),
+        common::model::Error::NoSuch(what) => {
+            tide::Error::from_str(StatusCode::NotFound, format!("no such {}", what))
+        }
+        err => err.into(),
+    }
+}
+
+pub async fn add_rsvp(mut req: tide::Request<Database>) -> tide::Result {
+    let slug = req.param("slug")?.to_string();
+    let person = request_person(&mut req)
+        .await?
+        .ok_or_else(|| tide::Error::from_str(400, "authentication required"))?;
+    let form: RsvpForm = req.body_json().await?;
+    let db = req.state();
+
+    let opp = Opportunity::load_by_slug(db, &slug).await?;
+
+    let registration = opp
+        .rsvp(db, &person, &form.occurrence)
+        .await
+        .map_err(rsvp_error)?;
+    let rsvp = registration.rsvp;
+
+    if registration.changed {
+        rsvp.notify(db, &opp, RsvpNotice::for_status(rsvp.status))
+            .await?;
+    }
+
+    common::log(
+        Some(&person.exterior.uid),
+        "ui-rsvp",
+        &json!({ "opportunity": slug, "occurrence": form.occurrence, "status": rsvp.status }),
+    );
+
+    okay(&rsvp)
+}
+
+pub async fn cancel_rsvp(mut req: tide::Request<Database>) -> tide::Result {
+    let slug = req.param("slug")?.to_string();
+    let person = request_person(&mut req)
+        .await?
+        .ok_or_else(|| tide::Error::from_str(400, "authentication required"))?;
+    let form: RsvpForm = req.body_json().await?;
+    let db = req.state();
+
+    let opp = Opportunity::load_by_slug(db, &slug).await?;
+
+    let cancellation = opp
+        .cancel_rsvp(db, &person, &form.occurrence)
+        .await
+        .map_err(rsvp_error)?;
+
+    cancellation
+        .cancelled
+        .notify(db, &opp, RsvpNotice::Cancelled)
+        .await?;
+
+    for promoted in cancellation.promoted.iter() {
+        promoted.notify(db, &opp, RsvpNotice::Promoted).await?;
+    }
+
+    common::log(
+        Some(&person.exterior.uid),
+        "ui-cancel-rsvp",
+        &json!({ "opportunity": slug, "occurrence": form.occurrence, "promoted": cancellation.promoted.len() }),
+    );
+
+    okay(&cancellation.cancelled)
+}
+
 #[derive(Debug, Deserialize)]
 struct StatusForm {
     status: ReviewStatus,
+    /// Explanation for the submitter, recorded in the moderation thread
+    #[serde(default)]
+    note: String,
 }
 
 pub async fn set_review_status(mut req: tide::Request<Database>) -> tide::Result {
@@ -523,67 +660,21 @@ pub async fn set_review_status(mut req: tide::Request<Database>) -> tide::Result
             .check_authorization(req.state(), &opp, PermitAction::Manage)
             .await?
     {
+        let from = opp.interior.review_status;
         opp.interior.review_status = form.status;
-        opp.store(req.state()).await?;
-
-        if let ReviewStatus::Publish = opp.interior.review_status {
-            if let Some(person_uid) = opp.interior.submitted_by {
-                let submitted_by = Person::load_by_uid(req.state(), &person_uid).await?;
-                let partner = Partner::load_by_uid(req.state(), &opp.exterior.partner).await?;
-
-                let template = common::emails::EmailMessage::load_or_default(
-                    req.state(),
-                    "opportunity-approved",
-                    "Published on Science Near Me: {title}",
-                    r#"
-<p>The opportunity <strong>{title}</strong> has been published on {partner_name} and Science Near Me.</p>
-<p>You can view the opportunity on {partner_name}'s web site or at <a href="https://sciencenearme.org/{opp_slug}">Science Near Me</a>.</p>
-"#,
-                ).await;
-
-                let msg = template.materialize(vec![
-                    ("title", &opp.exterior.title),
-                    ("partner_name", &partner.exterior.name),
-                    ("opp_slug", &opp.exterior.slug),
-                ]);
-
-                common::emails::send_message(submitted_by.interior.email, &msg).await
-            }
-        } else if let ReviewStatus::Draft = opp.interior.review_status {
-            if let Some(person_uid) = opp.interior.submitted_by {
-                let submitted_by = Person::load_by_uid(req.state(), &person_uid).await?;
-
-                let template = common::emails::EmailMessage::load_or_default(
-                    req.state(),
-                    "opportunity-returned-to-draft",
-                    "Revisions needed on Science Near Me: {title}",
-                    r#"
-<p>The opportunity <strong>{title}</strong> has been returned to draft status, as it still requires work in order to be ready for publication.</p>
-"#,
-                ).await;
-
-                let msg = template.materialize(vec![("title", &opp.exterior.title)]);
-
-                common::emails::send_message(submitted_by.interior.email, &msg).await
-            }
-        } else if let ReviewStatus::Reject = opp.interior.review_status {
-            if let Some(person_uid) = opp.interior.submitted_by {
-                let submitted_by = Person::load_by_uid(req.state(), &person_uid).await?;
-
-                let template = common::emails::EmailMessage::load_or_default(
-                    req.state(),
-                    "opportunity-rejected",
-                    "Not published on Science Near Me: {title}",
-                    r#"
-<p>We're sorry, but the opportunity <strong>{title}</strong> has been rejected for publication.</p>
-"#,
-                )
-                .await;
-
-                let msg = template.materialize(vec![("title", &opp.exterior.title)]);
-
-                common::emails::send_message(submitted_by.interior.email, &msg).await
-            }
+        opp.store_by(
+            req.state(),
+            &RevisionAuthor::Person(person.exterior.uid),
+            "ui",
+        )
+        .await?;
+
+        if from != opp.interior.review_status {
+            opp.record_review_transition(req.state(), Some(&person.exterior.uid), from, &form.note)
+                .await?;
+
+            moderation::notify_transition(req.state(), &opp, Some(&person), from, &form.note)
+                .await?;
         }
 
         okay_empty()
@@ -2,17 +2,21 @@ use chrono::{DateTime, FixedOffset};
 use common::{
     geo,
     model::{
+        geojson::{BoundingBox, Geometry},
         opportunity::{
-            Cost, Descriptor, EntityType, OpportunityQuery, OpportunityQueryOrdering,
-            OpportunityQueryPhysical, OpportunityQueryTemporal, Topic, VenueType,
+            Accessibility, Cost, Descriptor, EntityType, OpportunityQuery,
+            OpportunityQueryOrdering, OpportunityQueryPhysical, OpportunityQueryTemporal,
+            TileCoordinates, Topic, VenueType,
         },
         person::Permission,
+        serde_helpers::{deserialize_bbox, deserialize_geometry},
         Opportunity, OpportunityExterior, Pagination, Person, SelectOption,
     },
     Database,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 use tide::StatusCode;
 use tide_fluent_routes::{
     routebuilder::{RouteBuilder, RouteBuilderExt},
@@ -21,6 +25,7 @@ use tide_fluent_routes::{
 use uuid::Uuid;
 
 use crate::ui::{okay, request_person};
+use crate::v1::requested_language;
 
 const FIFTY_MILES: f32 = 80467.0;
 
@@ -35,6 +40,7 @@ pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
         .at("states", |r| r.get(states))
         .at("random-categories", |r| r.get(random_categories))
         .at("search", |r| r.get(search))
+        .at("tiles/:z/:x/:y", |r| r.get(tile))
         .at("geo", |r| r.post(geo))
         .at("geom", |r| r.post(geom))
 }
@@ -250,6 +256,7 @@ struct SearchQuery {
     pub proximity: Option<f32>,
     pub online: Option<bool>,
     pub text: Option<String>,
+    pub language: Option<String>,
     pub include_tags: Option<String>,
     pub beginning: Option<DateTime<FixedOffset>>,
     pub ending: Option<DateTime<FixedOffset>>,
@@ -261,6 +268,7 @@ struct SearchQuery {
     pub descriptors: Option<Vec<Descriptor>>,
     pub cost: Option<Cost>,
     pub venue_type: Option<VenueType>,
+    pub accessibility: Option<Vec<Accessibility>>,
     pub host: Option<String>,
     pub partner: Option<Uuid>,
     pub include_partners: Option<String>,
@@ -268,6 +276,9 @@ struct SearchQuery {
     pub mine: Option<bool>,
     pub sort: Option<OpportunityQueryOrdering>,
     pub page: Option<u32>,
+    /// Switches to keyset pagination, continuing from a cursor
+    /// returned with an earlier page. Empty to start at the beginning.
+    pub cursor: Option<String>,
     pub per_page: Option<u32>,
     pub saved: Option<bool>,
     pub participated: Option<bool>,
@@ -279,51 +290,20 @@ struct SearchQuery {
     pub adults_only: Option<bool>,
     pub year: Option<u32>,
     pub month: Option<u8>,
+    pub open_at: Option<DateTime<FixedOffset>>,
+    pub parent: Option<Uuid>,
+    pub hide_children: Option<bool>,
     pub refs: Option<bool>,
+    pub facets: Option<bool>,
+    #[serde(default, deserialize_with = "deserialize_bbox")]
+    pub within_bbox: Option<BoundingBox>,
+    #[serde(default, deserialize_with = "deserialize_geometry")]
+    pub within_polygon: Option<Geometry>,
 }
 
-pub async fn search(mut req: tide::Request<Database>) -> tide::Result {
-    let person = request_person(&mut req).await?;
-    let person_uid = person.as_ref().map(|p| p.exterior.uid.clone());
-
-    let db = req.state();
-
-    let search: SearchQuery = req.query()?;
-
-    if let (Some(person_id), Some(longitude), Some(latitude)) = (
-        person.as_ref().and_then(|p| p.id.clone()),
-        &search.longitude,
-        &search.latitude,
-    ) {
-        // This query is constructed like this so as to be able to
-        // detect changes, without creating a race condition
-        let changed_location = sqlx::query_scalar!(
-            r#"
-UPDATE c_person post
-SET
-  "home_location" = COALESCE(post."home_location", ST_SetSRID(ST_Point($2, $3), 4326)),
-  "last_location" = ST_SetSRID(ST_Point($2, $3), 4326)
-FROM (SELECT "id", "home_location", "last_location" FROM c_person WHERE id = $1 FOR UPDATE) pre
-WHERE post.id = pre.id
-RETURNING coalesce(pre."home_location" != post."home_location", true) as "changed!"
-"#,
-            person_id,
-            *longitude as f64,
-            *latitude as f64
-        )
-        .fetch_one(db)
-        .await?;
-
-        if changed_location {
-            async_std::task::spawn({
-                let db = db.clone();
-                async move {
-                    let _ = Person::update_state_and_metro_by_id(&db, person_id).await;
-                }
-            });
-        }
-    }
-
+/// Translates the finder's search parameters into an opportunity
+/// query, applying the defaults and permissions of the searcher
+fn opportunity_query(search: &SearchQuery, person: Option<&Person>) -> OpportunityQuery {
     let mut query = OpportunityQuery::default();
 
     query.entity_type = Some(vec![
@@ -332,22 +312,29 @@ RETURNING coalesce(pre."home_location" != post."home_location", true) as "change
         EntityType::Unspecified,
     ]);
 
-    query.text = search.text;
+    query.text = search.text.clone();
+    query.text_language = search.language.clone();
     query.beginning = search.beginning;
     query.ending = search.ending;
     query.min_age = search.min_age;
     query.max_age = search.max_age;
     query.kids_only = search.kids_only;
     query.adults_only = search.adults_only;
-    query.descriptors = search.descriptors;
+    query.descriptors = search.descriptors.clone();
     query.cost = search.cost;
-    query.topics = search.topics;
+    query.topics = search.topics.clone();
     query.venue_type = search.venue_type;
-    query.host = search.host;
+    query.accessibility = search.accessibility.clone();
+    query.host = search.host.clone();
     query.partner = search.partner;
     query.prefer_partner = search.prefer_partner;
     query.current = Some(search.current.unwrap_or(true));
     query.temporal = search.temporal;
+    query.within_bbox = search.within_bbox;
+    query.within_polygon = search.within_polygon.clone();
+    query.open_at = search.open_at;
+    query.parent = search.parent;
+    query.hide_children = search.hide_children;
 
     query.calendar = match (search.year, search.month) {
         (Some(y), Some(m)) => Some((y, m)),
@@ -360,11 +347,11 @@ RETURNING coalesce(pre."home_location" != post."home_location", true) as "change
         None
     };
 
-    if let Some(combined) = search.include_tags {
+    if let Some(combined) = &search.include_tags {
         query.include_tags = Some(combined.split(',').map(str::to_owned).collect());
     }
 
-    if let Some(combined) = search.include_partners {
+    if let Some(combined) = &search.include_partners {
         query.include_partners = Some(combined.split(',').flat_map(Uuid::parse_str).collect());
     }
 
@@ -410,20 +397,6 @@ RETURNING coalesce(pre."home_location" != post."home_location", true) as "change
                 query.withdrawn = Some(false);
             }
         }
-
-        if let Some(text) = &query.text {
-            if !text.is_empty() && !text.trim().is_empty() {
-                if let Some(id) = p.id {
-                    sqlx::query!(
-                        r#"INSERT INTO "c_person_searches" ("person_id", "text") VALUES ($1, $2)"#,
-                        id,
-                        text
-                    )
-                    .execute(db)
-                    .await?;
-                }
-            }
-        }
     } else {
         query.accepted = Some(true);
         query.withdrawn = Some(false);
@@ -456,7 +429,108 @@ RETURNING coalesce(pre."home_location" != post."home_location", true) as "change
         (None, None) => {}
     }
 
-    let pagination = if let Some(page) = search.page {
+    query
+}
+
+/// Translates a partner's default finder query, overridden by the
+/// parameters in `overrides`, into an anonymous opportunity query. A
+/// parameter in `overrides` replaces every default parameter with the
+/// same name, including all the elements of a list.
+pub(crate) fn partner_query(
+    defaults: &str,
+    overrides: &str,
+) -> Result<OpportunityQuery, serde_qs::Error> {
+    fn name(pair: &str) -> String {
+        let key = pair.split('=').next().unwrap_or_default();
+        let key = key.replace("%5B", "[").replace("%5b", "[");
+        key.split('[').next().unwrap_or_default().to_string()
+    }
+
+    let overridden: Vec<String> = overrides
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(name)
+        .collect();
+
+    let combined: Vec<&str> = defaults
+        .trim_start_matches('?')
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !overridden.contains(&name(pair)))
+        .chain(overrides.split('&').filter(|pair| !pair.is_empty()))
+        .collect();
+
+    let search: SearchQuery =
+        serde_qs::Config::new(5, false).deserialize_str(&combined.join("&"))?;
+
+    let mut query = opportunity_query(&search, None);
+    query.sort = search.sort;
+
+    Ok(query)
+}
+
+pub async fn search(mut req: tide::Request<Database>) -> tide::Result {
+    let person = request_person(&mut req).await?;
+    let person_uid = person.as_ref().map(|p| p.exterior.uid.clone());
+
+    let db = req.state();
+
+    let search: SearchQuery = req.query()?;
+
+    if let (Some(person_id), Some(longitude), Some(latitude)) = (
+        person.as_ref().and_then(|p| p.id.clone()),
+        &search.longitude,
+        &search.latitude,
+    ) {
+        // This query is constructed like this so as to be able to
+        // detect changes, without creating a race condition
+        let changed_location = sqlx::query_scalar!(
+            r#"
+UPDATE c_person post
+SET
+  "home_location" = COALESCE(post."home_location", ST_SetSRID(ST_Point($2, $3), 4326)),
+  "last_location" = ST_SetSRID(ST_Point($2, $3), 4326)
+FROM (SELECT "id", "home_location", "last_location" FROM c_person WHERE id = $1 FOR UPDATE) pre
+WHERE post.id = pre.id
+RETURNING coalesce(pre."home_location" != post."home_location", true) as "changed!"
+"#,
+            person_id,
+            *longitude as f64,
+            *latitude as f64
+        )
+        .fetch_one(db)
+        .await?;
+
+        if changed_location {
+            async_std::task::spawn({
+                let db = db.clone();
+                async move {
+                    let _ = Person::update_state_and_metro_by_id(&db, person_id).await;
+                }
+            });
+        }
+    }
+
+    let mut query = opportunity_query(&search, person.as_ref());
+    query.language = requested_language(&req);
+
+    if let (Some(id), Some(text)) = (person.as_ref().and_then(|p| p.id), &query.text) {
+        if !text.trim().is_empty() {
+            sqlx::query!(
+                r#"INSERT INTO "c_person_searches" ("person_id", "text") VALUES ($1, $2)"#,
+                id,
+                text
+            )
+            .execute(db)
+            .await?;
+        }
+    }
+
+    let pagination = if let Some(cursor) = &search.cursor {
+        Pagination::Cursor {
+            cursor: Some(cursor.clone()).filter(|c| !c.is_empty()),
+            size: search.per_page.unwrap_or(10),
+        }
+    } else if let Some(page) = search.page {
         Pagination::Page {
             index: page,
             size: search.per_page.unwrap_or(10).into(),
@@ -468,15 +542,69 @@ RETURNING coalesce(pre."home_location" != post."home_location", true) as "change
         }
     };
 
-    let matches: Vec<OpportunityExterior> =
-        Opportunity::load_matching(db, &query, search.sort.unwrap_or_default(), pagination)
+    let has_text = query
+        .text
+        .as_ref()
+        .map(|t| !t.trim().is_empty())
+        .unwrap_or(false);
+
+    // Searching for text implies that the best matches should come
+    // first, unless some other order was requested
+    let ordering = match search.sort {
+        Some(sort) => sort,
+        None if has_text => OpportunityQueryOrdering::Relevance,
+        None => OpportunityQueryOrdering::default(),
+    };
+
+    let preferences = query
+        .language
+        .as_deref()
+        .map(common::language_preferences)
+        .unwrap_or_default();
+
+    let (matches, cursors): (Vec<OpportunityExterior>, _) =
+        match Opportunity::load_matching_page(db, &query, ordering, pagination.clone()).await {
+            Ok((found, cursors)) => (
+                found
+                    .into_iter()
+                    .map(|m| {
+                        let mut exterior = m.exterior;
+                        exterior.localize(&preferences);
+                        exterior
+                    })
+                    .collect(),
+                cursors,
+            ),
+            Err(err @ (common::model::Error::OutOfBounds(_) | common::model::Error::Value(_))) => {
+                return Err(tide::Error::from_str(
+                    StatusCode::BadRequest,
+                    err.to_string(),
+                ))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+    let snippets = match (&query.text, has_text) {
+        (Some(text), true) => {
+            Opportunity::load_snippets(
+                db,
+                text,
+                query.text_language.as_deref(),
+                &matches.iter().map(|m| m.uid).collect::<Vec<_>>(),
+            )
             .await?
-            .into_iter()
-            .map(|m| m.exterior)
-            .collect();
+        }
+        _ => HashMap::new(),
+    };
 
     let total = Opportunity::count_matching(db, &query).await?;
 
+    let facets = if search.facets.unwrap_or(false) {
+        Some(Opportunity::facets(db, &query).await?)
+    } else {
+        None
+    };
+
     let (page_index, last_page, per_page) = pagination.expand(total);
 
     common::log(person_uid.as_ref(), "ui-search", &req.url().query());
@@ -488,8 +616,12 @@ RETURNING coalesce(pre."home_location" != post."home_location", true) as "change
                 "per_page": per_page,
                 "last_page": last_page,
                 "total": total,
+                "next": cursors.next,
+                "prev": cursors.prev,
             },
-            "matches": matches.into_iter().map(|x| x.into_reference()).collect::<Vec<_>>()
+            "matches": matches.into_iter().map(|x| x.into_reference()).collect::<Vec<_>>(),
+            "snippets": snippets,
+            "facets": facets,
         }))
     } else {
         okay(&json!({
@@ -498,12 +630,81 @@ RETURNING coalesce(pre."home_location" != post."home_location", true) as "change
                 "per_page": per_page,
                 "last_page": last_page,
                 "total": total,
+                "next": cursors.next,
+                "prev": cursors.prev,
             },
-            "matches": matches
+            "matches": matches,
+            "snippets": snippets,
+            "facets": facets,
         }))
     }
 }
 
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum TileFormat {
+    #[default]
+    Mvt,
+    GeoJson,
+}
+
+#[derive(Deserialize, Debug)]
+struct TileOptions {
+    pub format: Option<TileFormat>,
+}
+
+pub async fn tile(mut req: tide::Request<Database>) -> tide::Result {
+    let person = request_person(&mut req).await?;
+
+    let db = req.state();
+
+    let search: SearchQuery = req.query()?;
+    let options: TileOptions = req.query()?;
+
+    let coordinates = match TileCoordinates::new(
+        req.param("z")?.parse()?,
+        req.param("x")?.parse()?,
+        req.param("y")?.parse()?,
+    ) {
+        Ok(coordinates) => coordinates,
+        Err(err) => {
+            return Err(tide::Error::from_str(
+                StatusCode::BadRequest,
+                err.to_string(),
+            ))
+        }
+    };
+
+    let query = opportunity_query(&search, person.as_ref());
+
+    let result = match options.format.unwrap_or_default() {
+        TileFormat::Mvt => Opportunity::load_tile_mvt(db, &query, &coordinates)
+            .await
+            .map(|tile| {
+                tide::Response::builder(StatusCode::Ok)
+                    .content_type("application/vnd.mapbox-vector-tile")
+                    .body(tile)
+                    .build()
+            }),
+        TileFormat::GeoJson => Opportunity::load_tile_geojson(db, &query, &coordinates)
+            .await
+            .map(|collection| {
+                tide::Response::builder(StatusCode::Ok)
+                    .content_type("application/geo+json")
+                    .body(collection.to_string())
+                    .build()
+            }),
+    };
+
+    match result {
+        Ok(res) => Ok(res),
+        Err(err @ (common::model::Error::OutOfBounds(_) | common::model::Error::Value(_))) => Err(
+            tide::Error::from_str(StatusCode::BadRequest, err.to_string()),
+        ),
+        Err(err) => Err(err.into()),
+    }
+}
+
 pub async fn metros(req: tide::Request<Database>) -> tide::Result {
     let rows = sqlx::query!(r#"SELECT DISTINCT "state" AS "state!", "metro" AS "metro!" FROM c_person WHERE "state" IS NOT NULL AND "metro" IS NOT NULL ORDER BY "state", "metro""#).map(|row| (row.state, row.metro)).fetch_all(req.state()).await?;
     Ok(serde_json::to_string(&rows)?.into())
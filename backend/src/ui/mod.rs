@@ -4,6 +4,7 @@ pub mod entity;
 pub mod finder;
 pub mod invitation;
 pub mod misc;
+pub mod moderation;
 pub mod opportunity;
 pub mod organization;
 pub mod profile;
@@ -35,6 +36,7 @@ pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
         .at("opportunity/", opportunity::routes)
         .at("invitation/", invitation::routes)
         .at("misc/", misc::routes)
+        .at("moderation/", moderation::routes)
         .at("content", |r| r.get(content))
         .at("timezone", |r| r.get(timezone))
 }
@@ -0,0 +1,270 @@
+use common::{
+    model::{
+        opportunity::{ModerationNotice, Opportunity, ReviewStatus},
+        person::{Permission, PermitAction},
+        Partner, Person,
+    },
+    Database, ToFixedOffset,
+};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::json;
+use tide::{Status, StatusCode};
+use tide_fluent_routes::{
+    routebuilder::{RouteBuilder, RouteBuilderExt},
+    RouteSegment,
+};
+use uuid::Uuid;
+
+use super::{okay, okay_empty, request_person};
+
+/// Partners whose submissions are reviewed by Science Near Me staff
+/// rather than by the partner's own people
+static STAFF_REVIEWS: Lazy<[Uuid; 0]> = Lazy::new(|| {
+    [
+        // Alabama STEM Council
+        //Uuid::parse_str("b9224b48-dcc3-5153-9c31-7b53ff24a380").unwrap(),
+    ]
+});
+
+pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
+    routes.at("queue", |r| r.get(queue))
+}
+
+/// The people who should hear about an opportunity's review: the
+/// assigned reviewer if there is one, otherwise staff or the
+/// partner's authorized people
+async fn reviewers(
+    db: &Database,
+    partner: &Partner,
+    opp: &Opportunity,
+) -> Result<Vec<Person>, tide::Error> {
+    if let Some(assignee) = opp.moderation_assignee(db).await? {
+        return Ok(vec![Person::load_by_uid(db, &assignee).await?]);
+    }
+
+    let found = if STAFF_REVIEWS.contains(&partner.exterior.uid) {
+        Person::all_by_permission(db, &Permission::ManageOpportunities).await?
+    } else {
+        partner.load_authorized_persons(db).await?
+    };
+
+    Ok(found.into_iter().flatten().collect())
+}
+
+fn display_name(person: Option<&Person>) -> String {
+    person
+        .and_then(|p| p.exterior.username.clone())
+        .unwrap_or_else(|| "Science Near Me".to_string())
+}
+
+/// Emails the other party about a change of the opportunity's review
+/// status from `from`. Changes made by the submitter go to the
+/// reviewers, and all others go to the submitter.
+pub async fn notify_transition(
+    db: &Database,
+    opp: &Opportunity,
+    actor: Option<&Person>,
+    from: ReviewStatus,
+    note: &str,
+) -> Result<(), tide::Error> {
+    let partner = opp.load_partner(db).await?;
+
+    let by_submitter = match (actor, opp.interior.submitted_by) {
+        (Some(actor), Some(submitter)) => actor.exterior.uid == submitter,
+        _ => false,
+    };
+
+    let recipients = if by_submitter {
+        reviewers(db, &partner, opp).await?
+    } else if let Some(submitter) = opp.interior.submitted_by {
+        vec![Person::load_by_uid(db, &submitter).await?]
+    } else {
+        return Ok(());
+    };
+
+    let msg = ModerationNotice::for_transition(opp.interior.review_status, by_submitter)
+        .message(
+            db,
+            opp,
+            &partner.exterior.name,
+            &display_name(actor),
+            from,
+            note,
+        )
+        .await;
+
+    for recipient in recipients {
+        common::emails::send_message(recipient.interior.email, &msg).await;
+    }
+
+    Ok(())
+}
+
+/// Emails the other party about a new comment in the moderation
+/// thread
+async fn notify_comment(
+    db: &Database,
+    opp: &Opportunity,
+    author: &Person,
+    body: &str,
+) -> Result<(), tide::Error> {
+    let partner = opp.load_partner(db).await?;
+
+    let recipients = match opp.interior.submitted_by {
+        Some(submitter) if submitter != author.exterior.uid => {
+            vec![Person::load_by_uid(db, &submitter).await?]
+        }
+        _ => reviewers(db, &partner, opp)
+            .await?
+            .into_iter()
+            .filter(|p| p.exterior.uid != author.exterior.uid)
+            .collect(),
+    };
+
+    let msg = ModerationNotice::Comment
+        .message(
+            db,
+            opp,
+            &partner.exterior.name,
+            &display_name(Some(author)),
+            opp.interior.review_status,
+            body,
+        )
+        .await;
+
+    for recipient in recipients {
+        common::emails::send_message(recipient.interior.email, &msg).await;
+    }
+
+    Ok(())
+}
+
+async fn is_reviewer(db: &Database, person: &Person, opp: &Opportunity) -> tide::Result<bool> {
+    Ok(person.check_permission(&Permission::ManageOpportunities)
+        || person
+            .check_authorization(db, opp, PermitAction::Manage)
+            .await?)
+}
+
+/// Loads the opportunity named in the request and the person making
+/// it, who must be either its submitter or one of its reviewers
+async fn thread_participant(
+    req: &mut tide::Request<Database>,
+) -> tide::Result<(Opportunity, Person, bool)> {
+    let person = request_person(req)
+        .await?
+        .ok_or_else(|| tide::Error::from_str(StatusCode::Unauthorized, "Not authorized"))?;
+
+    let opp = Opportunity::load_by_slug(req.state(), req.param("slug")?)
+        .await
+        .with_status(|| StatusCode::NotFound)?;
+
+    let reviewer = is_reviewer(req.state(), &person, &opp).await?;
+
+    if !reviewer && opp.interior.submitted_by != Some(person.exterior.uid) {
+        return Err(tide::Error::from_str(
+            StatusCode::Forbidden,
+            "permission denied",
+        ));
+    }
+
+    Ok((opp, person, reviewer))
+}
+
+pub async fn thread(mut req: tide::Request<Database>) -> tide::Result {
+    let (opp, _, _) = thread_participant(&mut req).await?;
+    let db = req.state();
+
+    okay(&json!({
+        "review_status": opp.interior.review_status,
+        "submitted_by": opp.interior.submitted_by,
+        "assignee": opp.moderation_assignee(db).await?,
+        "comments": opp.moderation_thread(db).await?,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentForm {
+    body: String,
+}
+
+pub async fn add_comment(mut req: tide::Request<Database>) -> tide::Result {
+    let (opp, person, _) = thread_participant(&mut req).await?;
+    let form: CommentForm = req.body_json().await?;
+    let db = req.state();
+
+    opp.add_moderation_comment(db, &person.exterior.uid, &form.body)
+        .await
+        .with_status(|| StatusCode::BadRequest)?;
+
+    notify_comment(db, &opp, &person, &form.body).await?;
+
+    common::log(
+        Some(&person.exterior.uid),
+        "moderation-comment",
+        &json!({"opp": opp.exterior.uid}),
+    );
+
+    okay(&opp.moderation_thread(db).await?)
+}
+
+#[derive(Debug, Deserialize)]
+struct AssigneeForm {
+    assignee: Option<Uuid>,
+}
+
+pub async fn assign(mut req: tide::Request<Database>) -> tide::Result {
+    let (opp, person, reviewer) = thread_participant(&mut req).await?;
+
+    if !reviewer {
+        return Err(tide::Error::from_str(
+            StatusCode::Forbidden,
+            "permission denied",
+        ));
+    }
+
+    let form: AssigneeForm = req.body_json().await?;
+    let db = req.state();
+
+    if let Some(uid) = form.assignee.as_ref() {
+        let assignee = Person::load_by_uid(db, uid)
+            .await
+            .with_status(|| StatusCode::BadRequest)?;
+
+        if !is_reviewer(db, &assignee, &opp).await? {
+            return Err(tide::Error::from_str(
+                StatusCode::BadRequest,
+                "the assignee can not review this opportunity",
+            ));
+        }
+    }
+
+    opp.assign_moderator(db, form.assignee.as_ref()).await?;
+
+    common::log(
+        Some(&person.exterior.uid),
+        "moderation-assign",
+        &json!({"opp": opp.exterior.uid, "assignee": form.assignee}),
+    );
+
+    okay_empty()
+}
+
+/// Everything pending review which the requesting person can act on,
+/// longest waiting first
+pub async fn queue(mut req: tide::Request<Database>) -> tide::Result {
+    let person = request_person(&mut req)
+        .await?
+        .ok_or_else(|| tide::Error::from_str(StatusCode::Unauthorized, "Not authorized"))?;
+
+    let manager = if person.check_permission(&Permission::ManageOpportunities) {
+        None
+    } else {
+        Some(&person.exterior.uid)
+    };
+
+    let now = chrono::Utc::now().to_fixed_offset();
+
+    okay(&Opportunity::moderation_queue(req.state(), manager, &now).await?)
+}
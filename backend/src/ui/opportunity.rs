@@ -1,12 +1,11 @@
 use common::{
     model::{
-        opportunity::ReviewStatus,
+        opportunity::{ReviewStatus, RevisionAuthor},
         person::{LogEvent, LogIdentifier, Permission, PermitAction},
         Opportunity, Partner, Person,
     },
     Database,
 };
-use once_cell::sync::Lazy;
 use serde_json::json;
 use tide::{Status, StatusCode};
 use tide_fluent_routes::{
@@ -15,18 +14,22 @@ use tide_fluent_routes::{
 };
 use uuid::Uuid;
 
-use super::{okay, request_person};
+use super::{moderation, okay, request_person};
+use crate::v1::{opportunity_tag, precondition_failed};
 
-static STAFF_REVIEWS: Lazy<[Uuid; 0]> = Lazy::new(|| {
-    [
-        // Alabama STEM Council
-        //Uuid::parse_str("b9224b48-dcc3-5153-9c31-7b53ff24a380").unwrap(),
-    ]
-});
+fn revision_author(person: &Option<Person>) -> RevisionAuthor {
+    person
+        .as_ref()
+        .map(|p| RevisionAuthor::Person(p.exterior.uid))
+        .unwrap_or(RevisionAuthor::System)
+}
 
 pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
     routes.get(blank_opp).post(add_opp).at(":uid", |r| {
-        r.get(load_opp).put(save_opp).post(duplicate_opp)
+        r.get(load_opp)
+            .put(save_opp)
+            .post(duplicate_opp)
+            .delete(trash_opp)
     })
 }
 
@@ -37,53 +40,6 @@ pub async fn blank_opp(_: tide::Request<Database>) -> tide::Result {
     okay(&opp)
 }
 
-async fn notify_pending_approval(
-    req: &tide::Request<Database>,
-    partner: &Partner,
-    opp: &Opportunity,
-) -> Result<(), tide::Error> {
-    let template = common::emails::EmailMessage::load_or_default(
-            req.state(),
-            "opportunity-pending-approval",
-            "Pending Approval on Science Near Me: {title}",
-            r#"
-<p>The {org_name} opportunity <strong>{title}</strong> on {partner_name} / Science Near Me has been created or updated, and is pending approval for publication.</p>
-<p>Please evaluate the opportunity and <a href="https://sciencenearme.org/exchange/{partner_uid}/{opp_slug}">approve, reject, or send it back to draft</a> it.</p>
-"#,
-        ).await;
-
-    let msg = template.materialize(vec![
-        ("title", &opp.exterior.title),
-        ("partner_name", &partner.exterior.name),
-        ("partner_uid", &partner.exterior.uid.to_string()),
-        ("org_name", &opp.exterior.organization_name),
-        ("opp_slug", &opp.exterior.slug),
-    ]);
-
-    if STAFF_REVIEWS.iter().any(|x| *x == partner.exterior.uid) {
-        for reviewer in Person::all_by_permission(req.state(), &Permission::ManageOpportunities)
-            .await?
-            .into_iter()
-        {
-            if let Ok(person) = reviewer {
-                common::emails::send_message(person.interior.email, &msg).await;
-            }
-        }
-    } else {
-        for reviewer in partner
-            .load_authorized_persons(req.state())
-            .await?
-            .into_iter()
-        {
-            if let Ok(person) = reviewer {
-                common::emails::send_message(person.interior.email, &msg).await;
-            }
-        }
-    }
-
-    Ok(())
-}
-
 pub async fn add_opp(mut req: tide::Request<Database>) -> tide::Result {
     let person = request_person(&mut req).await?;
 
@@ -121,7 +77,8 @@ pub async fn add_opp(mut req: tide::Request<Database>) -> tide::Result {
         ReviewStatus::NotRequired
     };
 
-    opp.store(req.state()).await?;
+    opp.store_by(req.state(), &revision_author(&person), "ui")
+        .await?;
 
     common::log(
         person.as_ref().map(|p| &p.exterior.uid),
@@ -130,7 +87,7 @@ pub async fn add_opp(mut req: tide::Request<Database>) -> tide::Result {
             "opp": opp.exterior.uid}),
     );
 
-    if let Some(person) = person {
+    if let Some(person) = &person {
         person
             .log(
                 req.state(),
@@ -140,7 +97,11 @@ pub async fn add_opp(mut req: tide::Request<Database>) -> tide::Result {
     }
 
     if let ReviewStatus::Pending = opp.interior.review_status {
-        notify_pending_approval(&req, &partner, &opp).await?;
+        let author = person.as_ref().map(|p| &p.exterior.uid);
+        opp.record_review_transition(req.state(), author, ReviewStatus::Draft, "")
+            .await?;
+        moderation::notify_transition(req.state(), &opp, person.as_ref(), ReviewStatus::Draft, "")
+            .await?;
     }
 
     okay(&opp)
@@ -171,7 +132,9 @@ pub async fn load_opp(mut req: tide::Request<Database>) -> tide::Result {
         ));
     }
 
-    okay(&opp)
+    let mut resp = okay(&opp)?;
+    resp.insert_header("ETag", opportunity_tag(&opp));
+    Ok(resp)
 }
 
 pub async fn save_opp(mut req: tide::Request<Database>) -> tide::Result {
@@ -204,6 +167,15 @@ pub async fn save_opportunity(
         ));
     }
 
+    // Someone else, possibly a partner using the API, has saved the
+    // opportunity since the editor loaded it
+    if precondition_failed(&req, &opportunity_tag(&original)) {
+        return Err(tide::Error::from_str(
+            StatusCode::PreconditionFailed,
+            "the opportunity has been changed by someone else",
+        ));
+    }
+
     let mut opp: Opportunity = req.body_json().await?;
     let partner = Partner::load_by_uid(req.state(), &opp.exterior.partner).await?;
 
@@ -232,7 +204,8 @@ pub async fn save_opportunity(
         (Some(true), Some(x)) => Some(x),
     };
 
-    opp.store(req.state()).await?;
+    opp.store_by(req.state(), &revision_author(&person), "ui")
+        .await?;
 
     common::log(
         person.as_ref().map(|p| &p.exterior.uid),
@@ -241,7 +214,7 @@ pub async fn save_opportunity(
             "opportunity": opp.exterior.uid}),
     );
 
-    if let Some(person) = person {
+    if let Some(person) = &person {
         person
             .log(
                 req.state(),
@@ -250,13 +223,19 @@ pub async fn save_opportunity(
             .await?;
     }
 
-    if let ReviewStatus::Draft = original.interior.review_status {
-        if let ReviewStatus::Pending = opp.interior.review_status {
-            notify_pending_approval(&req, &partner, &opp).await?;
-        }
+    if original.interior.review_status != opp.interior.review_status {
+        let from = original.interior.review_status;
+        let author = person.as_ref().map(|p| &p.exterior.uid);
+        opp.record_review_transition(req.state(), author, from, "")
+            .await?;
+        moderation::notify_transition(req.state(), &opp, person.as_ref(), from, "").await?;
     }
 
-    okay(&opp)
+    let stored = Opportunity::load_by_uid(req.state(), &opp.exterior.uid).await?;
+
+    let mut resp = okay(&stored)?;
+    resp.insert_header("ETag", opportunity_tag(&stored));
+    Ok(resp)
 }
 
 pub async fn duplicate_opp(mut req: tide::Request<Database>) -> tide::Result {
@@ -299,7 +278,8 @@ pub async fn duplicate_opp(mut req: tide::Request<Database>) -> tide::Result {
         ReviewStatus::NotRequired
     };
 
-    opp.store(req.state()).await?;
+    opp.store_by(req.state(), &revision_author(&person), "ui")
+        .await?;
 
     common::log(
         person.as_ref().map(|p| &p.exterior.uid),
@@ -312,3 +292,40 @@ pub async fn duplicate_opp(mut req: tide::Request<Database>) -> tide::Result {
 
     okay(&opp)
 }
+
+/// Moves the opportunity to the trash. People who manage it can
+/// delete it, but only administrators can restore it, from the
+/// manage pages.
+pub async fn trash_opp(mut req: tide::Request<Database>) -> tide::Result {
+    let person = request_person(&mut req).await?;
+
+    let mut opp = Opportunity::load_by_uid(req.state(), &Uuid::parse_str(req.param("uid")?)?)
+        .await
+        .with_status(|| StatusCode::NotFound)?;
+
+    let authorized = if let Some(p) = person.as_ref() {
+        p.check_permission(&Permission::ManageOpportunities)
+            || p.check_authorization(req.state(), &opp, PermitAction::Manage)
+                .await?
+    } else {
+        false
+    };
+
+    if !authorized {
+        return Err(tide::Error::from_str(
+            StatusCode::Forbidden,
+            "permission denied",
+        ));
+    }
+
+    opp.trash(req.state(), &revision_author(&person)).await?;
+
+    common::log(
+        person.as_ref().map(|p| &p.exterior.uid),
+        "trash-opportunity",
+        &json!({
+            "opportunity": opp.exterior.uid}),
+    );
+
+    okay(&opp)
+}
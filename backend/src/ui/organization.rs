@@ -570,8 +570,16 @@ GROUP BY v.descriptor;
 
     let activity_current: BTreeMap<String, i64> = activity_current.into_iter().collect();
 
-    let indoor_all = sqlx::query_scalar!(r#"SELECT count(*) AS "total!" FROM c_opportunity WHERE 'indoors' = ANY(opp_venue)"#).fetch_one(req.state()).await?;
-    let outdoor_all = sqlx::query_scalar!(r#"SELECT count(*) AS "total!" FROM c_opportunity WHERE 'outdoors' = ANY(opp_venue)"#).fetch_one(req.state()).await?;
+    let indoor_all = sqlx::query_scalar!(
+        r#"SELECT count(*) AS "total!" FROM c_opportunity WHERE 'indoors' = ANY(opp_venue)"#
+    )
+    .fetch_one(req.state())
+    .await?;
+    let outdoor_all = sqlx::query_scalar!(
+        r#"SELECT count(*) AS "total!" FROM c_opportunity WHERE 'outdoors' = ANY(opp_venue)"#
+    )
+    .fetch_one(req.state())
+    .await?;
     let indoor_current = sqlx::query_scalar!(r#"SELECT count(*) AS "total!" FROM c_opportunity WHERE c_opportunity_is_current(c_opportunity) AND 'indoors' = ANY(opp_venue)"#).fetch_one(req.state()).await?;
     let outdoor_current = sqlx::query_scalar!(r#"SELECT count(*) AS "total!" FROM c_opportunity WHERE c_opportunity_is_current(c_opportunity) AND 'outdoors' = ANY(opp_venue)"#).fetch_one(req.state()).await?;
 
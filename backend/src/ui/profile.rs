@@ -279,7 +279,7 @@ pub async fn get_involved(mut req: tide::Request<Database>) -> tide::Result {
         query.min,
         query.max,
         query.text,
-        pagination,
+        pagination.clone(),
     )
     .await?;
 
@@ -0,0 +1,147 @@
+//! Opportunities as schema.org JSON-LD and as GeoJSON, either singly or
+//! for every opportunity matching a search. Visibility follows the
+//! JSON endpoints: unpublished opportunities are only exported to the
+//! partner which owns them.
+
+use common::model::opportunity::{
+    export, OpportunityExterior, OpportunityQuery, OpportunityQueryOrdering,
+};
+use common::model::{Error, Opportunity, Pagination};
+use common::Database;
+use serde_json::Value;
+use tide::http::StatusCode;
+use tide::Response;
+use uuid::Uuid;
+
+use super::opportunity::restrict_query;
+use super::{error, header_check, requested_language};
+
+const JSON_LD: &str = "application/ld+json";
+const GEOJSON: &str = "application/geo+json";
+
+// Number of opportunities exported per page, unless per_page says
+// otherwise
+const EXPORT_PAGE_SIZE: u32 = 200;
+
+fn exported(content_type: &str, body: Value) -> tide::Result {
+    Ok(Response::builder(StatusCode::Ok)
+        .content_type(content_type)
+        .body(body)
+        .build())
+}
+
+/// Loads the opportunity named in the request path, if the requester
+/// is allowed to see it
+async fn requested_opportunity(
+    req: &tide::Request<Database>,
+) -> Result<OpportunityExterior, Response> {
+    let auth = header_check(req, &super::API_AUDIENCE)?;
+
+    let uid: Uuid = match req.param("uid").map(str::parse) {
+        Ok(Ok(uid)) => uid,
+        _ => {
+            return Err(error(
+                StatusCode::BadRequest,
+                "Unable to parse a UUID from the request path",
+            ))
+        }
+    };
+
+    let opp = match Opportunity::load_by_uid(req.state(), &uid).await {
+        Ok(opp) => opp,
+        Err(_) => {
+            return Err(error(
+                StatusCode::NotFound,
+                "Could not load opportunity with that uid",
+            ))
+        }
+    };
+
+    let hidden = opp.interior.withdrawn || opp.interior.trashed_at.is_some();
+
+    match (auth, hidden) {
+        (Some(auth), _) if auth == opp.exterior.partner => Ok(opp.exterior),
+        (_, false) => {
+            let mut exterior = opp.exterior;
+
+            if let Some(language) = requested_language(req) {
+                exterior.localize(&common::language_preferences(&language));
+            }
+
+            Ok(exterior)
+        }
+        _ => Err(error(
+            StatusCode::NotFound,
+            "Could not load opportunity with that id",
+        )),
+    }
+}
+
+/// Loads a page of the opportunities matching the search in the
+/// request's query string. Problems with the request are returned as
+/// the response to send instead.
+async fn matching_opportunities(
+    req: &tide::Request<Database>,
+) -> tide::Result<Result<Vec<OpportunityExterior>, Response>> {
+    let auth = match header_check(req, &super::API_AUDIENCE) {
+        Ok(auth) => auth,
+        Err(res) => return Ok(Err(res)),
+    };
+
+    let mut query: OpportunityQuery = match req.query() {
+        Ok(query) => query,
+        Err(err) => return Ok(Err(error(StatusCode::BadRequest, err.to_string()))),
+    };
+
+    restrict_query(&auth, &mut query);
+
+    query.language = requested_language(req);
+
+    let pagination = Pagination::Page {
+        index: query.page.unwrap_or(0),
+        size: query.per_page.map(u32::from).unwrap_or(EXPORT_PAGE_SIZE),
+    };
+
+    match Opportunity::load_matching(
+        req.state(),
+        &query,
+        query.sort.unwrap_or(OpportunityQueryOrdering::Alphabetical),
+        pagination,
+    )
+    .await
+    {
+        Ok(found) => Ok(Ok(found.into_iter().map(|opp| opp.exterior).collect())),
+        Err(err @ (Error::OutOfBounds(_) | Error::Value(_))) => {
+            Ok(Err(error(StatusCode::BadRequest, err.to_string())))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub async fn opportunity_json_ld(req: tide::Request<Database>) -> tide::Result {
+    match requested_opportunity(&req).await {
+        Ok(opp) => exported(JSON_LD, opp.to_json_ld()),
+        Err(res) => Ok(res),
+    }
+}
+
+pub async fn opportunity_geojson(req: tide::Request<Database>) -> tide::Result {
+    match requested_opportunity(&req).await {
+        Ok(opp) => exported(GEOJSON, opp.to_geojson()),
+        Err(res) => Ok(res),
+    }
+}
+
+pub async fn search_json_ld(req: tide::Request<Database>) -> tide::Result {
+    match matching_opportunities(&req).await? {
+        Ok(opps) => exported(JSON_LD, export::json_ld_graph(&opps)),
+        Err(res) => Ok(res),
+    }
+}
+
+pub async fn search_geojson(req: tide::Request<Database>) -> tide::Result {
+    match matching_opportunities(&req).await? {
+        Ok(opps) => exported(GEOJSON, export::feature_collection(&opps)),
+        Err(res) => Ok(res),
+    }
+}
@@ -0,0 +1,162 @@
+//! Calendar and Atom feeds of the opportunities matching a query, for
+//! subscribing to a search. The general feeds accept the same
+//! parameters as the opportunity search, while partner feeds apply
+//! the partner's default query, as their page on the site does.
+
+use chrono::Utc;
+use common::model::opportunity::feed::{self, FeedEntry};
+use common::model::opportunity::{OpportunityQuery, OpportunityQueryOrdering};
+use common::model::{Error, Opportunity, Pagination, Partner};
+use common::{Database, ToFixedOffset};
+use tide::http::StatusCode;
+use tide::Response;
+use uuid::Uuid;
+
+use super::opportunity::restrict_query;
+use super::{entity_tag, error, http_date, not_modified, requested_language};
+
+const SITE: &str = "https://sciencenearme.org";
+
+// Most opportunities included in a single feed
+const FEED_SIZE: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedFormat {
+    Calendar,
+    Atom,
+}
+
+impl FeedFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            FeedFormat::Calendar => "text/calendar; charset=utf-8",
+            FeedFormat::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+
+    fn render(&self, title: &str, self_url: &str, entries: &[FeedEntry]) -> String {
+        let now = Utc::now().to_fixed_offset();
+
+        match self {
+            FeedFormat::Calendar => feed::ical(title, entries, &now),
+            FeedFormat::Atom => feed::atom(title, self_url, entries, &now),
+        }
+    }
+}
+
+/// Responds with the feed of opportunities matching `query`, or with
+/// 304 Not Modified if the requester already has it
+async fn respond(
+    req: &tide::Request<Database>,
+    query: &OpportunityQuery,
+    format: FeedFormat,
+    title: &str,
+) -> tide::Result {
+    let db = req.state();
+
+    let found = match Opportunity::load_matching(
+        db,
+        query,
+        query.sort.unwrap_or(OpportunityQueryOrdering::Soonest),
+        Pagination::Page {
+            index: 0,
+            size: FEED_SIZE,
+        },
+    )
+    .await
+    {
+        Ok(found) => found,
+        Err(err @ (Error::OutOfBounds(_) | Error::Value(_))) => {
+            return Ok(error(StatusCode::BadRequest, err.to_string()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let entries = Opportunity::feed_entries(db, found).await?;
+
+    let self_url = match req.url().query() {
+        Some(params) => format!("{}{}?{}", SITE, req.url().path(), params),
+        None => format!("{}{}", SITE, req.url().path()),
+    };
+
+    let body = format.render(title, &self_url, &entries);
+    let etag = entity_tag(body.as_bytes());
+    let last_modified = feed::last_updated(&entries);
+
+    let mut res = if not_modified(req, &etag, last_modified.as_ref()) {
+        Response::new(StatusCode::NotModified)
+    } else {
+        let mut res = Response::new(StatusCode::Ok);
+        res.set_content_type(format.content_type());
+        res.set_body(body);
+        res
+    };
+
+    res.insert_header("ETag", etag);
+    res.insert_header("Cache-Control", "public, max-age=900");
+
+    if let Some(modified) = last_modified {
+        res.insert_header("Last-Modified", http_date(&modified));
+    }
+
+    Ok(res)
+}
+
+async fn opportunity_feed(req: tide::Request<Database>, format: FeedFormat) -> tide::Result {
+    let mut query: OpportunityQuery = match req.query() {
+        Ok(query) => query,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    // Feeds are public, so they never include unpublished
+    // opportunities
+    restrict_query(&None, &mut query);
+
+    query.current = Some(query.current.unwrap_or(true));
+    query.language = requested_language(&req);
+
+    respond(&req, &query, format, "Science Near Me").await
+}
+
+pub async fn opportunity_ical(req: tide::Request<Database>) -> tide::Result {
+    opportunity_feed(req, FeedFormat::Calendar).await
+}
+
+pub async fn opportunity_atom(req: tide::Request<Database>) -> tide::Result {
+    opportunity_feed(req, FeedFormat::Atom).await
+}
+
+async fn partner_feed(req: tide::Request<Database>, format: FeedFormat) -> tide::Result {
+    let partner = match Uuid::parse_str(req.param("uid")?) {
+        Ok(uid) => Partner::load_by_uid(req.state(), &uid).await.ok(),
+        Err(_) => None,
+    };
+
+    let Some(partner) = partner else {
+        return Ok(error(StatusCode::NotFound, "No such partner"));
+    };
+
+    let mut query = match crate::ui::finder::partner_query(
+        partner
+            .exterior
+            .default_query
+            .as_deref()
+            .unwrap_or_default(),
+        req.url().query().unwrap_or_default(),
+    ) {
+        Ok(query) => query,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    query.language = requested_language(&req);
+
+    respond(&req, &query, format, &partner.exterior.name).await
+}
+
+pub async fn partner_ical(req: tide::Request<Database>) -> tide::Result {
+    partner_feed(req, FeedFormat::Calendar).await
+}
+
+pub async fn partner_atom(req: tide::Request<Database>) -> tide::Result {
+    partner_feed(req, FeedFormat::Atom).await
+}
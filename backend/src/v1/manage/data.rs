@@ -411,7 +411,10 @@ pub async fn partners_csv(req: tide::Request<Database>) -> tide::Result {
 
     Ok(Response::builder(StatusCode::Ok)
         .content_type("text/csv")
-        .header("Content-Disposition", "attachment; filename=\"partners.csv\"")
+        .header(
+            "Content-Disposition",
+            "attachment; filename=\"partners.csv\"",
+        )
         .body(out.into_inner()?)
         .build())
 }
@@ -434,7 +437,10 @@ pub async fn exchanges_csv(req: tide::Request<Database>) -> tide::Result {
 
     Ok(Response::builder(StatusCode::Ok)
         .content_type("text/csv")
-        .header("Content-Disposition", "attachment; filename=\"exchanges.csv\"")
+        .header(
+            "Content-Disposition",
+            "attachment; filename=\"exchanges.csv\"",
+        )
         .body(out.into_inner()?)
         .build())
 }
@@ -18,6 +18,7 @@ pub mod content;
 pub mod data;
 pub mod emails;
 pub mod opportunities;
+pub mod regions;
 
 const BASE: &'static str = "/api/v1/manage/";
 
@@ -72,6 +73,7 @@ pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
         .at("content/", content::routes)
         .at("emails/", emails::routes)
         .at("opportunities/", opportunities::routes)
+        .at("regions/", regions::routes)
         .at("data/", data::routes)
         .at("health/", |r| r.get(health))
 }
@@ -347,10 +349,10 @@ async fn persons(mut req: tide::Request<Database>) -> tide::Result {
             let db = req.state();
 
             let (persons, total) = if let Some(q) = &query.q {
-                Person::find_matching(db, &q, query.pagination).await?
+                Person::find_matching(db, &q, query.pagination.clone()).await?
             } else {
                 (
-                    Person::catalog(db, query.pagination).await?,
+                    Person::catalog(db, query.pagination.clone()).await?,
                     Person::total(db).await?,
                 )
             };
@@ -3,9 +3,10 @@ use chrono::{DateTime, FixedOffset};
 use common::{
     model::{
         opportunity::{
-            Cost, Descriptor, Domain, EntityType, LocationType, OpenDays, OpenHours,
-            OpportunityQuery, OpportunityQueryOrdering, OrganizationType, PageLayout, PageOptions,
-            Topic, VenueType,
+            Accessibility, Cost, Descriptor, Domain, Duplicate, DuplicateStatus, EntityType,
+            FieldChange, LocationType, OpenDays, OpenHours, OpportunityQuery,
+            OpportunityQueryOrdering, OrganizationType, PageLayout, PageOptions, Revision,
+            RevisionAuthor, Topic, VenueType,
         },
         partner::PartnerReference,
         person::Permission,
@@ -24,13 +25,21 @@ use tide_fluent_routes::{
 use uuid::Uuid;
 
 use crate::v1::redirect;
+use common::model::opportunity::trash::TRASH_RETENTION;
 
 pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
-    routes.get(search).post(add_opportunity).at(":uid", |r| {
-        r.at("overlay", |r| r.get(overlay).post(overlay))
-            .get(opportunity)
-            .post(opportunity)
-    })
+    routes
+        .get(search)
+        .post(add_opportunity)
+        .at("duplicates", |r| r.get(duplicates).post(duplicates))
+        .at("trash", |r| r.get(trash).post(trash))
+        .at(":uid", |r| {
+            r.at("overlay", |r| r.get(overlay).post(overlay))
+                .at("revisions", |r| r.get(revisions).post(revisions))
+                .get(opportunity)
+                .post(opportunity)
+                .delete(opportunity)
+        })
 }
 
 #[derive(TemplateOnce)]
@@ -139,6 +148,7 @@ struct OpportunityForm {
     tags: String,
     opp_topics: Option<Vec<Topic>>,
     ticket_required: Option<bool>,
+    capacity: Option<String>,
     description: Option<String>,
     short_desc: Option<String>,
     image_url: Option<String>,
@@ -147,6 +157,7 @@ struct OpportunityForm {
     end_datetimes: Option<Vec<Option<DateTime<FixedOffset>>>>,
     cost: Option<Cost>,
     languages: Option<String>,
+    accessibility: Option<Vec<Accessibility>>,
     opp_hashtags: Option<String>,
     is_online: Option<bool>,
     location_type: Option<LocationType>,
@@ -202,6 +213,8 @@ impl OpportunityForm {
             .map(|s| s.trim().to_string())
             .collect();
 
+        opportunity.exterior.accessibility = self.accessibility.unwrap_or_default();
+
         opportunity.exterior.opp_hashtags = self
             .opp_hashtags
             .unwrap_or_default()
@@ -241,6 +254,7 @@ impl OpportunityForm {
         opportunity.exterior.tags = self.tags.split(',').map(|s| s.trim().to_string()).collect();
         opportunity.exterior.opp_topics = self.opp_topics.unwrap_or_default();
         opportunity.exterior.ticket_required = self.ticket_required.unwrap_or(false);
+        opportunity.exterior.capacity = self.capacity.and_then(|c| c.trim().parse().ok());
         opportunity.exterior.description = self.description.unwrap_or_default();
         opportunity.exterior.short_desc = self.short_desc.unwrap_or_default();
         opportunity.exterior.image_url = self.image_url.unwrap_or_default();
@@ -258,6 +272,15 @@ impl OpportunityForm {
         opportunity.interior.contact_email = self.contact_email.unwrap_or_default();
         opportunity.interior.contact_phone = self.contact_phone.unwrap_or_default();
 
+        // The form only edits the weekly hours, so dated exceptions
+        // are carried over as they were
+        let exceptions = opportunity
+            .exterior
+            .attraction_hours
+            .take()
+            .map(|hours| hours.exceptions)
+            .unwrap_or_default();
+
         opportunity.exterior.attraction_hours = if self.monday_opens.is_some()
             || self.tuesday_opens.is_some()
             || self.wednesday_opens.is_some()
@@ -265,6 +288,7 @@ impl OpportunityForm {
             || self.friday_opens.is_some()
             || self.saturday_opens.is_some()
             || self.sunday_opens.is_some()
+            || !exceptions.is_empty()
         {
             Some(OpenDays {
                 monday: if let (Some(opens), Some(closes)) = (self.monday_opens, self.monday_closes)
@@ -313,6 +337,7 @@ impl OpportunityForm {
                 } else {
                     None
                 },
+                exceptions,
             })
         } else {
             None
@@ -323,7 +348,7 @@ impl OpportunityForm {
 }
 
 async fn opportunity(mut req: tide::Request<Database>) -> tide::Result {
-    let _admin = match super::authorized_admin(&req, &Permission::ManageOpportunities).await {
+    let admin = match super::authorized_admin(&req, &Permission::ManageOpportunities).await {
         Ok(person) => person,
         Err(resp) => return Ok(resp),
     };
@@ -334,6 +359,14 @@ async fn opportunity(mut req: tide::Request<Database>) -> tide::Result {
         Opportunity::load_by_uid(db, &uid).await?
     };
 
+    if let Method::Delete = req.method() {
+        opportunity
+            .trash(req.state(), &RevisionAuthor::Person(admin.exterior.uid))
+            .await?;
+
+        return Ok(redirect("trash"));
+    }
+
     if let Method::Post = req.method() {
         let qs = serde_qs::Config::new(5, false);
         let form: OpportunityForm = qs.deserialize_str(&req.body_string().await?)?;
@@ -342,7 +375,10 @@ async fn opportunity(mut req: tide::Request<Database>) -> tide::Result {
 
         let db = req.state();
 
-        if let Err(err) = opportunity.store(db).await {
+        if let Err(err) = opportunity
+            .store_by(db, &RevisionAuthor::Person(admin.exterior.uid), "manage")
+            .await
+        {
             return Ok(OpportunityPage {
                 message: dbg!(err).to_string(),
                 all_partners: Partner::catalog(db).await?,
@@ -366,7 +402,7 @@ async fn opportunity(mut req: tide::Request<Database>) -> tide::Result {
 }
 
 async fn add_opportunity(mut req: tide::Request<Database>) -> tide::Result {
-    let _admin = match super::authorized_admin(&req, &Permission::ManageOpportunities).await {
+    let admin = match super::authorized_admin(&req, &Permission::ManageOpportunities).await {
         Ok(person) => person,
         Err(resp) => return Ok(resp),
     };
@@ -383,7 +419,9 @@ async fn add_opportunity(mut req: tide::Request<Database>) -> tide::Result {
 
     form.apply(&mut opportunity)?;
 
-    opportunity.store(db).await?;
+    opportunity
+        .store_by(db, &RevisionAuthor::Person(admin.exterior.uid), "manage")
+        .await?;
 
     Ok(redirect(&format!(
         "{}{}",
@@ -407,6 +445,7 @@ struct OverlayForm {
     pes_domain: Option<common::model::opportunity::Domain>,
     opp_descriptor: Vec<common::model::opportunity::Descriptor>,
     opp_topics: Vec<common::model::opportunity::Topic>,
+    accessibility: Vec<common::model::opportunity::Accessibility>,
     tags: Vec<String>,
     min_age: Option<i16>,
     max_age: Option<i16>,
@@ -455,6 +494,17 @@ impl OverlayForm {
             delete(exterior, "opp_topics");
         }
 
+        if !self.accessibility.is_empty() {
+            exterior["accessibility"] = self
+                .accessibility
+                .into_iter()
+                .map(|x| x.to_option().0)
+                .collect::<Vec<_>>()
+                .into();
+        } else {
+            delete(exterior, "accessibility");
+        }
+
         if !self.tags.is_empty() {
             exterior["tags"] = self.tags.clone().into();
         } else {
@@ -602,3 +652,212 @@ WHERE
 
     Ok(form.into_response(StatusCode::Ok)?)
 }
+
+#[derive(TemplateOnce)]
+#[template(path = "manage/revisions.stpl.html")]
+struct RevisionsPage {
+    message: String,
+    opportunity: Opportunity,
+    revisions: Vec<Revision>,
+    /// The fields changed by each revision, relative to the one before it
+    changed: Vec<Vec<String>>,
+    comparison: Option<(i32, i32, Vec<FieldChange>)>,
+}
+
+#[derive(Deserialize)]
+struct RevisionsQuery {
+    from: Option<i32>,
+    to: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct RollbackForm {
+    revision: i32,
+}
+
+async fn revisions(mut req: tide::Request<Database>) -> tide::Result {
+    let admin = match super::authorized_admin(&req, &Permission::ManageOpportunities).await {
+        Ok(person) => person,
+        Err(resp) => return Ok(resp),
+    };
+
+    let uid: Uuid = req.param("uid")?.parse()?;
+    let mut opportunity = Opportunity::load_by_uid(req.state(), &uid).await?;
+    let mut message = String::new();
+
+    if let Method::Post = req.method() {
+        let form: RollbackForm = req.body_form().await?;
+        let db = req.state();
+        let revision = Opportunity::load_revision(db, &uid, form.revision).await?;
+
+        opportunity.restore(&revision)?;
+
+        match opportunity
+            .store_by(db, &RevisionAuthor::Person(admin.exterior.uid), "rollback")
+            .await
+        {
+            Ok(_) => return Ok(redirect(req.url().path())),
+            Err(err) => message = err.to_string(),
+        }
+    }
+
+    let query: RevisionsQuery = req.query()?;
+    let db = req.state();
+    let revisions = Opportunity::load_revisions(db, &uid).await?;
+
+    // Revisions are listed most recent first, so each one's
+    // predecessor is the next one in the list
+    let changed = revisions
+        .iter()
+        .zip(
+            revisions
+                .iter()
+                .skip(1)
+                .map(Some)
+                .chain(std::iter::once(None)),
+        )
+        .map(|(revision, previous)| match previous {
+            Some(previous) => previous
+                .diff(revision)
+                .into_iter()
+                .map(|change| change.field)
+                .collect(),
+            None => Vec::new(),
+        })
+        .collect();
+
+    let find = |id| revisions.iter().find(|r| r.id == id);
+
+    let comparison = match (query.from.and_then(find), query.to.and_then(find)) {
+        (Some(from), Some(to)) => Some((from.id, to.id, from.diff(to))),
+        _ => None,
+    };
+
+    RevisionsPage {
+        message,
+        opportunity,
+        revisions,
+        changed,
+        comparison,
+    }
+    .into_response(StatusCode::Ok)
+}
+
+#[derive(TemplateOnce)]
+#[template(path = "manage/duplicates.stpl.html")]
+struct DuplicatesPage {
+    message: String,
+    duplicates: Vec<Duplicate>,
+}
+
+#[derive(Deserialize)]
+struct DuplicateForm {
+    duplicate: i32,
+    /// Either "first" or "second" to merge into that opportunity, or
+    /// "dismiss" if they aren't duplicates after all
+    action: String,
+}
+
+async fn duplicates(mut req: tide::Request<Database>) -> tide::Result {
+    let admin = match super::authorized_admin(&req, &Permission::ManageOpportunities).await {
+        Ok(person) => person,
+        Err(resp) => return Ok(resp),
+    };
+
+    let mut message = String::new();
+
+    if let Method::Post = req.method() {
+        let form: DuplicateForm = req.body_form().await?;
+        let db = req.state();
+        let mut duplicate = Duplicate::load_by_id(db, form.duplicate).await?;
+        let author = RevisionAuthor::Person(admin.exterior.uid);
+
+        let result = match form.action.as_str() {
+            "first" | "second" => {
+                let (keep, lose) = if form.action == "first" {
+                    (&duplicate.first.uid, &duplicate.second.uid)
+                } else {
+                    (&duplicate.second.uid, &duplicate.first.uid)
+                };
+
+                Opportunity::merge(db, keep, lose, &author)
+                    .await
+                    .map(|_| ())
+            }
+            "dismiss" => duplicate.dismiss(db, Some(admin.exterior.uid)).await,
+            _ => Err(common::model::Error::Value("action".to_string())),
+        };
+
+        match result {
+            Ok(_) => return Ok(redirect(req.url().path())),
+            Err(err) => message = err.to_string(),
+        }
+    }
+
+    DuplicatesPage {
+        message,
+        duplicates: Duplicate::load_with_status(req.state(), DuplicateStatus::Pending).await?,
+    }
+    .into_response(StatusCode::Ok)
+}
+
+#[derive(TemplateOnce)]
+#[template(path = "manage/trash.stpl.html")]
+struct TrashPage {
+    message: String,
+    retention_days: i64,
+    trashed: Vec<Opportunity>,
+}
+
+#[derive(Deserialize)]
+struct TrashForm {
+    uid: Uuid,
+    /// Either "trash" to move the opportunity to the trash, or
+    /// "restore" to take it back out
+    action: String,
+}
+
+async fn trash(mut req: tide::Request<Database>) -> tide::Result {
+    let admin = match super::authorized_admin(&req, &Permission::ManageOpportunities).await {
+        Ok(person) => person,
+        Err(resp) => return Ok(resp),
+    };
+
+    let mut message = String::new();
+
+    if let Method::Post = req.method() {
+        let form: TrashForm = req.body_form().await?;
+        let db = req.state();
+        let mut opportunity = Opportunity::load_by_uid(db, &form.uid).await?;
+        let author = RevisionAuthor::Person(admin.exterior.uid);
+
+        let result = match form.action.as_str() {
+            "trash" => opportunity.trash(db, &author).await,
+            "restore" => opportunity.restore_from_trash(db, &author).await,
+            _ => Err(common::model::Error::Value("action".to_string())),
+        };
+
+        match result {
+            Ok(_) => return Ok(redirect(req.url().path())),
+            Err(err) => message = err.to_string(),
+        }
+    }
+
+    let trashed = Opportunity::load_matching(
+        req.state(),
+        &OpportunityQuery {
+            trashed: Some(true),
+            ..Default::default()
+        },
+        OpportunityQueryOrdering::Native,
+        Pagination::All,
+    )
+    .await?;
+
+    TrashPage {
+        message,
+        retention_days: TRASH_RETENTION.num_days(),
+        trashed,
+    }
+    .into_response(StatusCode::Ok)
+}
@@ -0,0 +1,157 @@
+use common::model::geojson::Geometry;
+use common::model::person::Permission;
+use common::model::region::RegionReference;
+use common::model::Region;
+use common::Database;
+use http_types::{Method, StatusCode};
+use sailfish::TemplateOnce;
+use serde::Deserialize;
+use tide_fluent_routes::{
+    routebuilder::{RouteBuilder, RouteBuilderExt},
+    RouteSegment,
+};
+
+use crate::v1::redirect;
+
+use super::{authorized_admin, IntoResponse};
+
+pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
+    routes
+        .get(regions)
+        .post(regions)
+        .at(":id", |r| r.get(region).post(region))
+}
+
+#[derive(TemplateOnce, Default)]
+#[template(path = "manage/regions.stpl.html")]
+struct RegionsPage {
+    message: String,
+    regions: Vec<RegionReference>,
+    name: String,
+    parent: Option<i32>,
+    geometry: String,
+}
+
+#[derive(Deserialize)]
+struct RegionForm {
+    #[serde(default)]
+    action: String,
+    name: String,
+    #[serde(default)]
+    parent: String,
+    geometry: String,
+}
+
+impl RegionForm {
+    fn parent(&self) -> Option<i32> {
+        self.parent.parse().ok()
+    }
+
+    fn region(&self, id: Option<i32>) -> Result<Region, common::model::Error> {
+        Ok(Region {
+            id,
+            name: self.name.clone(),
+            parent: self.parent(),
+            geometry: serde_json::from_str::<Geometry>(&self.geometry)
+                .map_err(|err| common::model::Error::Value(format!("geometry: {}", err)))?,
+        })
+    }
+}
+
+async fn regions(mut req: tide::Request<Database>) -> tide::Result {
+    let _admin = match authorized_admin(&req, &Permission::ManageContent).await {
+        Ok(person) => person,
+        Err(resp) => return Ok(resp),
+    };
+
+    let mut page = RegionsPage::default();
+
+    if let Method::Post = req.method() {
+        let form: RegionForm = req.body_form().await?;
+
+        let stored = match form.region(None) {
+            Ok(mut region) => region.store(req.state()).await.map(|_| region.id),
+            Err(err) => Err(err),
+        };
+
+        match stored {
+            Ok(Some(id)) => return Ok(redirect(&format!("{}{}", req.url().path(), id))),
+            Ok(None) => {}
+            Err(err) => {
+                page.message = err.to_string();
+                page.parent = form.parent();
+                page.name = form.name;
+                page.geometry = form.geometry;
+            }
+        }
+    }
+
+    page.regions = Region::list(req.state()).await?;
+
+    page.into_response(StatusCode::Ok)
+}
+
+#[derive(TemplateOnce)]
+#[template(path = "manage/region.stpl.html")]
+struct RegionPage {
+    message: String,
+    name: String,
+    parent: Option<i32>,
+    geometry: String,
+    regions: Vec<RegionReference>,
+}
+
+async fn region(mut req: tide::Request<Database>) -> tide::Result {
+    let _admin = match authorized_admin(&req, &Permission::ManageContent).await {
+        Ok(person) => person,
+        Err(resp) => return Ok(resp),
+    };
+
+    let id: i32 = req.param("id")?.parse()?;
+
+    let mut page = match Region::load_by_id(req.state(), id).await {
+        Ok(region) => RegionPage {
+            message: String::new(),
+            name: region.name,
+            parent: region.parent,
+            geometry: serde_json::to_string(&region.geometry)?,
+            regions: Vec::new(),
+        },
+        Err(common::model::Error::SQLx(sqlx::Error::RowNotFound)) => {
+            return Ok(tide::Response::new(StatusCode::NotFound))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    if let Method::Post = req.method() {
+        let form: RegionForm = req.body_form().await?;
+
+        if form.action == "delete" {
+            Region::delete(req.state(), id).await?;
+            return Ok(redirect("."));
+        }
+
+        let stored = match form.region(Some(id)) {
+            Ok(mut region) => region.store(req.state()).await,
+            Err(err) => Err(err),
+        };
+
+        match stored {
+            Ok(_) => return Ok(redirect(req.url().path())),
+            Err(err) => {
+                page.message = err.to_string();
+                page.parent = form.parent();
+                page.name = form.name;
+                page.geometry = form.geometry;
+            }
+        }
+    }
+
+    page.regions = Region::list(req.state())
+        .await?
+        .into_iter()
+        .filter(|r| r.id != id)
+        .collect();
+
+    page.into_response(StatusCode::Ok)
+}
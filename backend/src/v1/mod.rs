@@ -1,7 +1,11 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use common::model::opportunity::ValidationIssue;
+use common::model::Opportunity;
 use common::Database;
 use once_cell::sync::Lazy;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
+use sha2::{Digest, Sha256};
 use std::convert::TryInto;
 use tide::http::{mime, StatusCode};
 use tide::prelude::*;
@@ -11,7 +15,10 @@ use uuid::Uuid;
 
 use common::jwt::{check_jwt, issue_jwt};
 
+pub mod export;
+pub mod feed;
 pub mod manage;
+pub mod openapi;
 pub mod opportunity;
 pub mod participation;
 pub mod partner;
@@ -42,18 +49,126 @@ where
         .build())
 }
 
-pub fn error<S, M>(code: S, msg: M) -> Response
+/// The body of every error response
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub error: String,
+    /// Everything wrong with an opportunity which failed validation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<ValidationIssue>,
+}
+
+pub fn error_with<S>(code: S, body: &ErrorBody) -> Response
 where
     S: TryInto<StatusCode>,
     S::Error: std::fmt::Debug,
-    M: AsRef<str>,
 {
     Response::builder(code)
         .content_type(mime::JSON)
-        .body(json!({ "error": msg.as_ref() }))
+        .body(json!(body))
         .build()
 }
 
+pub fn error<S, M>(code: S, msg: M) -> Response
+where
+    S: TryInto<StatusCode>,
+    S::Error: std::fmt::Debug,
+    M: AsRef<str>,
+{
+    error_with(
+        code,
+        &ErrorBody {
+            error: msg.as_ref().to_string(),
+            issues: Vec::new(),
+        },
+    )
+}
+
+#[derive(Deserialize)]
+struct LanguageParam {
+    language: Option<String>,
+}
+
+/// The languages the requester would like to read, from the
+/// `language` query parameter if present, or else the Accept-Language
+/// header. The result is in Accept-Language format, and can be
+/// parsed with `common::language_preferences`.
+pub fn requested_language<S>(req: &tide::Request<S>) -> Option<String> {
+    req.query::<LanguageParam>()
+        .ok()
+        .and_then(|param| param.language)
+        .or_else(|| {
+            req.header("Accept-Language")
+                .map(|header| header.last().as_str().to_string())
+        })
+}
+
+/// A strong entity tag identifying a response body
+pub fn entity_tag(body: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(&Sha256::digest(body)[..16]))
+}
+
+/// A strong entity tag identifying the stored state of an
+/// opportunity, which changes whenever anything about it is saved.
+/// Writers send it back in If-Match to avoid overwriting changes
+/// they haven't seen.
+pub fn opportunity_tag(opp: &Opportunity) -> String {
+    // Going through Value sorts the keys of every map, but the tags
+    // are a set that serializes in whatever order the HashSet iterates
+    // them, so they need to be put in order too
+    let mut value = serde_json::to_value(opp).unwrap_or_default();
+    if let Some(serde_json::Value::Array(tags)) = value.get_mut("tags") {
+        tags.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+    }
+    entity_tag(value.to_string().as_bytes())
+}
+
+/// Formats a time as an HTTP-date, e.g. for Last-Modified
+pub fn http_date(dt: &DateTime<FixedOffset>) -> String {
+    dt.with_timezone(&Utc)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// True if the requester's cached copy, as described by the
+/// If-None-Match or If-Modified-Since header, is still current. When
+/// both are present only If-None-Match is considered.
+pub fn not_modified<S>(
+    req: &tide::Request<S>,
+    etag: &str,
+    last_modified: Option<&DateTime<FixedOffset>>,
+) -> bool {
+    if let Some(header) = req.header("If-None-Match") {
+        return header
+            .iter()
+            .flat_map(|value| value.as_str().split(','))
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+
+    match (req.header("If-Modified-Since"), last_modified) {
+        (Some(header), Some(modified)) => DateTime::parse_from_rfc2822(header.last().as_str())
+            .map(|since| modified.timestamp() <= since.timestamp())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// True if the request has an If-Match header which doesn't match
+/// the current entity tag, meaning the requester is working from an
+/// outdated copy and the write should be refused. Requests without
+/// If-Match are never stale.
+pub fn precondition_failed<S>(req: &tide::Request<S>, etag: &str) -> bool {
+    match req.header("If-Match") {
+        Some(header) => !header
+            .iter()
+            .flat_map(|value| value.as_str().split(','))
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag == etag),
+        None => false,
+    }
+}
+
 pub fn redirect(dest: &str) -> Response {
     Response::builder(StatusCode::SeeOther)
         .header("Location", dest)
@@ -142,3 +257,26 @@ pub fn header_check(req: &tide::Request<Database>, aud: &Uuid) -> Result<Option<
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opportunity_tag_ignores_tag_order() {
+        let names: Vec<String> = (0..16).map(|i| format!("tag-{i}")).collect();
+
+        let tagged = |names: &mut dyn Iterator<Item = &String>| {
+            let mut opp = Opportunity::default();
+            opp.exterior.tags = names.cloned().collect();
+            opportunity_tag(&opp)
+        };
+
+        let expected = tagged(&mut names.iter());
+
+        for _ in 0..20 {
+            assert_eq!(tagged(&mut names.iter()), expected);
+            assert_eq!(tagged(&mut names.iter().rev()), expected);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! The OpenAPI document describing `/api/v1/`. The paths are written
+//! by hand in `openapi_base.json`, while the schemas they refer to are
+//! generated from the types the handlers actually exchange. The
+//! generated document is checked in as `static/openapi.json`, and a
+//! test fails when the two differ; run the tests with
+//! `UPDATE_OPENAPI=1` to rewrite the checked in copy.
+
+use common::model::opportunity::{
+    Attendee, FieldChange, OccurrenceWindow, Opportunity, OpportunityExterior,
+    OpportunityReference, Revision, Translation, ValidationIssue,
+};
+use common::model::participation::{Participation, ParticipationExterior};
+use common::model::schema::{JsonSchema, ObjectSchema};
+use common::Database;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use tide::http::{mime, StatusCode};
+use tide::Response;
+
+use super::ErrorBody;
+
+const BASE: &str = include_str!("openapi_base.json");
+
+static DOCUMENT: Lazy<String> = Lazy::new(|| {
+    serde_json::to_string_pretty(&document()).expect("The OpenAPI document should serialize")
+});
+
+impl JsonSchema for ErrorBody {
+    fn schema() -> Value {
+        ObjectSchema::new()
+            .field::<String>("error", "A description of what went wrong")
+            .field::<Vec<ValidationIssue>>(
+                "issues",
+                "Everything wrong with an opportunity which failed validation. Omitted for other errors.",
+            )
+            .required(&["error"])
+            .build()
+    }
+}
+
+fn schemas() -> Value {
+    json!({
+        "attendee": Attendee::schema(),
+        "error": ErrorBody::schema(),
+        "field_change": FieldChange::schema(),
+        "occurrence_window": OccurrenceWindow::schema(),
+        "opportunity": Opportunity::schema(),
+        "opportunity_exterior": OpportunityExterior::schema(),
+        "opportunity_ref": OpportunityReference::schema(),
+        "participation": Participation::schema(),
+        "participation_exterior": ParticipationExterior::schema(),
+        "revision": Revision::schema(),
+        "translation": Translation::schema(),
+        "validation_issue": ValidationIssue::schema(),
+    })
+}
+
+pub fn document() -> Value {
+    let mut doc: Value =
+        serde_json::from_str(BASE).expect("openapi_base.json should contain valid JSON");
+
+    doc["components"]["schemas"] = schemas();
+
+    doc
+}
+
+pub async fn openapi_json(_req: tide::Request<Database>) -> tide::Result {
+    Ok(Response::builder(StatusCode::Ok)
+        .content_type(mime::JSON)
+        .body(DOCUMENT.as_str())
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECKED_IN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/static/openapi.json");
+
+    fn references(value: &Value, found: &mut Vec<String>) {
+        match value {
+            Value::Object(obj) => {
+                if let Some(Value::String(target)) = obj.get("$ref") {
+                    found.push(target.clone());
+                }
+                obj.values().for_each(|v| references(v, found));
+            }
+            Value::Array(items) => items.iter().for_each(|v| references(v, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn checked_in_spec_matches_generated() {
+        let generated = document();
+
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(CHECKED_IN, format!("{}\n", *DOCUMENT)).unwrap();
+        }
+
+        let checked_in: Value =
+            serde_json::from_str(&std::fs::read_to_string(CHECKED_IN).unwrap()).unwrap();
+
+        assert!(
+            checked_in == generated,
+            "static/openapi.json is out of date; rerun the tests with UPDATE_OPENAPI=1 to regenerate it"
+        );
+    }
+
+    #[test]
+    fn error_schema_documents_error_body() {
+        let body = ErrorBody {
+            error: "title must not be empty".to_string(),
+            issues: vec![ValidationIssue {
+                path: "$.title".to_string(),
+                code: common::model::opportunity::IssueCode::Required,
+                severity: common::model::opportunity::Severity::Error,
+                message: "title must not be empty".to_string(),
+            }],
+        };
+
+        let serialized = serde_json::to_value(&body).unwrap();
+        let documented = ErrorBody::schema();
+
+        assert_eq!(
+            serialized.as_object().unwrap().keys().collect::<Vec<_>>(),
+            documented["properties"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn every_reference_resolves() {
+        let doc = document();
+        let mut found = Vec::new();
+        references(&doc, &mut found);
+
+        assert!(!found.is_empty());
+
+        for target in found {
+            let name = target.trim_start_matches("#/components/schemas/");
+            assert!(
+                doc["components"]["schemas"].get(name).is_some(),
+                "unresolved reference {}",
+                target
+            );
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use chrono::{DateTime, FixedOffset};
 use common::model::opportunity::{
-    EntityType, Opportunity, OpportunityImportRecord, OpportunityQuery, OpportunityQueryOrdering,
+    EntityType, Interests, IssueCode, Opportunity, OpportunityImportRecord, OpportunityQuery,
+    OpportunityQueryOrdering, RevisionAuthor, Topic, Translation, ValidationIssue,
+    ValidationReport,
 };
 use common::model::partner::LoggedErrorLevel;
 use common::model::{Pagination, Partner};
@@ -10,17 +13,54 @@ use tide::Response;
 use tide_fluent_routes::prelude::*;
 use uuid::Uuid;
 
-use super::{error, header_check, success};
+use super::{
+    error, error_with, export, feed, header_check, not_modified, opportunity_tag,
+    precondition_failed, requested_language, success, ErrorBody,
+};
+
+const SEARCH_PAGE_SIZE: u8 = 50;
+
+const RECOMMEND_PAGE_SIZE: u8 = 10;
+
+/// The most opportunities a single batch request may upsert, or
+/// change the status of
+const BATCH_LIMIT: usize = 200;
 
 pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
     routes
         .post(opportunity_new)
         .get(opportunity_search)
         .at("recommend", |r| r.get(opportunity_recommend))
-        .at(
-            ":uid",
-            |r| r.get(opportunity_get).put(opportunity_put), /*.patch(opportunity_patch)*/
-        )
+        .at("validate", |r| r.post(opportunity_validate))
+        .at("batch", |r| r.post(opportunity_batch))
+        .at("status", |r| r.post(opportunity_bulk_status))
+        .at("occurrences", |r| r.get(opportunity_occurrences))
+        .at("feed.ics", |r| r.get(feed::opportunity_ical))
+        .at("feed.atom", |r| r.get(feed::opportunity_atom))
+        .at("export.jsonld", |r| r.get(export::search_json_ld))
+        .at("export.geojson", |r| r.get(export::search_geojson))
+        .at(":uid", |r| {
+            r.get(opportunity_get)
+                .put(opportunity_put)
+                .patch(opportunity_patch)
+                .delete(opportunity_delete)
+                .at("revisions", |r| {
+                    r.get(opportunity_revisions)
+                        .at("diff", |r| r.get(opportunity_revision_diff))
+                        .at(":revision", |r| {
+                            r.at("rollback", |r| r.post(opportunity_rollback))
+                        })
+                })
+                .at("attendees", |r| r.get(opportunity_attendees))
+                .at("export.jsonld", |r| r.get(export::opportunity_json_ld))
+                .at("export.geojson", |r| r.get(export::opportunity_geojson))
+                .at("translations", |r| {
+                    r.get(opportunity_translations).at(":language", |r| {
+                        r.put(opportunity_translation_put)
+                            .delete(opportunity_translation_delete)
+                    })
+                })
+        })
 }
 
 async fn opportunity_new(mut req: tide::Request<Database>) -> tide::Result {
@@ -66,22 +106,10 @@ async fn opportunity_new(mut req: tide::Request<Database>) -> tide::Result {
     opp.exterior.partner = auth;
     opp.interior.accepted = Some(true); // Policy now to trust partners by default
 
-    if let Err(err) = opp.validate().await {
-        println!(
-            "Logged error {}: {}",
-            partner
-                .log_error(
-                    req.state(),
-                    LoggedErrorLevel::Error,
-                    err.to_string(),
-                    Some(&opp.exterior.title),
-                    Option::<&str>::None,
-                )
-                .await?,
-            err
-        );
+    let report = opp.validation_report().await;
 
-        return Ok(error(StatusCode::BadRequest, err.to_string()));
+    if !report.is_valid() {
+        return invalid(req.state(), &partner, &opp, report).await;
     }
 
     let db = req.state();
@@ -93,7 +121,10 @@ async fn opportunity_new(mut req: tide::Request<Database>) -> tide::Result {
         ));
     }
 
-    if let Err(err) = opp.store(db).await {
+    if let Err(err) = opp
+        .store_by(db, &RevisionAuthor::Partner(auth), "api")
+        .await
+    {
         return Ok(error(StatusCode::BadRequest, err.to_string()));
     }
 
@@ -108,6 +139,391 @@ async fn opportunity_new(mut req: tide::Request<Database>) -> tide::Result {
     Ok(res)
 }
 
+/// Logs the errors in the report for the partner, and responds with
+/// the whole report
+async fn invalid(
+    db: &Database,
+    partner: &Partner,
+    opp: &Opportunity,
+    report: ValidationReport,
+) -> tide::Result {
+    log_issues(db, partner, opp, &report).await?;
+
+    let message = report
+        .errors()
+        .next()
+        .map(|issue| issue.message.clone())
+        .unwrap_or_default();
+
+    Ok(error_with(
+        StatusCode::BadRequest,
+        &ErrorBody {
+            error: message,
+            issues: report.issues,
+        },
+    ))
+}
+
+async fn log_issues(
+    db: &Database,
+    partner: &Partner,
+    opp: &Opportunity,
+    report: &ValidationReport,
+) -> tide::Result<()> {
+    for issue in report.errors() {
+        let mut logged = issue.logged_error(&opp.exterior.title);
+        logged.partner_id = partner.id.unwrap_or_default();
+        println!("Logged error {}: {}", logged.store(db).await?, logged);
+    }
+
+    Ok(())
+}
+
+/// Checks an opportunity document the way creating it would, and
+/// responds with everything that's wrong with it without storing
+/// anything
+async fn opportunity_validate(mut req: tide::Request<Database>) -> tide::Result {
+    let auth = match header_check(&req, &super::API_AUDIENCE) {
+        Ok(x) => match x {
+            Some(auth) => auth,
+            None => return Ok(error(StatusCode::Unauthorized, "Authorization is required")),
+        },
+        Err(res) => return Ok(res),
+    };
+
+    let body = req.body_bytes().await?;
+
+    let mut opp: Opportunity = match serde_json::from_slice(&body) {
+        Ok(data) => data,
+        Err(err) => {
+            let mut report = ValidationReport::default();
+            report.error("$", IssueCode::Malformed, err.to_string());
+            return success(&json!({ "valid": false, "issues": report.issues }));
+        }
+    };
+
+    opp.exterior.partner = auth;
+
+    let mut report = opp.validation_report().await;
+
+    if let EntityType::Page(_) = opp.exterior.entity_type {
+        report.error(
+            "$.entity_type",
+            IssueCode::NotAllowed,
+            "Page entities can not be created via the API",
+        );
+    }
+
+    success(&json!({
+        "valid": report.is_valid(),
+        "uid": opp.exterior.uid,
+        "issues": report.issues,
+    }))
+}
+
+#[derive(serde::Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum BatchStatus {
+    Created,
+    Updated,
+    Invalid,
+    NotFound,
+    Forbidden,
+    #[default]
+    Error,
+}
+
+/// The outcome for one entry of a batch request
+#[derive(serde::Serialize, Default)]
+struct BatchResult {
+    index: usize,
+    uid: Option<Uuid>,
+    status: BatchStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    issues: Vec<ValidationIssue>,
+}
+
+impl BatchResult {
+    fn failed(index: usize, uid: Option<Uuid>, status: BatchStatus, error: String) -> Self {
+        BatchResult {
+            index,
+            uid,
+            status,
+            error: Some(error),
+            ..Default::default()
+        }
+    }
+}
+
+fn batch_too_large() -> Response {
+    error(
+        StatusCode::PayloadTooLarge,
+        format!("A batch may contain at most {} entries", BATCH_LIMIT),
+    )
+}
+
+/// Reads the body of a batch request as a list of at most
+/// BATCH_LIMIT entries
+async fn batch_body<T>(req: &mut tide::Request<Database>) -> Result<Vec<T>, Response>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let entries: Vec<T> = match req.body_json().await {
+        Ok(entries) => entries,
+        Err(err) => return Err(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    if entries.len() > BATCH_LIMIT {
+        return Err(batch_too_large());
+    }
+
+    Ok(entries)
+}
+
+/// Creates or replaces each opportunity in a list, matching existing
+/// opportunities by uid (which is derived from partner_name and title
+/// when it isn't given). One entry failing doesn't stop the others
+/// from being stored, so the response lists the outcome of each.
+async fn opportunity_batch(mut req: tide::Request<Database>) -> tide::Result {
+    let auth = match header_check(&req, &super::API_AUDIENCE) {
+        Ok(x) => match x {
+            Some(auth) => auth,
+            None => return Ok(error(StatusCode::Unauthorized, "Authorization is required")),
+        },
+        Err(res) => return Ok(res),
+    };
+
+    let entries: Vec<serde_json::Value> = match batch_body(&mut req).await {
+        Ok(entries) => entries,
+        Err(res) => return Ok(res),
+    };
+
+    let db = req.state();
+    let partner = Partner::load_by_uid(db, &auth).await?;
+    let mut results = Vec::with_capacity(entries.len());
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        results.push(batch_upsert(db, &partner, auth, index, entry).await?);
+    }
+
+    success(&json!({ "results": results }))
+}
+
+async fn batch_upsert(
+    db: &Database,
+    partner: &Partner,
+    auth: Uuid,
+    index: usize,
+    entry: serde_json::Value,
+) -> tide::Result<BatchResult> {
+    let mut opp: Opportunity = match serde_json::from_value(entry.clone()) {
+        Ok(data) => data,
+        Err(err) => {
+            println!(
+                "Logged error {}: {}",
+                partner
+                    .log_error(
+                        db,
+                        LoggedErrorLevel::Error,
+                        err.to_string(),
+                        Option::<&str>::None,
+                        Some(entry.to_string())
+                    )
+                    .await?,
+                err
+            );
+
+            let mut report = ValidationReport::default();
+            report.error("$", IssueCode::Malformed, err.to_string());
+
+            return Ok(BatchResult {
+                index,
+                status: BatchStatus::Invalid,
+                error: Some(err.to_string()),
+                issues: report.issues,
+                ..Default::default()
+            });
+        }
+    };
+
+    opp.exterior.partner = auth;
+
+    let mut report = opp.validation_report().await;
+
+    if let EntityType::Page(_) = opp.exterior.entity_type {
+        report.error(
+            "$.entity_type",
+            IssueCode::NotAllowed,
+            "Page entities can not be created via the API",
+        );
+    }
+
+    let uid = opp.exterior.uid;
+
+    if !report.is_valid() {
+        log_issues(db, partner, &opp, &report).await?;
+
+        let message = report.errors().next().map(|issue| issue.message.clone());
+
+        return Ok(BatchResult {
+            index,
+            uid: Some(uid),
+            status: BatchStatus::Invalid,
+            error: message,
+            issues: report.issues,
+        });
+    }
+
+    let created = match Opportunity::load_by_uid(db, &uid).await {
+        Ok(old_opp) => {
+            if old_opp.exterior.partner != auth {
+                return Ok(BatchResult::failed(
+                    index,
+                    Some(uid),
+                    BatchStatus::Forbidden,
+                    "Not authorized to edit that opportunity".to_string(),
+                ));
+            }
+
+            opp.id = old_opp.id;
+            opp.interior.accepted = old_opp.interior.accepted;
+            false
+        }
+        Err(_) => {
+            opp.interior.accepted = Some(true); // Policy now to trust partners by default
+            true
+        }
+    };
+
+    if let Err(err) = opp
+        .store_by(db, &RevisionAuthor::Partner(auth), "api")
+        .await
+    {
+        println!(
+            "Logged error {}: {}",
+            partner
+                .log_error(
+                    db,
+                    LoggedErrorLevel::Error,
+                    err.to_string(),
+                    Some(&opp.exterior.title),
+                    Some(entry.to_string()),
+                )
+                .await?,
+            err
+        );
+
+        return Ok(BatchResult::failed(
+            index,
+            Some(uid),
+            BatchStatus::Error,
+            err.to_string(),
+        ));
+    }
+
+    OpportunityImportRecord::store(db, &auth, &uid, created, false).await?;
+
+    Ok(BatchResult {
+        index,
+        uid: Some(uid),
+        status: if created {
+            BatchStatus::Created
+        } else {
+            BatchStatus::Updated
+        },
+        issues: report.issues,
+        ..Default::default()
+    })
+}
+
+#[derive(serde::Deserialize)]
+struct BulkStatus {
+    uids: Vec<Uuid>,
+    withdrawn: bool,
+}
+
+/// Withdraws, or un-withdraws, each of a list of the partner's
+/// opportunities
+async fn opportunity_bulk_status(mut req: tide::Request<Database>) -> tide::Result {
+    let auth = match header_check(&req, &super::API_AUDIENCE) {
+        Ok(x) => match x {
+            Some(auth) => auth,
+            None => return Ok(error(StatusCode::Unauthorized, "Authorization is required")),
+        },
+        Err(res) => return Ok(res),
+    };
+
+    let BulkStatus { uids, withdrawn } = match req.body_json().await {
+        Ok(body) => body,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    if uids.len() > BATCH_LIMIT {
+        return Ok(batch_too_large());
+    }
+
+    let db = req.state();
+    let mut results = Vec::with_capacity(uids.len());
+
+    for (index, uid) in uids.into_iter().enumerate() {
+        let mut opp = match Opportunity::load_by_uid(db, &uid).await {
+            Ok(opp) => opp,
+            Err(_) => {
+                results.push(BatchResult::failed(
+                    index,
+                    Some(uid),
+                    BatchStatus::NotFound,
+                    "Could not load opportunity with that uid".to_string(),
+                ));
+                continue;
+            }
+        };
+
+        if opp.exterior.partner != auth {
+            results.push(BatchResult::failed(
+                index,
+                Some(uid),
+                BatchStatus::Forbidden,
+                "Not authorized to edit that opportunity".to_string(),
+            ));
+            continue;
+        }
+
+        opp.interior.withdrawn = withdrawn;
+
+        if let Err(err) = opp
+            .store_by(db, &RevisionAuthor::Partner(auth), "api")
+            .await
+        {
+            results.push(BatchResult::failed(
+                index,
+                Some(uid),
+                BatchStatus::Error,
+                err.to_string(),
+            ));
+            continue;
+        }
+
+        OpportunityImportRecord::store(db, &auth, &uid, false, false).await?;
+
+        results.push(BatchResult {
+            index,
+            uid: Some(uid),
+            status: BatchStatus::Updated,
+            ..Default::default()
+        });
+    }
+
+    success(&json!({ "results": results }))
+}
+
+#[derive(serde::Deserialize)]
+struct SearchCursor {
+    cursor: Option<String>,
+}
+
 async fn opportunity_search(req: tide::Request<Database>) -> tide::Result {
     let auth = match header_check(&req, &super::API_AUDIENCE) {
         Ok(x) => x,
@@ -116,18 +532,62 @@ async fn opportunity_search(req: tide::Request<Database>) -> tide::Result {
 
     let mut query: OpportunityQuery = req.query()?;
 
-    if auth.is_some() && query.partner == auth {
+    restrict_query(&auth, &mut query);
+
+    query.language = requested_language(&req);
+
+    let SearchCursor { cursor } = req.query()?;
+
+    // Results are only paged when the client asks for it, so that
+    // existing clients continue to receive every match
+    let pagination = if cursor.is_some() || query.per_page.is_some() {
+        Pagination::Cursor {
+            cursor: cursor.filter(|c| !c.is_empty()),
+            size: query.per_page.unwrap_or(SEARCH_PAGE_SIZE).into(),
+        }
+    } else {
+        Pagination::All
+    };
+
+    let db = req.state();
+
+    let (matches, cursors) = match Opportunity::load_matching_refs_page(
+        db,
+        &query,
+        query.sort.unwrap_or(OpportunityQueryOrdering::Alphabetical),
+        pagination,
+    )
+    .await
+    {
+        Ok(found) => found,
+        Err(err @ (common::model::Error::OutOfBounds(_) | common::model::Error::Value(_))) => {
+            return Ok(error(StatusCode::BadRequest, err.to_string()))
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(Response::builder(StatusCode::Ok)
+        .content_type(mime::JSON)
+        .body(json!({ "matches": matches, "next": cursors.next, "prev": cursors.prev }))
+        .build())
+}
+
+/// Narrows a query received via the API to what the requester is
+/// allowed to see
+pub(super) fn restrict_query(auth: &Option<Uuid>, query: &mut OpportunityQuery) {
+    if auth.is_some() && query.partner == *auth {
         // Request is authenticated and the authenticated partner
         // is the target of the query, so we allow searches to
         // include non-accepted and withdrawn opportunities.
     } else {
         query.accepted = Some(true);
         query.withdrawn = Some(false);
+        query.trashed = None;
     }
 
     // Filter out EntityType::Page entries, even if they were
     // requested. They are not meant to be addressed via the API.
-    if let Some(requested) = query.entity_type {
+    if let Some(requested) = query.entity_type.take() {
         query.entity_type = Some(
             requested
                 .into_iter()
@@ -143,46 +603,103 @@ async fn opportunity_search(req: tide::Request<Database>) -> tide::Result {
     } else {
         query.entity_type = Some(vec![EntityType::Opportunity, EntityType::Attraction])
     }
+}
 
-    let db = req.state();
+#[derive(serde::Deserialize)]
+struct OccurrencesWindow {
+    begin: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    timezone: Option<String>,
+}
 
-    let matches = Opportunity::load_matching_refs(
-        db,
+async fn opportunity_occurrences(req: tide::Request<Database>) -> tide::Result {
+    let auth = match header_check(&req, &super::API_AUDIENCE) {
+        Ok(x) => x,
+        Err(res) => return Ok(res),
+    };
+
+    let window: OccurrencesWindow = match req.query() {
+        Ok(w) => w,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    let mut query: OpportunityQuery = req.query()?;
+
+    restrict_query(&auth, &mut query);
+
+    let found = match Opportunity::load_occurrences_between(
+        req.state(),
         &query,
-        OpportunityQueryOrdering::Alphabetical,
-        Pagination::All,
+        &window.begin,
+        &window.end,
+        window.timezone.as_deref(),
     )
-    .await?;
+    .await
+    {
+        Ok(found) => found,
+        Err(err @ (common::model::Error::OutOfBounds(_) | common::model::Error::Value(_))) => {
+            return Ok(error(StatusCode::BadRequest, err.to_string()))
+        }
+        Err(err) => return Err(err.into()),
+    };
 
     Ok(Response::builder(StatusCode::Ok)
         .content_type(mime::JSON)
-        .body(json!({ "matches": matches }))
+        .body(serde_json::to_value(found)?)
         .build())
 }
 
 #[derive(serde::Deserialize)]
 struct RecommendQuery {
-    _tags: Option<Vec<String>>,
-    _topics: Option<Vec<common::model::opportunity::Topic>>,
-    r#_abstract: Option<String>,
-    _longitude: Option<f32>,
-    _latitude: Option<f32>,
+    tags: Option<Vec<String>>,
+    topics: Option<Vec<Topic>>,
+    r#abstract: Option<String>,
+    longitude: Option<f64>,
+    latitude: Option<f64>,
+    page: Option<u32>,
+    per_page: Option<u8>,
 }
 
 async fn opportunity_recommend(req: tide::Request<Database>) -> tide::Result {
-    let _auth = match header_check(&req, &super::API_AUDIENCE) {
-        Ok(x) => x,
-        Err(res) => return Ok(res),
+    if let Err(res) = header_check(&req, &super::API_AUDIENCE) {
+        return Ok(res);
+    }
+
+    let query: RecommendQuery = match req.query() {
+        Ok(query) => query,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
     };
 
-    let mut _query: RecommendQuery = req.query()?;
+    let interests = Interests {
+        tags: query.tags.unwrap_or_default(),
+        topics: query.topics.unwrap_or_default(),
+        text: query.r#abstract,
+        longitude: query.longitude,
+        latitude: query.latitude,
+    };
 
-    // !!! TODO
+    let pagination = Pagination::Page {
+        index: query.page.unwrap_or(0),
+        size: query
+            .per_page
+            .unwrap_or(RECOMMEND_PAGE_SIZE)
+            .clamp(1, SEARCH_PAGE_SIZE)
+            .into(),
+    };
 
-    Ok(Response::builder(StatusCode::Ok)
-        .content_type(mime::JSON)
-        .body(json!({ "recommended": [] }))
-        .build())
+    let (recommended, total) = Opportunity::recommend(req.state(), &interests, &pagination).await?;
+
+    let (page_index, last_page, per_page) = pagination.expand(total);
+
+    success(&json!({
+        "recommended": recommended,
+        "pagination": {
+            "page_index": page_index,
+            "per_page": per_page,
+            "last_page": last_page,
+            "total": total,
+        },
+    }))
 }
 
 async fn opportunity_get(req: tide::Request<Database>) -> tide::Result {
@@ -213,9 +730,30 @@ async fn opportunity_get(req: tide::Request<Database>) -> tide::Result {
         }
     };
 
-    match (auth, opp.interior.withdrawn) {
-        (Some(auth), _) if auth == opp.exterior.partner => success(&opp),
-        (_, false) => success(&opp.exterior),
+    let hidden = opp.interior.withdrawn || opp.interior.trashed_at.is_some();
+
+    match (auth, hidden) {
+        (Some(auth), _) if auth == opp.exterior.partner => {
+            let etag = opportunity_tag(&opp);
+
+            let mut res = if not_modified(&req, &etag, None) {
+                Response::new(StatusCode::NotModified)
+            } else {
+                success(&opp)?
+            };
+
+            res.insert_header("ETag", etag);
+            Ok(res)
+        }
+        (_, false) => {
+            let mut exterior = opp.exterior;
+
+            if let Some(language) = requested_language(&req) {
+                exterior.localize(&common::language_preferences(&language));
+            }
+
+            success(&exterior)
+        }
         _ => Ok(error(
             StatusCode::NotFound,
             "Could not load opportunity with that id",
@@ -263,10 +801,70 @@ async fn opportunity_put(mut req: tide::Request<Database>) -> tide::Result {
         return Ok(error(StatusCode::Conflict, "uid mismatch"));
     }
 
+    if precondition_failed(&req, &opportunity_tag(&old_opp)) {
+        return Ok(stale());
+    }
+
+    let partner = Partner::load_by_uid(req.state(), &auth).await?;
+    let body = req.body_bytes().await?;
+
+    let new_opp: Opportunity = match serde_json::from_slice(&body) {
+        Ok(data) => data,
+        Err(err) => {
+            println!(
+                "Logged error {}: {}",
+                partner
+                    .log_error(
+                        req.state(),
+                        LoggedErrorLevel::Error,
+                        err.to_string(),
+                        Some(&old_opp.exterior.title),
+                        Some(String::from_utf8_lossy(&body))
+                    )
+                    .await?,
+                err
+            );
+
+            return Ok(error(StatusCode::BadRequest, err.to_string()));
+        }
+    };
+
+    replace_opportunity(req.state(), &partner, &old_opp, new_opp, &body).await
+}
+
+/// Changes the fields named in an RFC 7396 JSON merge patch, leaving
+/// the rest of the opportunity as it was
+async fn opportunity_patch(mut req: tide::Request<Database>) -> tide::Result {
+    let (auth, old_opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    if let Some(content_type) = req.content_type() {
+        if content_type.essence() != "application/merge-patch+json"
+            && content_type.essence() != mime::JSON.essence()
+        {
+            return Ok(error(
+                StatusCode::UnsupportedMediaType,
+                "The patch must be application/merge-patch+json",
+            ));
+        }
+    }
+
+    if precondition_failed(&req, &opportunity_tag(&old_opp)) {
+        return Ok(stale());
+    }
+
     let partner = Partner::load_by_uid(req.state(), &auth).await?;
     let body = req.body_bytes().await?;
 
-    let mut new_opp: Opportunity = match serde_json::from_slice(&body) {
+    let patched = serde_json::from_slice(&body).and_then(|patch| {
+        let mut doc = serde_json::to_value(&old_opp)?;
+        merge_patch(&mut doc, &patch);
+        serde_json::from_value::<Opportunity>(doc)
+    });
+
+    let new_opp = match patched {
         Ok(data) => data,
         Err(err) => {
             println!(
@@ -287,6 +885,53 @@ async fn opportunity_put(mut req: tide::Request<Database>) -> tide::Result {
         }
     };
 
+    replace_opportunity(req.state(), &partner, &old_opp, new_opp, &body).await
+}
+
+/// Applies an RFC 7396 JSON merge patch to `target`. Members of the
+/// patch which are null are removed from the target, objects are
+/// merged recursively, and anything else replaces what was there.
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = json!({});
+    }
+
+    if let serde_json::Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(
+                    target.entry(key.clone()).or_insert(serde_json::Value::Null),
+                    value,
+                );
+            }
+        }
+    }
+}
+
+fn stale() -> Response {
+    error(
+        StatusCode::PreconditionFailed,
+        "The opportunity has changed since the version named in If-Match",
+    )
+}
+
+/// Stores `new_opp` in place of `old_opp`, keeping the fields a
+/// partner isn't allowed to change, and responds with the stored
+/// opportunity and its new entity tag
+async fn replace_opportunity(
+    db: &Database,
+    partner: &Partner,
+    old_opp: &Opportunity,
+    mut new_opp: Opportunity,
+    body: &[u8],
+) -> tide::Result {
     if let EntityType::Page(_) = new_opp.exterior.entity_type {
         return Ok(error(
             StatusCode::BadRequest,
@@ -294,23 +939,29 @@ async fn opportunity_put(mut req: tide::Request<Database>) -> tide::Result {
         ));
     }
 
-    let db = req.state();
-
     new_opp.id = old_opp.id;
     new_opp.interior.accepted = old_opp.interior.accepted;
-    new_opp.exterior.partner = auth;
-    new_opp.exterior.uid = uid;
+    new_opp.exterior.partner = old_opp.exterior.partner;
+    new_opp.exterior.uid = old_opp.exterior.uid;
+
+    let report = new_opp.validation_report().await;
+
+    if !report.is_valid() {
+        return invalid(db, partner, &new_opp, report).await;
+    }
 
-    if let Err(err) = new_opp.store(db).await {
+    let author = RevisionAuthor::Partner(new_opp.exterior.partner);
+
+    if let Err(err) = new_opp.store_by(db, &author, "api").await {
         println!(
             "Logged error {}: {}",
             partner
                 .log_error(
-                    req.state(),
+                    db,
                     LoggedErrorLevel::Error,
                     err.to_string(),
                     Some(&new_opp.exterior.title),
-                    Some(String::from_utf8_lossy(&body)),
+                    Some(String::from_utf8_lossy(body)),
                 )
                 .await?,
             err
@@ -328,5 +979,329 @@ async fn opportunity_put(mut req: tide::Request<Database>) -> tide::Result {
     )
     .await?;
 
-    success(&new_opp)
+    // Reloaded so that the tag describes what was actually stored
+    let stored = Opportunity::load_by_uid(db, &new_opp.exterior.uid).await?;
+
+    let mut res = success(&stored)?;
+    res.insert_header("ETag", opportunity_tag(&stored));
+    Ok(res)
+}
+
+/// Moves the opportunity to the trash, from which an administrator
+/// can restore it until it is purged
+async fn opportunity_delete(req: tide::Request<Database>) -> tide::Result {
+    let (auth, mut opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    opp.trash(req.state(), &RevisionAuthor::Partner(auth))
+        .await?;
+
+    success(&opp)
+}
+
+/// Loads the opportunity identified in the request path, as long as
+/// it belongs to the partner the request is authorized for
+async fn partner_opportunity(
+    req: &tide::Request<Database>,
+) -> Result<(Uuid, Opportunity), Response> {
+    let auth = match header_check(req, &super::API_AUDIENCE)? {
+        Some(auth) => auth,
+        None => return Err(error(StatusCode::Unauthorized, "Authorization is required")),
+    };
+
+    let uid: Uuid = match req.param("uid").map(|p| p.parse()) {
+        Ok(Ok(uid)) => uid,
+        _ => {
+            return Err(error(
+                StatusCode::BadRequest,
+                "Unable to parse a UUID from the request path",
+            ));
+        }
+    };
+
+    let opp = match Opportunity::load_by_uid(req.state(), &uid).await {
+        Ok(opp) => opp,
+        Err(_) => {
+            return Err(error(
+                StatusCode::NotFound,
+                "Could not load opportunity with that uid",
+            ));
+        }
+    };
+
+    if auth != opp.exterior.partner {
+        return Err(error(
+            StatusCode::Forbidden,
+            "Not authorized to access that opportunity",
+        ));
+    }
+
+    Ok((auth, opp))
+}
+
+async fn opportunity_revisions(req: tide::Request<Database>) -> tide::Result {
+    let (_, opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    let revisions = Opportunity::load_revisions(req.state(), &opp.exterior.uid).await?;
+
+    success(&json!({ "revisions": revisions }))
+}
+
+#[derive(serde::Deserialize)]
+struct RevisionDiffQuery {
+    from: i32,
+    to: i32,
+}
+
+async fn opportunity_revision_diff(req: tide::Request<Database>) -> tide::Result {
+    let (_, opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    let query: RevisionDiffQuery = match req.query() {
+        Ok(q) => q,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    let db = req.state();
+
+    let (from, to) = match (
+        Opportunity::load_revision(db, &opp.exterior.uid, query.from).await,
+        Opportunity::load_revision(db, &opp.exterior.uid, query.to).await,
+    ) {
+        (Ok(from), Ok(to)) => (from, to),
+        _ => return Ok(error(StatusCode::NotFound, "No such revision")),
+    };
+
+    success(&json!({
+        "from": from.id,
+        "to": to.id,
+        "changes": from.diff(&to),
+    }))
+}
+
+async fn opportunity_rollback(req: tide::Request<Database>) -> tide::Result {
+    let (auth, mut opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    let revision_id: i32 = match req.param("revision")?.parse() {
+        Ok(id) => id,
+        Err(_) => return Ok(error(StatusCode::BadRequest, "Invalid revision id")),
+    };
+
+    let db = req.state();
+
+    let revision = match Opportunity::load_revision(db, &opp.exterior.uid, revision_id).await {
+        Ok(revision) => revision,
+        Err(_) => return Ok(error(StatusCode::NotFound, "No such revision")),
+    };
+
+    // Partners can restore their own content, but not whether it has
+    // been accepted
+    let accepted = opp.interior.accepted;
+
+    if let Err(err) = opp.restore(&revision) {
+        return Ok(error(StatusCode::BadRequest, err.to_string()));
+    }
+
+    opp.interior.accepted = accepted;
+    opp.exterior.partner = auth;
+
+    // The revision may predate the current validation rules
+    let report = opp.validation_report().await;
+
+    if !report.is_valid() {
+        let partner = Partner::load_by_uid(db, &auth).await?;
+        return invalid(db, &partner, &opp, report).await;
+    }
+
+    if let Err(err) = opp
+        .store_by(db, &RevisionAuthor::Partner(auth), "rollback")
+        .await
+    {
+        return Ok(error(StatusCode::BadRequest, err.to_string()));
+    }
+
+    success(&opp)
+}
+
+#[derive(serde::Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum AttendeesFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(serde::Deserialize)]
+struct AttendeesQuery {
+    occurrence: Option<DateTime<FixedOffset>>,
+    #[serde(default)]
+    format: AttendeesFormat,
+}
+
+async fn opportunity_attendees(req: tide::Request<Database>) -> tide::Result {
+    let (_, opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    let query: AttendeesQuery = match req.query() {
+        Ok(q) => q,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    let attendees = opp.attendees(req.state(), query.occurrence).await?;
+
+    match query.format {
+        AttendeesFormat::Json => success(&json!({ "attendees": attendees })),
+        AttendeesFormat::Csv => {
+            let mut out = csv::Writer::from_writer(Vec::new());
+
+            for row in attendees {
+                out.serialize(row)?;
+            }
+
+            out.flush()?;
+
+            Ok(Response::builder(StatusCode::Ok)
+                .content_type("text/csv")
+                .header(
+                    "Content-Disposition",
+                    format!(
+                        "attachment; filename=\"{}-attendees.csv\"",
+                        opp.exterior.slug
+                    ),
+                )
+                .body(out.into_inner()?)
+                .build())
+        }
+    }
+}
+
+async fn opportunity_translations(req: tide::Request<Database>) -> tide::Result {
+    let (_, opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    success(&opp.exterior.translations)
+}
+
+async fn opportunity_translation_put(mut req: tide::Request<Database>) -> tide::Result {
+    let (auth, mut opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    let language = req.param("language")?.to_string();
+    let body = req.body_bytes().await?;
+
+    let translation: Translation = match serde_json::from_slice(&body) {
+        Ok(translation) => translation,
+        Err(err) => return Ok(error(StatusCode::BadRequest, err.to_string())),
+    };
+
+    opp.exterior.translations.insert(language, translation);
+
+    if let Err(err) = opp
+        .store_by(req.state(), &RevisionAuthor::Partner(auth), "api")
+        .await
+    {
+        return Ok(error(StatusCode::BadRequest, err.to_string()));
+    }
+
+    success(&opp.exterior.translations)
+}
+
+async fn opportunity_translation_delete(req: tide::Request<Database>) -> tide::Result {
+    let (auth, mut opp) = match partner_opportunity(&req).await {
+        Ok(found) => found,
+        Err(res) => return Ok(res),
+    };
+
+    let language = req.param("language")?;
+
+    let before = opp.exterior.translations.len();
+
+    opp.exterior
+        .translations
+        .retain(|code, _| !code.eq_ignore_ascii_case(language));
+
+    if opp.exterior.translations.len() == before {
+        return Ok(error(StatusCode::NotFound, "No such translation"));
+    }
+
+    opp.store_by(req.state(), &RevisionAuthor::Partner(auth), "api")
+        .await?;
+
+    success(&opp.exterior.translations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_patch_follows_rfc_7396() {
+        // Examples from appendix A of the RFC
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (
+                json!({"a": "b", "b": "c"}),
+                json!({"a": null}),
+                json!({"b": "c"}),
+            ),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (
+                json!({"a": [{"b": "c"}]}),
+                json!({"a": [1]}),
+                json!({"a": [1]}),
+            ),
+            (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+            (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+            (json!({"a": "foo"}), json!(null), json!(null)),
+            (json!({"a": "foo"}), json!("bar"), json!("bar")),
+            (
+                json!({"e": null}),
+                json!({"a": 1}),
+                json!({"e": null, "a": 1}),
+            ),
+            (
+                json!([1, 2]),
+                json!({"a": "b", "c": null}),
+                json!({"a": "b"}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ];
+
+        for (mut target, patch, expected) in cases {
+            merge_patch(&mut target, &patch);
+            assert_eq!(target, expected, "patch {}", patch);
+        }
+    }
 }
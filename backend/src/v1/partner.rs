@@ -5,10 +5,15 @@ use tide::prelude::*;
 use tide_fluent_routes::prelude::*;
 use uuid::Uuid;
 
-use super::{error, issue_jwt, success};
+use super::{error, feed, issue_jwt, success};
 
 pub fn routes(routes: RouteSegment<Database>) -> RouteSegment<Database> {
-    routes.at("authorize", |r| r.post(partner_authorize))
+    routes
+        .at("authorize", |r| r.post(partner_authorize))
+        .at(":uid", |r| {
+            r.at("feed.ics", |r| r.get(feed::partner_ical))
+                .at("feed.atom", |r| r.get(feed::partner_atom))
+        })
 }
 
 #[derive(Debug, Serialize, Deserialize)]